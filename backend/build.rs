@@ -2,7 +2,7 @@ use std::process::Command;
 
 fn main() {
     // Only build the frontend in release mode to prevent "cargo check" from being blocked.
-    if std::env::var("PROFILE").map_or(false, |profile| profile.to_lowercase() == "release") {
+    if std::env::var("PROFILE").is_ok_and(|profile| profile.to_lowercase() == "release") {
         let status_install = Command::new("npm")
             .arg("install")
             .current_dir("../frontend/")
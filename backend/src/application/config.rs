@@ -12,6 +12,8 @@ const DEFAULT_FILE_APPLICATION_CONFIGURATION: &str = "configuration";
 const DEFAULT_FOLDER_APPLICATION_BACKUP: &str = "backups";
 /// The maximum number of backups stored.
 const DEFAULT_MAXIMUM_STORED_BACKUPS: usize = 3;
+/// The default base currency all amounts are converted to.
+const DEFAULT_BASE_CURRENCY: &str = "EUR";
 /// The default log level.
 const DEFAULT_LOG_LEVEL: log::Level = log::Level::Warn;
 /// The name of the default database file.
@@ -25,7 +27,7 @@ const DEFAULT_CONFIG_SERVER_ADDRESS: &str = "127.0.0.1";
 /// The default server port.
 const DEFAULT_CONFIG_SERVER_PORT: &str = "8080";
 /// The context for UUID generation.
-const UUID_CONTEXT: Context = Context::new(0);
+static UUID_CONTEXT: Context = Context::new(0);
 /// The node ID for UUID generation.
 const UUID_NODE_ID: &[u8; 6] = &[12, 21, 33, 4, 35, 116];
 /// The available thumbnail widths.
@@ -33,11 +35,12 @@ const THUMBNAIL_WIDTHS: &[u32; 7] = &[0, 100, 200, 400, 600, 800, 1000];
 
 use std::{
     fs::{File, OpenOptions},
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
     time::SystemTime,
 };
 
+use log::{info, warn};
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use uuid::{
@@ -45,9 +48,15 @@ use uuid::{
     Uuid,
 };
 
+use crate::service::{
+    backup_service::BackupService,
+    currency_service::validate_currency_code,
+    migration_service::{migrate_database, requires_backup},
+};
+
 use super::error::HomeworkError;
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 /// A configuration that defines basic parameters of the application.
 pub struct Configuration {
     server_address: Option<String>,
@@ -57,6 +66,9 @@ pub struct Configuration {
     log_level: Option<String>,
     backup_path: Option<String>,
     maximum_backups: Option<usize>,
+    base_currency: Option<String>,
+    backup_passphrase: Option<String>,
+    backup_key_file: Option<String>,
 }
 
 impl Configuration {
@@ -74,6 +86,7 @@ impl Configuration {
         }
         let file = OpenOptions::new()
             .create(true)
+            .truncate(true)
             .append(false)
             .write(true)
             .open(path)?;
@@ -87,114 +100,55 @@ impl Configuration {
         Ok(connection)
     }
 
-    pub fn initialise_database() -> Result<(), HomeworkError> {
-        let connection = Configuration::database_connection()?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS attachment (
-                id              TEXT PRIMARY KEY,
-                name            TEXT NOT NULL,
-                creation_time   TEXT NOT NULL
-                    )",
-            [],
-        )?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS recipe (
-                id              BLOB PRIMARY KEY,
-                title           TEXT NOT NULL,
-                instructions    TEXT NOT NULL,                    
-                reference       TEXT NOT NULL,
-                rating          RATING NOT NULL,
-                creation_time   TEXT NOT NULL,
-                thumbnail       BLOB,
-                FOREIGN KEY (thumbnail) REFERENCES attachment (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE SET NULL
-            )",
-            [],
-        )?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS ingredient (
-                    id                              BLOB PRIMARY KEY,
-                    amount                          TEXT NOT NULL,
-                    unit                            TEXT NOT NULL,                    
-                    text                            TEXT NOT NULL,
-                    creation_time                   TEXT NOT NULL,
-                    recipe_reference                BLOB,
-                    recipe_id                       BLOB NOT NULL,
-                    ordering                        INTEGER NOT NULL,
-                    filter_text                     TEXT,
-                    FOREIGN KEY (recipe_id) REFERENCES recipe (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE,
-                    FOREIGN KEY (recipe_reference) REFERENCES recipe (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE SET NULL
-                      )",
-            [],
-        )?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS payment (
-                    id                              BLOB PRIMARY KEY,
-                    target                          TEXT NOT NULL,
-                    note                            TEXT NOT NULL,
-                    paid                            TEXT NOT NULL,                    
-                    involved                        TEXT NOT NULL,
-                    payment_type                    TEXT NOT NULL,
-                    creation_time                   TEXT NOT NULL
-                      )",
-            [],
-        )?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS attachment_recipe_mapping (
-                    id                          INTEGER PRIMARY KEY,
-                    recipe_id                   BLOB NOT NULL,
-                    attachment_id               BLOB NOT NULL,
-                    FOREIGN KEY (recipe_id)     REFERENCES recipe (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE,
-                    FOREIGN KEY (attachment_id) REFERENCES attachment (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE
-                      )",
-            [],
-        )?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS tag_recipe_mapping (
-                    id                      INTEGER PRIMARY KEY,
-                    tag                     TEXT NOT NULL,
-                    recipe_id               BLOB NOT NULL,
-                    FOREIGN KEY (recipe_id) REFERENCES recipe (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE
-                      )",
-            [],
-        )?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS attachment_payment_mapping (
-                    id                          INTEGER PRIMARY KEY,
-                    payment_id                  BLOB NOT NULL,
-                    attachment_id               BLOB NOT NULL,
-                    FOREIGN KEY (payment_id)       REFERENCES payment (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE,
-                    FOREIGN KEY (attachment_id) REFERENCES attachment (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE
-                      )",
-            [],
-        )?;
-        connection.execute(
-            "CREATE TABLE IF NOT EXISTS tag_payment_mapping (
-                    id                          INTEGER PRIMARY KEY,
-                    tag                         TEXT NOT NULL,
-                    payment_id                  BLOB NOT NULL,
-                    FOREIGN KEY (payment_id)    REFERENCES payment (id) 
-                        ON UPDATE CASCADE
-                        ON DELETE CASCADE
-                      )",
-            [],
-        )?;
-        Ok(())
+    #[cfg(test)]
+    /// Opens an in-memory database with the latest schema for tests.
+    pub fn in_memory_database_connection() -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+        Configuration::default()
+            .initialise_database_schema(&connection)
+            .unwrap();
+        connection
+    }
+
+    /// Migrates the database schema to the latest version and the data of databases
+    /// created by earlier versions of the application. Databases of an older schema
+    /// version are backed up before, databases of a newer version are rejected.
+    ///
+    /// # Parameters
+    ///
+    /// * `backup_service` - the [`BackupService`] creating the backup before a migration
+    pub fn initialise_database(&self, backup_service: &BackupService) -> Result<(), HomeworkError> {
+        if requires_backup(&Configuration::database_connection()?)? {
+            info!("Backing up the database before migrating its schema.");
+            backup_service.create_backup()?;
+        }
+        self.upgrade_database()
+    }
+
+    /// Migrates the database schema and data to the latest version without backing
+    /// the database up before.
+    pub fn upgrade_database(&self) -> Result<(), HomeworkError> {
+        self.upgrade_database_file(&Configuration::application_database_file_path())
+    }
+
+    /// Migrates the database at the specified path like [`Configuration::upgrade_database`].
+    ///
+    /// # Parameters
+    ///
+    /// * `database_path` - the path of the database file
+    pub fn upgrade_database_file(&self, database_path: &Path) -> Result<(), HomeworkError> {
+        let connection = Connection::open(database_path)?;
+        connection.execute("PRAGMA foreign_keys = ON;", [])?;
+        self.initialise_database_schema(&connection)
+    }
+
+    /// Migrates the database schema of the specified connection to the latest version.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn initialise_database_schema(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        migrate_database(connection, self)
     }
 
     /// Checks if the configuration exists as a physical file.
@@ -207,7 +161,7 @@ impl Configuration {
         let now = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .expect("The UNIX epoch must be the earliest possible time point.");
-        let timestamp = Timestamp::from_unix(UUID_CONTEXT, now.as_secs(), now.subsec_nanos());
+        let timestamp = Timestamp::from_unix(&UUID_CONTEXT, now.as_secs(), now.subsec_nanos());
         Uuid::new_v1(timestamp, UUID_NODE_ID)
     }
 
@@ -235,11 +189,7 @@ impl Configuration {
     /// The path to the attachments folder.
     pub fn application_attachments_folder_path(&self) -> PathBuf {
         if let Some(configured_path_string) = self.attachment_path.clone() {
-            if let Ok(configured_path) = PathBuf::try_from(configured_path_string) {
-                return configured_path;
-            } else {
-                log::warn!("{:?} is not a valid path.", self.attachment_path);
-            }
+            return PathBuf::from(configured_path_string);
         }
         let mut default_path = Configuration::application_configuration_folder_path();
         default_path.push(DEFAULT_FOLDER_APPLICATION_ATTACHMENTS);
@@ -260,11 +210,7 @@ impl Configuration {
     /// The path to the thumbnail folder.
     pub fn application_thumbnail_folder_path(&self) -> PathBuf {
         if let Some(configured_path_string) = self.thumbnail_path.clone() {
-            if let Ok(configured_path) = PathBuf::try_from(configured_path_string) {
-                return configured_path;
-            } else {
-                log::warn!("{:?} is not a valid path.", self.thumbnail_path);
-            }
+            return PathBuf::from(configured_path_string);
         }
         let mut default_path = Configuration::application_configuration_folder_path();
         default_path.push(DEFAULT_FOLDER_APPLICATION_THUMBNAILS);
@@ -315,18 +261,47 @@ impl Configuration {
     /// Returns the maximum number of backups.
     pub fn maximum_backups(&self) -> usize {
         self.maximum_backups
-            .clone()
             .unwrap_or(DEFAULT_MAXIMUM_STORED_BACKUPS)
     }
 
+    /// Returns the currency all amounts are converted to.
+    /// The configured currency is checked once by [`Configuration::validate_base_currency`].
+    pub fn base_currency(&self) -> String {
+        self.base_currency
+            .clone()
+            .unwrap_or_else(|| DEFAULT_BASE_CURRENCY.to_string())
+    }
+
+    /// Normalises the configured base currency or falls back to the default
+    /// if it is no valid currency code.
+    pub fn validate_base_currency(&mut self) {
+        if let Some(currency) = self.base_currency.take() {
+            match validate_currency_code(&currency) {
+                Ok(code) => self.base_currency = Some(code),
+                Err(err) => warn!(
+                    "Parsing base currency {} failed, using {} instead: {}",
+                    currency, DEFAULT_BASE_CURRENCY, err
+                ),
+            }
+        }
+    }
+
+    /// Returns the secret backups are encrypted with if encryption is configured.
+    /// The content of a key file takes precedence over a passphrase.
+    pub fn backup_secret(&self) -> Result<Option<Vec<u8>>, HomeworkError> {
+        if let Some(key_file) = &self.backup_key_file {
+            return Ok(Some(std::fs::read(key_file)?));
+        }
+        Ok(self
+            .backup_passphrase
+            .as_ref()
+            .map(|passphrase| passphrase.as_bytes().to_vec()))
+    }
+
     /// The path to the backup folder.
     pub fn application_backup_folder_path(&self) -> PathBuf {
         if let Some(configured_path_string) = self.backup_path.clone() {
-            if let Ok(configured_path) = PathBuf::try_from(configured_path_string) {
-                return configured_path;
-            } else {
-                log::warn!("{:?} is not a valid path.", self.backup_path);
-            }
+            return PathBuf::from(configured_path_string);
         }
         let mut default_path = Configuration::application_configuration_folder_path();
         default_path.push(DEFAULT_FOLDER_APPLICATION_BACKUP);
@@ -334,19 +309,5 @@ impl Configuration {
     }
 }
 
-impl Default for Configuration {
-    fn default() -> Self {
-        Self {
-            server_address: None,
-            server_port: None,
-            attachment_path: None,
-            thumbnail_path: None,
-            log_level: None,
-            backup_path: None,
-            maximum_backups: None,
-        }
-    }
-}
-
 #[cfg(test)]
 mod test;
@@ -15,3 +15,22 @@ fn test_application_configuration_file_path() {
     assert_eq!(p.to_str().unwrap(), "application/configuration.json");
     assert!(p.is_relative());
 }
+
+#[test]
+/// Tests if the `validate_base_currency` function normalises valid currency codes
+/// and falls back to the default for invalid ones.
+fn test_validate_base_currency() {
+    let mut config = Configuration {
+        base_currency: Some(" gbp ".to_string()),
+        ..Default::default()
+    };
+    config.validate_base_currency();
+    assert_eq!(config.base_currency(), "GBP");
+
+    let mut config = Configuration {
+        base_currency: Some("pounds".to_string()),
+        ..Default::default()
+    };
+    config.validate_base_currency();
+    assert_eq!(config.base_currency(), DEFAULT_BASE_CURRENCY);
+}
@@ -13,6 +13,7 @@ pub const DEFAULT_INTERNAL_SERVER_ERROR_EXTERNAL_MESSAGE: &str =
 
 /// An application wide error type.
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum HomeworkError {
     /// A generic error implying an internal problem.
     InternalServerError(InternalError),
@@ -20,6 +21,8 @@ pub enum HomeworkError {
     NotFoundError(InternalError),
     /// A error representing an erroneous request.
     BadRequestError(InternalError),
+    /// A error representing a temporarily unavailable operation.
+    ServiceUnavailableError(InternalError),
 }
 
 impl HomeworkError {
@@ -43,6 +46,12 @@ impl HomeworkError {
                 name: self.status_code().to_string(),
                 message: internal.external_message().clone(),
             },
+            Self::ServiceUnavailableError(internal) => ErrorResponse {
+                code: self.status_code().as_u16(),
+                uuid: internal.uuid(),
+                name: self.status_code().to_string(),
+                message: internal.external_message().clone(),
+            },
         }
     }
 }
@@ -107,6 +116,7 @@ impl std::fmt::Display for HomeworkError {
             Self::InternalServerError(internal) => write!(f, "{}", internal),
             Self::NotFoundError(internal) => write!(f, "{}", internal),
             Self::BadRequestError(internal) => write!(f, "{}", internal),
+            Self::ServiceUnavailableError(internal) => write!(f, "{}", internal),
         }
     }
 }
@@ -121,6 +131,7 @@ impl ResponseError for HomeworkError {
             Self::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::NotFoundError(_) => StatusCode::NOT_FOUND,
             Self::BadRequestError(_) => StatusCode::BAD_REQUEST,
+            Self::ServiceUnavailableError(_) => StatusCode::SERVICE_UNAVAILABLE,
         }
     }
 }
@@ -185,3 +196,23 @@ impl From<image::ImageError> for HomeworkError {
         ))
     }
 }
+
+impl From<openssl::error::ErrorStack> for HomeworkError {
+    fn from(error: openssl::error::ErrorStack) -> Self {
+        Self::InternalServerError(InternalError::new(
+            "openssl::error::ErrorStack",
+            error,
+            DEFAULT_INTERNAL_SERVER_ERROR_EXTERNAL_MESSAGE,
+        ))
+    }
+}
+
+impl From<zip::result::ZipError> for HomeworkError {
+    fn from(error: zip::result::ZipError) -> Self {
+        Self::InternalServerError(InternalError::new(
+            "zip::result::ZipError",
+            error,
+            DEFAULT_INTERNAL_SERVER_ERROR_EXTERNAL_MESSAGE,
+        ))
+    }
+}
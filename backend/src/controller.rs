@@ -1,5 +1,11 @@
 pub mod attachment_controller;
+pub mod backup_controller;
+pub mod budget_controller;
+pub mod exchange_rate_controller;
+pub mod meal_plan_controller;
 pub mod payment_controller;
+pub mod person_controller;
 pub mod recipe_controller;
 pub mod resources_controller;
 pub mod routing;
+pub mod shopping_list_controller;
@@ -8,7 +8,7 @@ use actix_web::{
 };
 use futures_util::TryStreamExt as _;
 use log::info;
-use rusqlite::params;
+use rusqlite::{params, Connection};
 use uuid::Uuid;
 
 use crate::{
@@ -16,24 +16,20 @@ use crate::{
         config::Configuration,
         error::{HomeworkError, InternalError},
     },
-    entity::attachment::Attachment,
-    service::application_service::{backup_service_from_request, configuration_from_request},
+    entity::attachment::{Attachment, AttachmentFilter},
+    service::{
+        application_service::{backup_service_from_request, configuration_from_request},
+        pagination_service::Pagination,
+    },
 };
 
-/// Lists all attachments saved in the database.
-pub async fn all_attachments() -> actix_web::Result<impl Responder> {
+/// Lists a page of the attachments saved in the database matching the filter.
+pub async fn all_attachments(
+    filter: web::Query<AttachmentFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<impl Responder, HomeworkError> {
     let conn = Configuration::database_connection()?;
-    let mut stmt = conn
-        .prepare("SELECT id, name, creation_time FROM attachment")
-        .map_err(HomeworkError::from)?;
-    let attachments_sql = stmt
-        .query_map([], |row| Ok(Attachment::try_from(row)?))
-        .map_err(HomeworkError::from)?;
-    let mut attachments: Vec<Attachment> = Vec::new();
-    for attachment in attachments_sql {
-        attachments.push(attachment.map_err(HomeworkError::from)?);
-    }
-    Ok(web::Json(attachments))
+    Ok(web::Json(Attachment::select_page_from_database(&filter, &pagination, &conn)?))
 }
 
 pub async fn add_attachment(
@@ -88,6 +84,67 @@ pub async fn add_attachment(
     Ok(HttpResponse::Created().body(uuid.to_string()))
 }
 
+/// Reads the first file of a multipart upload into memory.
+/// Returns the sanitised file name if available and the file content.
+///
+/// # Parameters
+///
+/// * `payload` - the multipart upload
+pub async fn read_multipart_file(
+    mut payload: Multipart,
+) -> Result<(Option<String>, Vec<u8>), HomeworkError> {
+    if let Some(mut field) = payload.try_next().await? {
+        let file_name = field
+            .content_disposition()
+            .get_filename()
+            .map(sanitize_filename::sanitize);
+        let mut content = Vec::new();
+        while let Some(chunk) = field.try_next().await? {
+            content.extend_from_slice(&chunk);
+        }
+        Ok((file_name, content))
+    } else {
+        Err(HomeworkError::BadRequestError(InternalError::new(
+            "Missing file",
+            "The multipart upload did not contain any file.",
+            "No file was uploaded.",
+        )))
+    }
+}
+
+/// Saves the file as a new attachment to disk and database.
+/// Returns the ID of the created attachment.
+/// The file is removed again if the attachment cannot be inserted into the database.
+/// If the connection is a transaction, the caller must remove the file in case
+/// the transaction is not committed.
+///
+/// # Parameters
+///
+/// * `config` - the app [`Configuration`]
+/// * `file_name` - the name of the attachment
+/// * `content` - the content of the file
+/// * `connection` - the database connection
+pub fn insert_attachment(
+    config: &Configuration,
+    file_name: &str,
+    content: &[u8],
+    connection: &Connection,
+) -> Result<Uuid, HomeworkError> {
+    let uuid = Configuration::generate_uuid();
+    std::fs::create_dir_all(config.application_attachments_folder_path())?;
+    let file_path = config.application_attachment_file_path(uuid);
+    std::fs::write(&file_path, content)?;
+    if let Err(error) = connection.execute(
+        "INSERT INTO attachment (id, name, creation_time) VALUES (?1, ?2, ?3)",
+        params![uuid, file_name, chrono::Utc::now()],
+    ) {
+        // Remove the saved file as it is not referenced by any attachment.
+        std::fs::remove_file(&file_path)?;
+        return Err(error.into());
+    }
+    Ok(uuid)
+}
+
 pub async fn delete_attachment_request(
     request: HttpRequest,
     id: web::Path<Uuid>,
@@ -219,12 +276,12 @@ fn attachment_file_name_from_db(uuid: Uuid) -> Result<String, HomeworkError> {
         .prepare("SELECT id, name, creation_time FROM attachment WHERE id = ?1")
         .map_err(HomeworkError::from)?;
     let attachment_option = stmt
-        .query_map(params![uuid,], |row| Ok(Attachment::try_from(row)?))
+        .query_map(params![uuid,], |row| Attachment::try_from(row))
         .map_err(HomeworkError::from)?
         .take(1)
         .last();
-    let file_name = if attachment_option.is_some() {
-        attachment_option.unwrap()?.name().to_string()
+    let file_name = if let Some(attachment) = attachment_option {
+        attachment?.name().to_string()
     } else {
         uuid.to_string()
     };
@@ -0,0 +1,109 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    service::{application_service::backup_service_from_request, backup_service::BackupService},
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+/// The optional body of a restore or verification, which specifies the secret a backup
+/// archive is encrypted with if it differs from the configured one.
+pub struct BackupSecret {
+    /// The passphrase the archive is encrypted with.
+    passphrase: Option<String>,
+    /// The base64 encoded content of the key file the archive is encrypted with.
+    key_file: Option<String>,
+}
+
+impl BackupSecret {
+    /// Parses the JSON body of the request, which may be empty to use the configured secret.
+    ///
+    /// # Parameters
+    ///
+    /// * `body` - the request body
+    fn from_body(body: &[u8]) -> Result<Self, HomeworkError> {
+        if body.is_empty() {
+            return Ok(BackupSecret::default());
+        }
+        serde_json::from_slice(body).map_err(|err| {
+            HomeworkError::BadRequestError(InternalError::new(
+                "Invalid backup secret",
+                format!("The backup secret could not be parsed: {}", err),
+                "The passphrase or key file of the backup is invalid.",
+            ))
+        })
+    }
+
+    /// Returns the passphrase or decoded key file content if specified.
+    fn secret(&self) -> Result<Option<Vec<u8>>, HomeworkError> {
+        match (&self.passphrase, &self.key_file) {
+            (Some(_), Some(_)) => Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid backup secret",
+                "Both a passphrase and a key file were specified.",
+                "Either a passphrase or a key file must be specified, but not both.",
+            ))),
+            (Some(passphrase), None) => Ok(Some(passphrase.as_bytes().to_vec())),
+            (None, Some(key_file)) => base64::decode(key_file.trim()).map(Some).map_err(|err| {
+                HomeworkError::BadRequestError(InternalError::new(
+                    "Invalid backup secret",
+                    format!("The key file is not base64 encoded: {}", err),
+                    "The key file must be base64 encoded.",
+                ))
+            }),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Lists the file names of all stored backup archives, newest first.
+pub async fn all_backups(request: HttpRequest) -> Result<impl Responder, HomeworkError> {
+    let backup_service = backup_service_from_request(&request);
+    let mut backups: Vec<String> = backup_service
+        .lock()
+        .current_backups()?
+        .iter()
+        .filter_map(|backup_path| backup_path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect();
+    // Backup archives are named by timestamp.
+    backups.sort_by(|a, b| b.cmp(a));
+    Ok(web::Json(backups))
+}
+
+/// Restores the stored backup archive with the specified file name.
+/// Modifying requests are rejected until the restore finished.
+/// The body may specify the [`BackupSecret`] the archive is encrypted with.
+pub async fn restore_backup(
+    request: HttpRequest,
+    name: web::Path<String>,
+    body: web::Bytes,
+) -> Result<impl Responder, HomeworkError> {
+    let backup_service = backup_service_from_request(&request);
+    let secret = BackupSecret::from_body(&body)?.secret()?;
+    web::block(move || {
+        let archive_path = backup_service.lock().current_backup(&name)?;
+        BackupService::restore_backup(&backup_service, &archive_path, secret.as_deref())
+    })
+    .await??;
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Checks that the stored backup archive with the specified file name can be restored.
+/// The body may specify the [`BackupSecret`] the archive is encrypted with.
+pub async fn verify_backup(
+    request: HttpRequest,
+    name: web::Path<String>,
+    body: web::Bytes,
+) -> Result<impl Responder, HomeworkError> {
+    let backup_service = backup_service_from_request(&request);
+    let secret = BackupSecret::from_body(&body)?.secret()?;
+    web::block(move || {
+        let backup_service = backup_service.lock();
+        let archive_path = backup_service.current_backup(&name)?;
+        backup_service.verify_backup(&archive_path, secret.as_deref())
+    })
+    .await??;
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,96 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    application::{config::Configuration, error::HomeworkError},
+    entity::{budget::Budget, payment::Payment},
+    service::{
+        application_service::{backup_service_from_request, configuration_from_request},
+        budget_service::BudgetStatus,
+        currency_service::CurrencyConverter,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+/// The query parameters of the budget status.
+pub struct BudgetStatusQuery {
+    /// A date within the periods to compute the status for. Defaults to now.
+    date: Option<DateTime<Utc>>,
+}
+
+/// Lists all budgets saved in the database.
+pub async fn all_budgets() -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(Budget::select_all_from_database(&conn)?))
+}
+
+pub async fn single_budget(id: web::Path<Uuid>) -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(Budget::select_from_database_by_id(id.into_inner(), &conn)?))
+}
+
+pub async fn add_budget(
+    budget: web::Json<Budget>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut budget = budget.into_inner();
+    // Overwrite the UUID.
+    let uuid = Configuration::generate_uuid();
+    budget.set_id(uuid);
+    let conn = Configuration::database_connection()?;
+    budget.insert_into_database(&conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
+pub async fn update_budget(
+    id: web::Path<Uuid>,
+    budget: web::Json<Budget>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut budget = budget.into_inner();
+    budget.set_id(id.into_inner());
+    let conn = Configuration::database_connection()?;
+    budget.update_in_database(&conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn delete_budget(
+    id: web::Path<Uuid>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    Budget::delete_from_database_by_id(id.into_inner(), &conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Compares the spending of the current period of every budget against its limit
+/// in the configured base currency.
+pub async fn budget_status(
+    query: web::Query<BudgetStatusQuery>,
+    request: HttpRequest,
+) -> Result<impl Responder, HomeworkError> {
+    let config = configuration_from_request(&request);
+    let date = query.into_inner().date.unwrap_or_else(Utc::now);
+    let conn = Configuration::database_connection()?;
+    let payments = Payment::select_all_from_database(&conn)?;
+    let converter = CurrencyConverter::from_database(config.base_currency(), &conn)?;
+    let status = Budget::select_all_from_database(&conn)?
+        .into_iter()
+        .map(|budget| BudgetStatus::from_payments(budget, &payments, date, &converter))
+        .collect::<Result<Vec<BudgetStatus>, HomeworkError>>()?;
+    Ok(web::Json(status))
+}
@@ -0,0 +1,127 @@
+use std::str::FromStr;
+
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use bigdecimal::BigDecimal;
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        config::Configuration,
+        error::{HomeworkError, InternalError},
+    },
+    entity::exchange_rate::ExchangeRate,
+    service::application_service::backup_service_from_request,
+};
+
+use super::attachment_controller::read_multipart_file;
+
+#[derive(Debug, Deserialize)]
+/// A single exchange rate record of an imported CSV file.
+struct ExchangeRateRecord {
+    /// The date from which on the rate is valid in the format `YYYY-MM-DD`.
+    date: String,
+    /// The currency that is converted.
+    from: String,
+    /// The currency that is converted to.
+    to: String,
+    /// The units of the target currency one unit of the source currency is worth.
+    rate: String,
+}
+
+/// Lists all exchange rates saved in the database.
+pub async fn all_exchange_rates() -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(ExchangeRate::select_all_from_database(&conn)?))
+}
+
+pub async fn add_exchange_rate(
+    exchange_rate: web::Json<ExchangeRate>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut exchange_rate = exchange_rate.into_inner();
+    // Overwrite the UUID.
+    let uuid = Configuration::generate_uuid();
+    exchange_rate.set_id(uuid);
+    let conn = Configuration::database_connection()?;
+    exchange_rate.insert_into_database(&conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
+/// Imports exchange rates from an uploaded CSV file with the header `date,from,to,rate`.
+/// Either all or none of the exchange rates are imported.
+pub async fn import_exchange_rates(
+    request: HttpRequest,
+    payload: Multipart,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let (_, content) = read_multipart_file(payload).await?;
+    let exchange_rates = parse_exchange_rates_csv(&content)?;
+    let mut conn = Configuration::database_connection()?;
+    let transaction = conn.transaction()?;
+    for exchange_rate in &exchange_rates {
+        exchange_rate.insert_into_database(&transaction)?;
+    }
+    transaction.commit()?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().body(exchange_rates.len().to_string()))
+}
+
+pub async fn delete_exchange_rate(
+    id: web::Path<Uuid>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    ExchangeRate::delete_from_database_by_id(id.into_inner(), &conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Parses exchange rates from a CSV file with the header `date,from,to,rate`.
+///
+/// # Parameters
+///
+/// * `content` - the content of the CSV file
+fn parse_exchange_rates_csv(content: &[u8]) -> Result<Vec<ExchangeRate>, HomeworkError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content);
+    let mut exchange_rates = Vec::new();
+    for (line, record) in reader.deserialize::<ExchangeRateRecord>().enumerate() {
+        let invalid_record = |message: String| {
+            HomeworkError::BadRequestError(InternalError::new(
+                "Invalid exchange rate record",
+                format!("Record {} of the exchange rate import is invalid: {}", line + 1, message),
+                format!("Record {} is invalid: {}", line + 1, message),
+            ))
+        };
+        let record = record.map_err(|err| invalid_record(err.to_string()))?;
+        let date = NaiveDate::parse_from_str(&record.date, "%Y-%m-%d")
+            .map_err(|err| invalid_record(format!("date \"{}\": {}", record.date, err)))?;
+        let rate = BigDecimal::from_str(&record.rate)
+            .map_err(|err| invalid_record(format!("rate \"{}\": {}", record.rate, err)))?;
+        exchange_rates.push(ExchangeRate::new(
+            Configuration::generate_uuid(),
+            record.from,
+            record.to,
+            rate,
+            Utc.from_utc_datetime(
+                &date
+                    .and_hms_opt(0, 0, 0)
+                    .expect("Midnight is a valid time."),
+            ),
+        ));
+    }
+    Ok(exchange_rates)
+}
@@ -0,0 +1,118 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    application::{config::Configuration, error::HomeworkError},
+    entity::{meal::Meal, shopping_list::ShoppingList},
+    service::{
+        application_service::backup_service_from_request,
+        calendar_service::meal_plan_calendar,
+        shopping_list_service::{expand_ingredients, merge_ingredients, RecipeSelection},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+/// The query parameters of a meal plan range.
+pub struct MealPlanQuery {
+    /// The first date of the range.
+    from: NaiveDate,
+    /// The last date of the range.
+    to: NaiveDate,
+}
+
+/// Lists all meals planned within the queried date range ordered by date and slot.
+pub async fn meal_plan(query: web::Query<MealPlanQuery>) -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(Meal::select_from_database_by_date_range(query.from, query.to, &conn)?))
+}
+
+pub async fn single_meal(id: web::Path<Uuid>) -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(Meal::select_from_database_by_id(id.into_inner(), &conn)?))
+}
+
+pub async fn add_meal(
+    meal: web::Json<Meal>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut meal = meal.into_inner();
+    // Overwrite the UUID.
+    let uuid = Configuration::generate_uuid();
+    meal.set_id(uuid);
+    let conn = Configuration::database_connection()?;
+    meal.insert_into_database(&conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
+pub async fn update_meal(
+    id: web::Path<Uuid>,
+    meal: web::Json<Meal>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut meal = meal.into_inner();
+    meal.set_id(id.into_inner());
+    let conn = Configuration::database_connection()?;
+    meal.update_in_database(&conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn delete_meal(
+    id: web::Path<Uuid>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    Meal::delete_from_database_by_id(id.into_inner(), &conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Generates a shopping list from all meals planned within the queried date range.
+pub async fn meal_plan_shopping_list(
+    query: web::Query<MealPlanQuery>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut conn = Configuration::database_connection()?;
+    let transaction = conn.transaction()?;
+    let selections: Vec<RecipeSelection> =
+        Meal::select_from_database_by_date_range(query.from, query.to, &transaction)?
+            .iter()
+            .map(|meal| RecipeSelection::new(meal.recipe_id(), meal.servings()))
+            .collect();
+    let ingredients = expand_ingredients(&selections, &transaction)?;
+    // Generate a new UUID for the shopping list.
+    let uuid = Configuration::generate_uuid();
+    ShoppingList::new(
+        uuid,
+        format!("Meal plan {} - {}", query.from, query.to),
+        merge_ingredients(&ingredients),
+    )
+    .insert_into_database(&transaction)?;
+    transaction.commit()?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
+/// Returns all planned meals as iCalendar feed.
+pub async fn meal_plan_ics() -> Result<HttpResponse, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    let meals = Meal::select_all_from_database(&conn)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(meal_plan_calendar(&meals, Utc::now())))
+}
@@ -1,17 +1,80 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use actix_web::{web, HttpResponse, Responder};
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
-    application::{config::Configuration, error::HomeworkError},
-    entity::payment::Payment,
+    application::{
+        config::Configuration,
+        error::{HomeworkError, InternalError},
+    },
+    entity::payment::{Payment, PaymentFilter, PaymentType, SplitMode},
+    service::{
+        application_service::{backup_service_from_request, configuration_from_request},
+        bank_import_service::{
+            mark_duplicates, parse_bank_statement, BankStatementMapping, BankStatementRow,
+        },
+        currency_service::{validate_currency_code, CurrencyConverter},
+        occurrence_service::Occurrence,
+        pagination_service::Pagination,
+        report_service::SpendingReport,
+        settlement_service::{balance_history, Settlement, Transfer},
+    },
 };
 
-/// Lists all payments saved in the database.
-pub async fn all_payments() -> Result<impl Responder, HomeworkError> {
+use super::attachment_controller::{insert_attachment, read_multipart_file};
+
+/// The attachment name of imported bank statements without a file name.
+const BANK_STATEMENT_DEFAULT_FILE_NAME: &str = "bank_statement.csv";
+
+#[derive(Debug, Deserialize)]
+/// The query parameters selecting the payments to settle.
+pub struct SettlementQuery {
+    /// A comma separated list of payment IDs.
+    ids: Option<String>,
+    /// A tag the payments must be tagged with.
+    tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// The query parameters defining a time window.
+pub struct TimeWindowQuery {
+    /// The inclusive start of the time window.
+    from: DateTime<Utc>,
+    /// The inclusive end of the time window.
+    to: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+/// The query parameters of a spending report.
+pub struct ReportQuery {
+    /// The inclusive start of the time window.
+    from: DateTime<Utc>,
+    /// The inclusive end of the time window.
+    to: DateTime<Utc>,
+    /// The format of the report, either `json` (default) or `csv`.
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The query parameters of a bank statement import.
+pub struct BankStatementImportQuery {
+    /// If rows that likely duplicate existing payments are skipped. Defaults to `true`.
+    skip_duplicates: Option<bool>,
+}
+
+/// Lists a page of the payments saved in the database matching the filter.
+pub async fn all_payments(
+    filter: web::Query<PaymentFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<impl Responder, HomeworkError> {
     let conn = Configuration::database_connection()?;
-    Ok(web::Json(Payment::select_all_from_database(&conn)?))
+    Ok(web::Json(Payment::select_page_from_database(&filter, &pagination, &conn)?))
 }
 
 pub async fn single_payment(id: web::Path<Uuid>) -> Result<impl Responder, HomeworkError> {
@@ -20,12 +83,16 @@ pub async fn single_payment(id: web::Path<Uuid>) -> Result<impl Responder, Homew
     Ok(web::Json(Payment::select_from_database_by_id(uuid, &conn)?))
 }
 
-pub async fn create_payment(title: web::Json<String>) -> Result<HttpResponse, HomeworkError> {
+pub async fn create_payment(
+    title: web::Json<String>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    let config = configuration_from_request(&request);
     let conn = Configuration::database_connection()?;
     let title = title.into_inner();
     // Generate a new UUID for the payment.
     let uuid = Configuration::generate_uuid();
-    Payment::insert_into_database_new_entry(uuid, &title, &conn)?;
+    Payment::insert_into_database_new_entry(uuid, &title, &config.base_currency(), &conn)?;
     // Return the UUID of the created payment.
     Ok(HttpResponse::Created().body(uuid.to_string()))
 }
@@ -57,10 +124,60 @@ pub async fn change_payment_string_column(
     Ok(HttpResponse::Ok().finish())
 }
 
+pub async fn change_payment_paid(
+    paid: web::Json<HashMap<String, BigDecimal>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, HomeworkError> {
+    let uuid = path.into_inner();
+    let conn = Configuration::database_connection()?;
+    Payment::update_in_database_paid(uuid, &paid.into_inner(), &conn)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn change_payment_involved(
+    involved: web::Json<HashMap<String, BigDecimal>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, HomeworkError> {
+    let uuid = path.into_inner();
+    let conn = Configuration::database_connection()?;
+    Payment::update_in_database_involved(uuid, &involved.into_inner(), &conn)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn change_payment_split(
+    split: web::Json<Option<SplitMode>>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, HomeworkError> {
+    let uuid = path.into_inner();
+    let conn = Configuration::database_connection()?;
+    Payment::update_in_database_split(uuid, split.into_inner().as_ref(), &conn)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn change_payment_currency(
+    currency: web::Json<String>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, HomeworkError> {
+    let uuid = path.into_inner();
+    let conn = Configuration::database_connection()?;
+    Payment::update_in_database_currency(uuid, &currency.into_inner(), &conn)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn change_payment_type(
+    payment_type: web::Json<PaymentType>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, HomeworkError> {
+    let uuid = path.into_inner();
+    let conn = Configuration::database_connection()?;
+    Payment::update_in_database_payment_type(uuid, &payment_type.into_inner(), &conn)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
 pub async fn all_payment_tags() -> Result<impl Responder, HomeworkError> {
     let conn = Configuration::database_connection()?;
     let mut stmt = conn.prepare("SELECT tag FROM tag_payment_mapping")?;
-    let tag_rows = stmt.query_map([], |row| Ok(row.get(0)?))?;
+    let tag_rows = stmt.query_map([], |row| row.get(0))?;
     let mut tags: HashSet<String> = HashSet::new();
     for tag in tag_rows {
         tags.insert(tag?);
@@ -98,3 +215,265 @@ pub async fn add_attachment_to_payment(
     Payment::update_in_database_insert_attachment(uuid_payment, uuid_attachment, &conn)?;
     Ok(HttpResponse::Created().finish())
 }
+
+/// Computes the balance of every person and the transfers needed to settle them
+/// in the configured base currency.
+/// All payments are settled unless the selection is restricted by the query.
+pub async fn payment_settlement(
+    query: web::Query<SettlementQuery>,
+    request: HttpRequest,
+) -> Result<impl Responder, HomeworkError> {
+    let config = configuration_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    let payments = select_payments_to_settle(query.into_inner(), &conn)?;
+    let converter = CurrencyConverter::from_database(config.base_currency(), &conn)?;
+    Ok(web::Json(Settlement::from_payments(&payments, Utc::now(), &converter)?))
+}
+
+/// Lists the balances of every person after each payment occurrence until now ordered by date
+/// in the configured base currency, which shows when each debt was cleared.
+/// All payments are included unless the selection is restricted by the query.
+pub async fn payment_balance_history(
+    query: web::Query<SettlementQuery>,
+    request: HttpRequest,
+) -> Result<impl Responder, HomeworkError> {
+    let config = configuration_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    let payments = select_payments_to_settle(query.into_inner(), &conn)?;
+    let converter = CurrencyConverter::from_database(config.base_currency(), &conn)?;
+    Ok(web::Json(balance_history(&payments, Utc::now(), &converter)?))
+}
+
+/// Records a suggested transfer of the settlement in the configured base currency
+/// as a payment that settles the balances of the involved persons.
+pub async fn create_settlement_transfer(
+    transfer: web::Json<Transfer>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    let config = configuration_from_request(&request);
+    let transfer = transfer.into_inner();
+    let conn = Configuration::database_connection()?;
+    // Generate a new UUID for the payment.
+    let uuid = Configuration::generate_uuid();
+    Payment::insert_into_database_transfer(
+        uuid,
+        transfer.from(),
+        transfer.to(),
+        transfer.amount(),
+        &config.base_currency(),
+        Utc::now(),
+        &conn,
+    )?;
+    // Return the UUID of the created payment.
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
+/// Selects all payments matching the settlement query.
+///
+/// # Parameters
+///
+/// * `query` - the query restricting the selection
+/// * `connection` - the database connection
+fn select_payments_to_settle(
+    query: SettlementQuery,
+    connection: &rusqlite::Connection,
+) -> Result<Vec<Payment>, HomeworkError> {
+    let ids = query.ids.as_deref().map(parse_id_list).transpose()?;
+    let tag = query.tag.as_deref().map(str::trim);
+    Ok(Payment::select_all_from_database(connection)?
+        .into_iter()
+        .filter(|payment| ids.as_ref().is_none_or(|ids| ids.contains(&payment.id())))
+        .filter(|payment| tag.is_none_or(|tag| payment.tags().iter().any(|t| t == tag)))
+        .collect())
+}
+
+/// Parses a comma separated list of IDs.
+///
+/// # Parameters
+///
+/// * `ids` - the comma separated IDs
+fn parse_id_list(ids: &str) -> Result<HashSet<Uuid>, HomeworkError> {
+    ids.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            Uuid::parse_str(id).map_err(|err| {
+                HomeworkError::BadRequestError(InternalError::new(
+                    "Invalid ID",
+                    format!("\"{}\" is not a valid payment ID: {}", id, err),
+                    "An invalid payment ID was supplied.",
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Lists all occurrences of all payments within the queried time window
+/// ordered by date. Recurring payments are expanded into their single occurrences.
+pub async fn payment_occurrences(
+    query: web::Query<TimeWindowQuery>,
+) -> Result<impl Responder, HomeworkError> {
+    let TimeWindowQuery { from, to } = query.into_inner();
+    validate_time_window(from, to)?;
+    let conn = Configuration::database_connection()?;
+    let mut occurrences: Vec<Occurrence> = Payment::select_all_from_database(&conn)?
+        .iter()
+        .flat_map(|payment| Occurrence::from_payment(payment, from, to))
+        .collect();
+    occurrences.sort_by_key(|occurrence| occurrence.date());
+    Ok(web::Json(occurrences))
+}
+
+/// Reports the spending of all payments within the queried time window per month,
+/// per tag and per person in the configured base currency.
+/// Recurring payments are expanded into their single occurrences.
+/// The report is rendered as JSON unless `format=csv` is queried.
+pub async fn payment_report(
+    query: web::Query<ReportQuery>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    let config = configuration_from_request(&request);
+    let ReportQuery { from, to, format } = query.into_inner();
+    validate_time_window(from, to)?;
+    let conn = Configuration::database_connection()?;
+    let payments = Payment::select_all_from_database(&conn)?;
+    let converter = CurrencyConverter::from_database(config.base_currency(), &conn)?;
+    let report = SpendingReport::from_payments(&payments, from, to, &converter)?;
+    match format.as_deref().map(str::trim) {
+        None | Some("json") => Ok(HttpResponse::Ok().json(report)),
+        Some("csv") => Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .body(report.to_csv()?)),
+        Some(format) => Err(HomeworkError::BadRequestError(InternalError::new(
+            "Invalid report format",
+            format!("The report format \"{}\" is not supported.", format),
+            "The report format must be either json or csv.",
+        ))),
+    }
+}
+
+/// Throws a ```Bad Request``` if the end of the time window lies before its start.
+///
+/// # Parameters
+///
+/// * `from` - the inclusive start of the time window
+/// * `to` - the inclusive end of the time window
+fn validate_time_window(from: DateTime<Utc>, to: DateTime<Utc>) -> Result<(), HomeworkError> {
+    if to < from {
+        Err(HomeworkError::BadRequestError(InternalError::new(
+            "Invalid time window",
+            format!("The end {} of the time window lies before its start {}.", to, from),
+            "The end of the time window must not lie before its start.",
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses an uploaded bank statement in CSV format according to the queried column mapping
+/// without saving anything. Credits and rows that likely duplicate existing payments or
+/// earlier rows are marked.
+pub async fn preview_bank_statement_import(
+    mapping: web::Query<BankStatementMapping>,
+    request: HttpRequest,
+    payload: Multipart,
+) -> Result<impl Responder, HomeworkError> {
+    let currency = bank_statement_currency(&mapping, &configuration_from_request(&request))?;
+    let (_, content) = read_multipart_file(payload).await?;
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(parse_bank_statement_rows(&content, &mapping, &currency, &conn)?))
+}
+
+/// Imports an uploaded bank statement in CSV format according to the queried column mapping.
+/// Every debit becomes a one-off payment of the account holder, which is linked to the bank
+/// statement saved as attachment. Credits are skipped. Either all or none of the rows are imported.
+/// Returns the IDs of the created payments.
+pub async fn import_bank_statement(
+    mapping: web::Query<BankStatementMapping>,
+    query: web::Query<BankStatementImportQuery>,
+    request: HttpRequest,
+    payload: Multipart,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let config = configuration_from_request(&request);
+    let currency = bank_statement_currency(&mapping, &config)?;
+    let skip_duplicates = query.skip_duplicates.unwrap_or(true);
+    let (file_name, content) = read_multipart_file(payload).await?;
+    let mut conn = Configuration::database_connection()?;
+    let rows: Vec<BankStatementRow> =
+        parse_bank_statement_rows(&content, &mapping, &currency, &conn)?
+            .into_iter()
+            .filter(|row| !(row.credit() || skip_duplicates && row.duplicate()))
+            .collect();
+    if rows.is_empty() {
+        return Ok(HttpResponse::Created().json(Vec::<Uuid>::new()));
+    }
+    let transaction = conn.transaction()?;
+    let attachment_id = insert_attachment(
+        &config,
+        file_name
+            .as_deref()
+            .unwrap_or(BANK_STATEMENT_DEFAULT_FILE_NAME),
+        &content,
+        &transaction,
+    )?;
+    let payment_ids = rows
+        .iter()
+        .map(|row| {
+            let payment_id = row.insert_into_database(mapping.person(), &currency, &transaction)?;
+            Payment::update_in_database_insert_attachment(payment_id, attachment_id, &transaction)?;
+            Ok(payment_id)
+        })
+        .collect::<Result<Vec<Uuid>, HomeworkError>>()
+        .and_then(|payment_ids| {
+            transaction.commit()?;
+            Ok(payment_ids)
+        });
+    if payment_ids.is_err() {
+        // Remove the saved bank statement as the attachment was never committed.
+        std::fs::remove_file(config.application_attachment_file_path(attachment_id))?;
+    }
+    let payment_ids = payment_ids?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().json(payment_ids))
+}
+
+/// Returns the validated currency of the bank account, which defaults to the base currency.
+///
+/// # Parameters
+///
+/// * `mapping` - the mapping of the columns
+/// * `config` - the configuration
+fn bank_statement_currency(
+    mapping: &BankStatementMapping,
+    config: &Configuration,
+) -> Result<String, HomeworkError> {
+    validate_currency_code(
+        &mapping
+            .currency()
+            .map(str::to_string)
+            .unwrap_or_else(|| config.base_currency()),
+    )
+}
+
+/// Parses a bank statement and marks all rows that likely duplicate existing payments
+/// or earlier rows.
+///
+/// # Parameters
+///
+/// * `content` - the content of the CSV file
+/// * `mapping` - the mapping of the columns
+/// * `currency` - the currency of the bank account
+/// * `connection` - the database connection
+fn parse_bank_statement_rows(
+    content: &[u8],
+    mapping: &BankStatementMapping,
+    currency: &str,
+    connection: &rusqlite::Connection,
+) -> Result<Vec<BankStatementRow>, HomeworkError> {
+    let mut rows = parse_bank_statement(content, mapping)?;
+    mark_duplicates(&mut rows, &Payment::select_all_from_database(connection)?, currency);
+    Ok(rows)
+}
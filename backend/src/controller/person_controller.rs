@@ -0,0 +1,83 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use uuid::Uuid;
+
+use crate::{
+    application::{config::Configuration, error::HomeworkError},
+    entity::person::Person,
+    service::{application_service::backup_service_from_request, person_service::merge_persons},
+};
+
+/// Lists all persons saved in the database.
+pub async fn all_persons() -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(Person::select_all_from_database(&conn)?))
+}
+
+pub async fn single_person(id: web::Path<Uuid>) -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(Person::select_from_database_by_id(id.into_inner(), &conn)?))
+}
+
+pub async fn add_person(
+    person: web::Json<Person>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut person = person.into_inner();
+    // Overwrite the UUID.
+    let uuid = Configuration::generate_uuid();
+    person.set_id(uuid);
+    let conn = Configuration::database_connection()?;
+    person.insert_into_database(&conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
+pub async fn update_person(
+    id: web::Path<Uuid>,
+    person: web::Json<Person>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut person = person.into_inner();
+    person.set_id(id.into_inner());
+    let conn = Configuration::database_connection()?;
+    person.update_in_database(&conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub async fn delete_person(
+    id: web::Path<Uuid>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    Person::delete_from_database_by_id(id.into_inner(), &conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Merges the person into the person with the ID supplied in the body
+/// across all payments and budgets. The merged person is deleted.
+pub async fn merge_person(
+    id: web::Path<Uuid>,
+    target: web::Json<Uuid>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let mut conn = Configuration::database_connection()?;
+    let transaction = conn.transaction()?;
+    merge_persons(id.into_inner(), target.into_inner(), &transaction)?;
+    transaction.commit()?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
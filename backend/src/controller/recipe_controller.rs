@@ -1,7 +1,12 @@
 use std::{collections::HashSet, sync::Arc};
 
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
-use log::info;
+use actix_multipart::Multipart;
+use actix_web::{
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    web, HttpRequest, HttpResponse, Responder,
+};
+use log::{info, warn};
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
@@ -9,21 +14,216 @@ use crate::{
         config::Configuration,
         error::{HomeworkError, InternalError},
     },
-    entity::{ingredient::Ingredient, recipe::Recipe}, service::application_service::{backup_service_from_request, configuration_from_request},
+    entity::{
+        ingredient::Ingredient,
+        recipe::{Recipe, RecipeFilter},
+    },
+    service::{
+        application_service::{backup_service_from_request, configuration_from_request},
+        cookbook_service::cookbook_pdf,
+        pagination_service::Pagination,
+        recipe_export_service::{ExportImage, RecipeExportFormat},
+        recipe_import_service::parse_recipe_html,
+        search_service::search_recipe_index,
+        unit_service::UnitSystem,
+    },
 };
 
-use super::attachment_controller;
+use super::attachment_controller::{self, insert_attachment, read_multipart_file};
+
+#[derive(Debug, Deserialize)]
+/// The query parameters of a single recipe.
+pub struct RecipeQuery {
+    /// The number of servings to scale all ingredient quantities to.
+    servings: Option<u32>,
+    /// The system of units to convert all ingredient quantities to.
+    #[serde(default)]
+    units: UnitSystem,
+}
+
+impl RecipeQuery {
+    /// Scales and converts the quantities of the recipe as requested.
+    ///
+    /// # Parameters
+    ///
+    /// * `recipe` - the recipe to scale and convert
+    fn apply(&self, recipe: &mut Recipe) -> Result<(), HomeworkError> {
+        if let Some(servings) = self.servings {
+            recipe.scale(servings)?;
+        }
+        recipe.convert_units(self.units)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+/// The query parameters of a single recipe export.
+pub struct RecipeExportQuery {
+    /// The format to export the recipe in.
+    format: RecipeExportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+/// The query parameters of a cookbook export.
+pub struct CookbookQuery {
+    /// The title of the cookbook. Defaults to the filtered tag.
+    title: Option<String>,
+}
 
-/// Lists all recipes saved in the database.
-pub async fn all_recipes() -> Result<impl Responder, HomeworkError> {
+/// Lists a page of the recipes saved in the database matching the filter.
+pub async fn all_recipes(
+    filter: web::Query<RecipeFilter>,
+    pagination: web::Query<Pagination>,
+) -> Result<impl Responder, HomeworkError> {
     let conn = Configuration::database_connection()?;
-    Ok(web::Json(Recipe::select_all_from_database(&conn)?))
+    Ok(web::Json(Recipe::select_page_from_database(&filter, &pagination, &conn)?))
+}
+
+#[derive(Debug, Deserialize)]
+/// The query parameters of a recipe search.
+pub struct RecipeSearchQuery {
+    /// The words to search for.
+    q: String,
 }
 
-pub async fn single_recipe(id: web::Path<Uuid>) -> Result<impl Responder, HomeworkError> {
+/// Searches the title, instructions, reference, ingredients and tags of all recipes
+/// and lists the matching recipes ordered by relevance including highlighted snippets.
+pub async fn search_recipes(
+    query: web::Query<RecipeSearchQuery>,
+) -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(search_recipe_index(&query.q, &conn)?))
+}
+
+/// Returns the recipe. If a number of servings is requested, the quantities
+/// of all ingredients are scaled accordingly. Quantities can be converted into
+/// metric or imperial units.
+pub async fn single_recipe(
+    id: web::Path<Uuid>,
+    query: web::Query<RecipeQuery>,
+) -> Result<impl Responder, HomeworkError> {
     let uuid = id.into_inner();
     let conn = Configuration::database_connection()?;
-    Ok(web::Json(Recipe::select_from_database_by_id(uuid, &conn)?))
+    let mut recipe = Recipe::select_from_database_by_id(uuid, &conn)?;
+    query.apply(&mut recipe)?;
+    Ok(web::Json(recipe))
+}
+
+/// Returns the recipe with the ingredients of its sub-recipes inlined below the
+/// ingredients referencing them. The recipe can be scaled and converted like a single recipe.
+pub async fn expanded_recipe(
+    id: web::Path<Uuid>,
+    query: web::Query<RecipeQuery>,
+) -> Result<impl Responder, HomeworkError> {
+    let uuid = id.into_inner();
+    let conn = Configuration::database_connection()?;
+    let mut recipe = Recipe::select_from_database_by_id(uuid, &conn)?;
+    recipe.expand_sub_recipes(&conn)?;
+    query.apply(&mut recipe)?;
+    Ok(web::Json(recipe))
+}
+
+/// Returns all recipes using the recipe as sub-recipe.
+pub async fn recipe_used_in(id: web::Path<Uuid>) -> Result<impl Responder, HomeworkError> {
+    let uuid = id.into_inner();
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(Recipe::select_from_database_by_sub_recipe(uuid, &conn)?))
+}
+
+/// Exports the recipe as Markdown or as printable HTML page including its thumbnail.
+/// The recipe can be scaled and converted like a single recipe.
+pub async fn export_recipe(
+    id: web::Path<Uuid>,
+    query: web::Query<RecipeQuery>,
+    export_query: web::Query<RecipeExportQuery>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    let config = configuration_from_request(&request);
+    let uuid = id.into_inner();
+    let conn = Configuration::database_connection()?;
+    let mut recipe = Recipe::select_from_database_by_id(uuid, &conn)?;
+    query.apply(&mut recipe)?;
+    let format = export_query.format;
+    let (recipe, thumbnail) = web::block(move || {
+        let thumbnail = export_thumbnail(&config, &recipe);
+        (recipe, thumbnail)
+    })
+    .await?;
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(attachment_disposition(recipe.title(), format.extension()))
+        .body(format.render(&recipe, thumbnail.as_ref())))
+}
+
+/// Exports all recipes matching the filter as cookbook PDF with a table of contents.
+pub async fn export_cookbook(
+    filter: web::Query<RecipeFilter>,
+    query: web::Query<CookbookQuery>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    let config = configuration_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    let recipes =
+        Recipe::select_page_from_database(&filter, &Pagination::default(), &conn)?.into_items();
+    let title = query
+        .into_inner()
+        .title
+        .or_else(|| filter.tag().map(str::to_string))
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| "Cookbook".to_string());
+    let disposition = attachment_disposition(&title, "pdf");
+    let pdf = web::block(move || {
+        let recipes: Vec<(Recipe, Option<ExportImage>)> = recipes
+            .into_iter()
+            .map(|recipe| {
+                let thumbnail = export_thumbnail(&config, &recipe);
+                (recipe, thumbnail)
+            })
+            .collect();
+        cookbook_pdf(&title, &recipes)
+    })
+    .await?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/pdf")
+        .insert_header(disposition)
+        .body(pdf))
+}
+
+/// Loads the thumbnail of the recipe for an export. Thumbnails that cannot be
+/// decoded as image are left out, so the recipe can be exported anyway.
+///
+/// # Parameters
+///
+/// * `config` - the app [`Configuration`]
+/// * `recipe` - the recipe to load the thumbnail of
+fn export_thumbnail(config: &Configuration, recipe: &Recipe) -> Option<ExportImage> {
+    let thumbnail = recipe.thumbnail()?;
+    ExportImage::from_file(&config.application_attachment_file_path(thumbnail.id()))
+        .map_err(|error| {
+            warn!(
+                "The thumbnail {} of recipe {} cannot be exported: {:?}",
+                thumbnail.id(),
+                recipe.title(),
+                error
+            );
+        })
+        .ok()
+}
+
+/// Returns the header to download the response as file named after the title.
+///
+/// # Parameters
+///
+/// * `title` - the title of the exported document
+/// * `extension` - the file extension of the exported document
+fn attachment_disposition(title: &str, extension: &str) -> ContentDisposition {
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![DispositionParam::Filename(format!(
+            "{}.{}",
+            sanitize_filename::sanitize(title),
+            extension
+        ))],
+    }
 }
 
 pub async fn create_recipe(
@@ -43,6 +243,71 @@ pub async fn create_recipe(
     Ok(HttpResponse::Created().body(uuid.to_string()))
 }
 
+/// Imports a recipe from an uploaded HTML page describing the recipe as schema.org
+/// `Recipe` in JSON-LD. An image embedded into the page is stored as attachment
+/// and used as thumbnail. Returns the ID of the created recipe.
+pub async fn import_recipe(
+    request: HttpRequest,
+    payload: Multipart,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service and configuration.
+    let backup_service = backup_service_from_request(&request);
+    let config = configuration_from_request(&request);
+    let (_, content) = read_multipart_file(payload).await?;
+    let imported = parse_recipe_html(&String::from_utf8_lossy(&content))?;
+    let mut conn = Configuration::database_connection()?;
+    let transaction = conn.transaction()?;
+    // Generate a new UUID for the recipe.
+    let uuid = Configuration::generate_uuid();
+    Recipe::insert_into_database_new_entry(uuid, imported.title(), &transaction)?;
+    Recipe::update_in_database_string_column(
+        uuid,
+        "instructions",
+        imported.instructions(),
+        &transaction,
+    )?;
+    Recipe::update_in_database_string_column(
+        uuid,
+        "reference",
+        imported.reference(),
+        &transaction,
+    )?;
+    Recipe::update_in_database_servings(uuid, imported.servings(), &transaction)?;
+    for (ordering, ingredient) in imported.ingredients().iter().enumerate() {
+        Ingredient::new(
+            Configuration::generate_uuid(),
+            uuid,
+            ingredient.amount(),
+            ingredient.unit(),
+            ingredient.text(),
+            ordering as i32,
+        )
+        .insert_into_database(&transaction)?;
+    }
+    let attachment_id = imported
+        .image()
+        .map(|image| insert_attachment(&config, image.file_name(), image.content(), &transaction))
+        .transpose()?;
+    let result = attachment_id
+        .map(|attachment_id| {
+            Recipe::update_in_database_insert_attachment(uuid, attachment_id, &transaction)?;
+            Recipe::update_in_database_thumbnail(uuid, Some(attachment_id), &transaction)
+        })
+        .transpose()
+        .and_then(|_| Ok(transaction.commit()?));
+    if result.is_err() {
+        if let Some(attachment_id) = attachment_id {
+            // Remove the saved image as the attachment was never committed.
+            std::fs::remove_file(config.application_attachment_file_path(attachment_id))?;
+        }
+    }
+    result?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    info!("Imported recipe {}.", uuid);
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
 pub async fn remove_recipe(
     path: web::Path<Uuid>,
     request: HttpRequest,
@@ -95,10 +360,25 @@ pub async fn change_rating(
     Ok(HttpResponse::Ok().finish())
 }
 
+pub async fn change_servings(
+    servings: web::Json<Option<u32>>,
+    id: web::Path<Uuid>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let uuid = id.into_inner();
+    let conn = Configuration::database_connection()?;
+    Recipe::update_in_database_servings(uuid, servings.into_inner(), &conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
 pub async fn all_recipe_tags() -> Result<impl Responder, HomeworkError> {
     let conn = Configuration::database_connection()?;
     let mut stmt = conn.prepare("SELECT tag FROM tag_recipe_mapping")?;
-    let tag_rows = stmt.query_map([], |row| Ok(row.get(0)?))?;
+    let tag_rows = stmt.query_map([], |row| row.get(0))?;
     let mut tags: HashSet<String> = HashSet::new();
     for tag in tag_rows {
         tags.insert(tag?);
@@ -6,18 +6,40 @@ use super::{
         add_attachment, all_attachments, delete_attachment_request, download_attachment,
         thumbnail_image_attachment,
     },
+    backup_controller::{all_backups, restore_backup, verify_backup},
+    budget_controller::{
+        add_budget, all_budgets, budget_status, delete_budget, single_budget, update_budget,
+    },
+    exchange_rate_controller::{
+        add_exchange_rate, all_exchange_rates, delete_exchange_rate, import_exchange_rates,
+    },
+    meal_plan_controller::{
+        add_meal, delete_meal, meal_plan, meal_plan_ics, meal_plan_shopping_list, single_meal,
+        update_meal,
+    },
     payment_controller::{
         add_attachment_to_payment, add_tag_to_payment, all_payment_tags, all_payments,
-        change_payment_string_column, create_payment, remove_multiple_payments, remove_payment,
-        remove_tag_from_payment, single_payment,
+        change_payment_currency, change_payment_involved, change_payment_paid,
+        change_payment_split, change_payment_string_column, change_payment_type, create_payment,
+        create_settlement_transfer, import_bank_statement, payment_balance_history,
+        payment_occurrences, payment_report, payment_settlement, preview_bank_statement_import,
+        remove_multiple_payments, remove_payment, remove_tag_from_payment, single_payment,
+    },
+    person_controller::{
+        add_person, all_persons, delete_person, merge_person, single_person, update_person,
     },
     recipe_controller::{
         add_attachment_to_recipe, add_ingredient_to_recipe, add_tag_to_recipe, all_recipe_tags,
-        all_recipes, change_rating, change_recipe_string_column, create_recipe, modify_ingredient,
-        modify_ingredients_ordering, remove_ingredient_from_recipe, remove_recipe,
-        remove_tag_from_recipe, set_thumbnail_for_recipe, single_recipe,
+        all_recipes, change_rating, change_recipe_string_column, change_servings, create_recipe,
+        expanded_recipe, export_cookbook, export_recipe, import_recipe, modify_ingredient,
+        modify_ingredients_ordering, recipe_used_in, remove_ingredient_from_recipe, remove_recipe,
+        remove_tag_from_recipe, search_recipes, set_thumbnail_for_recipe, single_recipe,
     },
     resources_controller::favicon,
+    shopping_list_controller::{
+        all_shopping_lists, check_shopping_list_item, create_shopping_list, delete_shopping_list,
+        single_shopping_list,
+    },
 };
 
 async fn index() -> actix_web::Result<NamedFile> {
@@ -45,6 +67,11 @@ pub fn routing_config(cfg: &mut ServiceConfig) {
     )
     .route("/api/attachment/{id}/{width}", web::get().to(thumbnail_image_attachment))
 
+    // Backup controller routing
+    .route("/api/admin/backups", web::get().to(all_backups))
+    .route("/api/admin/backup/{name}/restore", web::post().to(restore_backup))
+    .route("/api/admin/backup/{name}/verify", web::post().to(verify_backup))
+
     // Recipe controller routing
     .service(
         web::resource("/api/recipes")
@@ -52,13 +79,20 @@ pub fn routing_config(cfg: &mut ServiceConfig) {
             .route(web::post().to(create_recipe))
     )
     .route("/api/recipes/tags", web::get().to(all_recipe_tags))
+    .route("/api/recipes/search", web::get().to(search_recipes))
+    .route("/api/recipes/import", web::post().to(import_recipe))
+    .route("/api/recipes/cookbook.pdf", web::get().to(export_cookbook))
     .service(
         web::resource("/api/recipe/{id}")
         .route(web::get().to(single_recipe))
         .route(web::delete().to(remove_recipe))
     )
+    .route("/api/recipe/{id}/export", web::get().to(export_recipe))
+    .route("/api/recipe/{id}/expanded", web::get().to(expanded_recipe))
+    .route("/api/recipe/{id}/usedin", web::get().to(recipe_used_in))
     .route("/api/recipe/{id}/string/{string_param}", web::post().to(change_recipe_string_column))
     .route("/api/recipe/{id}/rating", web::post().to(change_rating))
+    .route("/api/recipe/{id}/servings", web::post().to(change_servings))
     .route("/api/recipe/{id}/tags", web::post().to(add_tag_to_recipe))
     .route("/api/recipe/{id}/tag/{tag_name}", web::delete().to(remove_tag_from_recipe))
     .route("/api/recipe/{id}/attachments", web::post().to(add_attachment_to_recipe))
@@ -78,6 +112,13 @@ pub fn routing_config(cfg: &mut ServiceConfig) {
             .route(web::patch().to(remove_multiple_payments))
             .route(web::post().to(create_payment))
     )
+    .route("/api/payments/settlement", web::get().to(payment_settlement))
+    .route("/api/payments/settlement/history", web::get().to(payment_balance_history))
+    .route("/api/payments/settlement/transfer", web::post().to(create_settlement_transfer))
+    .route("/api/payments/occurrences", web::get().to(payment_occurrences))
+    .route("/api/payments/report", web::get().to(payment_report))
+    .route("/api/payments/import", web::post().to(import_bank_statement))
+    .route("/api/payments/import/preview", web::post().to(preview_bank_statement_import))
     .route("/api/payment/tags", web::get().to(all_payment_tags))
     .service(
         web::resource("/api/payment/{id}")
@@ -85,10 +126,80 @@ pub fn routing_config(cfg: &mut ServiceConfig) {
         .route(web::delete().to(remove_payment))
     )
     .route("/api/payment/{id}/string/{string_param}", web::post().to(change_payment_string_column))
+    .route("/api/payment/{id}/paid", web::post().to(change_payment_paid))
+    .route("/api/payment/{id}/involved", web::post().to(change_payment_involved))
+    .route("/api/payment/{id}/split", web::post().to(change_payment_split))
+    .route("/api/payment/{id}/currency", web::post().to(change_payment_currency))
+    .route("/api/payment/{id}/type", web::post().to(change_payment_type))
     .route("/api/payment/{id}/tags", web::post().to(add_tag_to_payment))
     .route("/api/payment/{id}/tag/{tag_name}", web::delete().to(remove_tag_from_payment))
     .route("/api/payment/{id}/attachments", web::post().to(add_attachment_to_payment))
 
+    // Person controller routing
+    .service(
+        web::resource("/api/persons")
+            .route(web::get().to(all_persons))
+            .route(web::post().to(add_person))
+    )
+    .service(
+        web::resource("/api/person/{id}")
+        .route(web::get().to(single_person))
+        .route(web::post().to(update_person))
+        .route(web::delete().to(delete_person))
+    )
+    .route("/api/person/{id}/merge", web::post().to(merge_person))
+
+    // Exchange rate controller routing
+    .service(
+        web::resource("/api/exchangerates")
+            .route(web::get().to(all_exchange_rates))
+            .route(web::post().to(add_exchange_rate))
+    )
+    .route("/api/exchangerates/import", web::post().to(import_exchange_rates))
+    .route("/api/exchangerate/{id}", web::delete().to(delete_exchange_rate))
+
+    // Budget controller routing
+    .service(
+        web::resource("/api/budgets")
+            .route(web::get().to(all_budgets))
+            .route(web::post().to(add_budget))
+    )
+    .route("/api/budgets/status", web::get().to(budget_status))
+    .service(
+        web::resource("/api/budget/{id}")
+        .route(web::get().to(single_budget))
+        .route(web::post().to(update_budget))
+        .route(web::delete().to(delete_budget))
+    )
+
+    // Meal plan controller routing
+    .service(
+        web::resource("/api/meals")
+            .route(web::get().to(meal_plan))
+            .route(web::post().to(add_meal))
+    )
+    .route("/api/meals/shoppinglist", web::post().to(meal_plan_shopping_list))
+    .route("/api/meals/calendar.ics", web::get().to(meal_plan_ics))
+    .service(
+        web::resource("/api/meal/{id}")
+        .route(web::get().to(single_meal))
+        .route(web::post().to(update_meal))
+        .route(web::delete().to(delete_meal))
+    )
+
+    // Shopping list controller routing
+    .service(
+        web::resource("/api/shoppinglists")
+            .route(web::get().to(all_shopping_lists))
+            .route(web::post().to(create_shopping_list))
+    )
+    .service(
+        web::resource("/api/shoppinglist/{id}")
+        .route(web::get().to(single_shopping_list))
+        .route(web::delete().to(delete_shopping_list))
+    )
+    .route("/api/shoppinglist/{id}/item/{item_id}/checked", web::post().to(check_shopping_list_item))
+
     // Redirects the favicon route.
     .route("/favicon.ico", web::get().to(favicon))
     // Registers static frontend resources. Needs to be last to not overwrite other routes.
@@ -0,0 +1,90 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    application::{config::Configuration, error::HomeworkError},
+    entity::shopping_list::ShoppingList,
+    service::{
+        application_service::backup_service_from_request,
+        shopping_list_service::{expand_ingredients, merge_ingredients, RecipeSelection},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The request to generate a shopping list.
+pub struct ShoppingListRequest {
+    /// The title of the shopping list.
+    #[serde(default)]
+    title: String,
+    /// The recipes to shop the ingredients for.
+    recipes: Vec<RecipeSelection>,
+}
+
+/// Lists all shopping lists saved in the database, the most recent first.
+pub async fn all_shopping_lists() -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(ShoppingList::select_all_from_database(&conn)?))
+}
+
+pub async fn single_shopping_list(id: web::Path<Uuid>) -> Result<impl Responder, HomeworkError> {
+    let conn = Configuration::database_connection()?;
+    Ok(web::Json(ShoppingList::select_from_database_by_id(id.into_inner(), &conn)?))
+}
+
+/// Generates a shopping list from the ingredients of the selected recipes
+/// including their sub-recipes.
+pub async fn create_shopping_list(
+    shopping_list: web::Json<ShoppingListRequest>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let shopping_list = shopping_list.into_inner();
+    let mut conn = Configuration::database_connection()?;
+    let transaction = conn.transaction()?;
+    let ingredients = expand_ingredients(&shopping_list.recipes, &transaction)?;
+    // Generate a new UUID for the shopping list.
+    let uuid = Configuration::generate_uuid();
+    ShoppingList::new(uuid, shopping_list.title, merge_ingredients(&ingredients))
+        .insert_into_database(&transaction)?;
+    transaction.commit()?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Created().body(uuid.to_string()))
+}
+
+pub async fn delete_shopping_list(
+    id: web::Path<Uuid>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let conn = Configuration::database_connection()?;
+    ShoppingList::delete_from_database_by_id(id.into_inner(), &conn)?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Checks or unchecks a single item of the shopping list.
+pub async fn check_shopping_list_item(
+    path: web::Path<(Uuid, Uuid)>,
+    checked: web::Json<bool>,
+    request: HttpRequest,
+) -> Result<HttpResponse, HomeworkError> {
+    // Load the backup service.
+    let backup_service = backup_service_from_request(&request);
+    let (uuid_shopping_list, uuid_item) = path.into_inner();
+    let conn = Configuration::database_connection()?;
+    ShoppingList::update_in_database_item_checked(
+        uuid_shopping_list,
+        uuid_item,
+        checked.into_inner(),
+        &conn,
+    )?;
+    // Request a backup as internal data changed.
+    backup_service.lock().request_timed_backup();
+    Ok(HttpResponse::Ok().finish())
+}
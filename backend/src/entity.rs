@@ -1,4 +1,9 @@
 pub mod attachment;
+pub mod budget;
+pub mod exchange_rate;
 pub mod ingredient;
+pub mod meal;
 pub mod payment;
+pub mod person;
 pub mod recipe;
+pub mod shopping_list;
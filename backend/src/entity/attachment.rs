@@ -3,7 +3,10 @@ use rusqlite::{Connection, Row};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::application::error::{HomeworkError, InternalError};
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    service::pagination_service::{select_page, Conditions, Page, Pagination, SortDirection},
+};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +27,42 @@ impl Attachment {
         &self.name
     }
 
+    /// Returns a page of all attachments matching the filter.
+    ///
+    /// # Parameters
+    ///
+    /// * `filter` - the filters and sort order
+    /// * `pagination` - the page to select
+    /// * `connection` - the database connection
+    pub fn select_page_from_database(
+        filter: &AttachmentFilter,
+        pagination: &Pagination,
+        connection: &Connection,
+    ) -> Result<Page<Attachment>, HomeworkError> {
+        let mut conditions = Conditions::default();
+        if let Some(from) = filter.from {
+            conditions.push("creation_time >= ?", from);
+        }
+        if let Some(to) = filter.to {
+            conditions.push("creation_time <= ?", to);
+        }
+        let order = match filter.sort {
+            AttachmentSortField::Name => format!("name COLLATE NOCASE {}, id", filter.direction),
+            AttachmentSortField::CreationTime => {
+                format!("creation_time {}, id", filter.direction)
+            },
+        };
+        select_page(
+            "SELECT id, name, creation_time FROM attachment",
+            "attachment",
+            &conditions,
+            &order,
+            pagination,
+            connection,
+            |row| Attachment::try_from(row),
+        )
+    }
+
     pub fn exists_in_database_by_id(
         id: Uuid,
         connection: &Connection,
@@ -43,7 +82,7 @@ impl Attachment {
         attachment_id: Uuid,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        if !Self::exists_in_database_by_id(attachment_id, &connection)? {
+        if !Self::exists_in_database_by_id(attachment_id, connection)? {
             Err(HomeworkError::NotFoundError(InternalError::new(
                 "Attachment not found",
                 format!("The attachment {} does not exist.", attachment_id),
@@ -66,3 +105,28 @@ impl TryFrom<&Row<'_>> for Attachment {
         })
     }
 }
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The query parameters filtering and sorting a list of attachments.
+pub struct AttachmentFilter {
+    /// The inclusive start of the time window the attachments were uploaded in.
+    from: Option<DateTime<Utc>>,
+    /// The inclusive end of the time window the attachments were uploaded in.
+    to: Option<DateTime<Utc>>,
+    /// The field the attachments are sorted by. Defaults to the name.
+    #[serde(default)]
+    sort: AttachmentSortField,
+    /// The direction the attachments are sorted in. Defaults to ascending.
+    #[serde(default)]
+    direction: SortDirection,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The fields a list of attachments can be sorted by.
+pub enum AttachmentSortField {
+    #[default]
+    Name,
+    CreationTime,
+}
@@ -0,0 +1,311 @@
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, Signed};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::application::error::{HomeworkError, InternalError};
+
+use super::person::Person;
+
+const PERIOD_WEEKLY: &str = "Weekly";
+const PERIOD_MONTHLY: &str = "Monthly";
+const PERIOD_YEARLY: &str = "Yearly";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A spending limit for all payments tagged with a certain tag within a recurring period.
+/// If a person is specified, only the share of the payments the person is involved with
+/// counts towards the limit.
+pub struct Budget {
+    #[serde(default)]
+    id: Uuid,
+    tag: String,
+    period: BudgetPeriod,
+    limit: BigDecimal,
+    person: Option<String>,
+}
+
+impl Budget {
+    /// Returns the ID of this `Budget`.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the tag of the payments this `Budget` limits.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Returns the period this `Budget` recurs with.
+    pub fn period(&self) -> &BudgetPeriod {
+        &self.period
+    }
+
+    /// Returns the spending limit of this `Budget` in the base currency.
+    pub fn limit(&self) -> &BigDecimal {
+        &self.limit
+    }
+
+    /// Returns the ID of the person this `Budget` is restricted to if any.
+    pub fn person(&self) -> Option<&str> {
+        self.person.as_deref()
+    }
+
+    pub fn set_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+
+    pub fn select_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<Budget, HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        let mut stmt = connection
+            .prepare("SELECT id, tag, period, spending_limit, person FROM budget WHERE id = ?1")?;
+        stmt.query_row([id], |row| Ok(Budget::try_from(row)))?
+    }
+
+    pub fn select_all_from_database(connection: &Connection) -> Result<Vec<Budget>, HomeworkError> {
+        let mut stmt = connection
+            .prepare("SELECT id, tag, period, spending_limit, person FROM budget ORDER BY tag")?;
+        let budget_query = stmt.query_and_then([], |row| Budget::try_from(row))?;
+        let mut budgets = Vec::new();
+        for budget in budget_query {
+            budgets.push(budget?);
+        }
+        Ok(budgets)
+    }
+
+    /// Validates the budget and inserts it into the database.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn insert_into_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        if Self::exists_in_database_by_id(self.id(), connection)? {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Budget already exists",
+                format!("The budget {} already exists.", self.id()),
+                "The budget already exists.",
+            )));
+        }
+        let budget = self.validate(connection)?;
+
+        connection.execute(
+            "INSERT INTO budget (id, tag, period, spending_limit, person, creation_time) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![budget.id(), budget.tag(), budget.period().to_string(), budget.limit().to_string(), budget.person(), chrono::Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    /// Validates the budget and replaces the stored budget with the same ID.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn update_in_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(self.id(), connection)?;
+        let budget = self.validate(connection)?;
+
+        connection.execute(
+            "UPDATE budget SET tag = ?1, period = ?2, spending_limit = ?3, person = ?4 WHERE id = ?5",
+            params![budget.tag(), budget.period().to_string(), budget.limit().to_string(), budget.person(), budget.id()],
+        )?;
+        Ok(())
+    }
+
+    /// Replaces every reference to a person with a reference to another person.
+    /// The references are not validated.
+    ///
+    /// # Parameters
+    ///
+    /// * `person` - the reference to replace
+    /// * `replacement` - the new reference
+    /// * `connection` - the database connection
+    pub fn update_in_database_person_references(
+        person: &str,
+        replacement: &str,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        connection.execute(
+            "UPDATE budget SET person = ?1 WHERE person = ?2",
+            params![replacement, person],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+
+        connection.execute("DELETE FROM budget WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn exists_in_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<bool, rusqlite::Error> {
+        let mut stmt = connection.prepare("SELECT 1 FROM budget WHERE id = ?1")?;
+        stmt.exists([id])
+    }
+
+    /// Automatically throws a ```Not Found``` if the entry does not exist.
+    /// Returns an ```Ok``` otherwise.
+    ///
+    /// Parameters
+    ///
+    /// * ```id``` - the ID of the budget
+    /// * ```connection``` - the database connection
+    pub fn exists_in_database_by_id_throw_not_found(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        if !Self::exists_in_database_by_id(id, connection)? {
+            Err(HomeworkError::NotFoundError(InternalError::new(
+                "Budget not found",
+                format!("The budget {} does not exist.", id),
+                "The budget does not exist.",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns a copy of the budget with trimmed tag and the person resolved to its ID
+    /// after checking that the tag is not empty and the limit is not negative.
+    fn validate(&self, connection: &Connection) -> Result<Budget, HomeworkError> {
+        let tag = self.tag().trim();
+        if tag.is_empty() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid budget",
+                format!("The budget {:?} has an empty tag.", self),
+                "The tag of a budget must not be empty.",
+            )));
+        }
+        if self.limit().is_negative() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid budget",
+                format!("The budget {:?} has a negative limit.", self),
+                "The limit of a budget must not be negative.",
+            )));
+        }
+        Ok(Budget {
+            id: self.id(),
+            tag: tag.to_string(),
+            period: self.period().clone(),
+            limit: self.limit().clone(),
+            person: self
+                .person()
+                .filter(|person| !person.trim().is_empty())
+                .map(|person| Person::resolve_id(person, connection).map(|id| id.to_string()))
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<&Row<'_>> for Budget {
+    type Error = HomeworkError;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let period: String = row.get(2)?;
+        let limit: String = row.get(3)?;
+        let limit = BigDecimal::from_str(&limit).map_err(|err| {
+            HomeworkError::InternalServerError(InternalError::new(
+                "Invalid budget limit",
+                format!("The stored budget limit {} could not be parsed: {}", limit, err),
+                "A stored budget limit is invalid.",
+            ))
+        })?;
+        Ok(Budget {
+            id: row.get(0)?,
+            tag: row.get(1)?,
+            period: BudgetPeriod::try_from(period.as_str())?,
+            limit,
+            person: row.get(4)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The period a [`Budget`] recurs with.
+pub enum BudgetPeriod {
+    /// A week starting on Monday.
+    Weekly,
+    /// A calendar month.
+    Monthly,
+    /// A calendar year.
+    Yearly,
+}
+
+impl BudgetPeriod {
+    /// Returns the inclusive start and end of the period containing the specified date.
+    ///
+    /// # Parameters
+    ///
+    /// * `date` - the date the period contains
+    pub fn window(&self, date: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let day = date.date_naive();
+        let (start, next_start) = match self {
+            BudgetPeriod::Weekly => {
+                let start = day - Duration::days(day.weekday().num_days_from_monday().into());
+                (start, start + Duration::days(7))
+            },
+            BudgetPeriod::Monthly => {
+                let start = first_day(day.year(), day.month());
+                let next_start = if day.month() == 12 {
+                    first_day(day.year() + 1, 1)
+                } else {
+                    first_day(day.year(), day.month() + 1)
+                };
+                (start, next_start)
+            },
+            BudgetPeriod::Yearly => (first_day(day.year(), 1), first_day(day.year() + 1, 1)),
+        };
+        let midnight = |day: NaiveDate| {
+            Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0).expect("Midnight is a valid time."))
+        };
+        (midnight(start), midnight(next_start) - Duration::nanoseconds(1))
+    }
+}
+
+/// Returns the first day of the specified month.
+fn first_day(year: i32, month: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, 1).expect("The first day of a month is a valid date.")
+}
+
+impl std::fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BudgetPeriod::Weekly => PERIOD_WEEKLY,
+                BudgetPeriod::Monthly => PERIOD_MONTHLY,
+                BudgetPeriod::Yearly => PERIOD_YEARLY,
+            }
+        )
+    }
+}
+
+impl TryFrom<&str> for BudgetPeriod {
+    type Error = HomeworkError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            PERIOD_WEEKLY => Ok(BudgetPeriod::Weekly),
+            PERIOD_MONTHLY => Ok(BudgetPeriod::Monthly),
+            PERIOD_YEARLY => Ok(BudgetPeriod::Yearly),
+            _ => Err(HomeworkError::InternalServerError(InternalError::new(
+                "Invalid budget period",
+                format!("\"{}\" is not a valid budget period.", value),
+                "A stored budget period is invalid.",
+            ))),
+        }
+    }
+}
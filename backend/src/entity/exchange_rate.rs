@@ -0,0 +1,195 @@
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, Signed};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    service::currency_service::validate_currency_code,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// An exchange rate that is valid from a certain date on.
+/// One unit of `source_currency` is worth `rate` units of `target_currency`.
+pub struct ExchangeRate {
+    #[serde(default)]
+    id: Uuid,
+    source_currency: String,
+    target_currency: String,
+    rate: BigDecimal,
+    valid_from: DateTime<Utc>,
+}
+
+impl ExchangeRate {
+    /// Creates a new `ExchangeRate`.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the exchange rate
+    /// * `source_currency` - the currency that is converted
+    /// * `target_currency` - the currency that is converted to
+    /// * `rate` - the units of `target_currency` one unit of `source_currency` is worth
+    /// * `valid_from` - the date from which on the rate is valid
+    pub fn new<T: ToString, U: ToString>(
+        id: Uuid,
+        source_currency: T,
+        target_currency: U,
+        rate: BigDecimal,
+        valid_from: DateTime<Utc>,
+    ) -> Self {
+        ExchangeRate {
+            id,
+            source_currency: source_currency.to_string(),
+            target_currency: target_currency.to_string(),
+            rate,
+            valid_from,
+        }
+    }
+
+    /// Returns the ID of this `ExchangeRate`.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the currency that is converted.
+    pub fn source_currency(&self) -> &str {
+        &self.source_currency
+    }
+
+    /// Returns the currency that is converted to.
+    pub fn target_currency(&self) -> &str {
+        &self.target_currency
+    }
+
+    /// Returns the units of the target currency one unit of the source currency is worth.
+    pub fn rate(&self) -> &BigDecimal {
+        &self.rate
+    }
+
+    /// Returns the date from which on the rate is valid.
+    pub fn valid_from(&self) -> DateTime<Utc> {
+        self.valid_from
+    }
+
+    pub fn set_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+
+    pub fn select_all_from_database(
+        connection: &Connection,
+    ) -> Result<Vec<ExchangeRate>, HomeworkError> {
+        let mut stmt = connection.prepare(
+            "SELECT id, source_currency, target_currency, rate, valid_from FROM exchange_rate ORDER BY valid_from",
+        )?;
+        let rate_query = stmt.query_and_then([], |row| ExchangeRate::try_from(row))?;
+        let mut rates = Vec::new();
+        for rate in rate_query {
+            rates.push(rate?);
+        }
+        Ok(rates)
+    }
+
+    /// Validates the exchange rate and inserts it into the database.
+    /// The currency codes are normalised before insertion.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn insert_into_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        if Self::exists_in_database_by_id(self.id(), connection)? {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Exchange rate already exists",
+                format!("The exchange rate {} already exists.", self.id()),
+                "The exchange rate already exists.",
+            )));
+        }
+        let source_currency = validate_currency_code(self.source_currency())?;
+        let target_currency = validate_currency_code(self.target_currency())?;
+        if source_currency == target_currency {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid exchange rate",
+                format!("The exchange rate {:?} converts a currency to itself.", self),
+                "An exchange rate must convert between two different currencies.",
+            )));
+        }
+        if !self.rate().is_positive() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid exchange rate",
+                format!("The rate of exchange rate {:?} is not positive.", self),
+                "An exchange rate must be positive.",
+            )));
+        }
+
+        connection.execute(
+            "INSERT INTO exchange_rate (id, source_currency, target_currency, rate, valid_from, creation_time) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![self.id(), source_currency, target_currency, self.rate().to_string(), self.valid_from(), chrono::Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+
+        connection.execute("DELETE FROM exchange_rate WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn exists_in_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<bool, rusqlite::Error> {
+        let mut stmt = connection.prepare("SELECT 1 FROM exchange_rate WHERE id = ?1")?;
+        stmt.exists([id])
+    }
+
+    /// Automatically throws a ```Not Found``` if the entry does not exist.
+    /// Returns an ```Ok``` otherwise.
+    ///
+    /// Parameters
+    ///
+    /// * ```id``` - the ID of the exchange rate
+    /// * ```connection``` - the database connection
+    pub fn exists_in_database_by_id_throw_not_found(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        if !Self::exists_in_database_by_id(id, connection)? {
+            Err(HomeworkError::NotFoundError(InternalError::new(
+                "Exchange rate not found",
+                format!("The exchange rate {} does not exist.", id),
+                "The exchange rate does not exist.",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl TryFrom<&Row<'_>> for ExchangeRate {
+    type Error = HomeworkError;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let rate: String = row.get(3)?;
+        let rate = BigDecimal::from_str(&rate).map_err(|err| {
+            HomeworkError::InternalServerError(InternalError::new(
+                "Invalid exchange rate",
+                format!("The stored exchange rate {} could not be parsed: {}", rate, err),
+                "A stored exchange rate is invalid.",
+            ))
+        })?;
+        Ok(ExchangeRate {
+            id: row.get(0)?,
+            source_currency: row.get(1)?,
+            target_currency: row.get(2)?,
+            rate,
+            valid_from: row.get(4)?,
+        })
+    }
+}
@@ -1,9 +1,20 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::application::error::{HomeworkError, InternalError};
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    service::{
+        batch_loader_service::load_grouped_by_ids,
+        quantity_service::{Fraction, Quantity},
+        search_service::index_recipe,
+        sub_recipe_service::validate_sub_recipe,
+        unit_service::{Measure, Unit, UnitSystem},
+    },
+};
 
 use super::recipe::Recipe;
 
@@ -19,9 +30,53 @@ pub struct Ingredient {
     recipe_id: Uuid,
     ordering: i32,
     filter_text: Option<String>,
+    /// The density in grams per millilitre used to convert between volume and mass.
+    #[serde(default)]
+    density: Option<String>,
+    /// The exact quantity parsed from the amount if the amount describes a quantity.
+    #[serde(default, skip_deserializing)]
+    quantity: Option<Quantity>,
+    /// The scaled ingredients of the referenced recipe if sub-recipes have been expanded.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    sub_ingredients: Vec<Ingredient>,
 }
 
 impl Ingredient {
+    /// Creates a new `Ingredient` that does not reference another recipe.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the ingredient
+    /// * `recipe_id` - the ID of the recipe the ingredient belongs to
+    /// * `amount` - the amount of the ingredient
+    /// * `unit` - the unit of the amount
+    /// * `text` - the description of the ingredient
+    /// * `ordering` - the position of the ingredient within the recipe
+    pub fn new<T: ToString, U: ToString, V: ToString>(
+        id: Uuid,
+        recipe_id: Uuid,
+        amount: T,
+        unit: U,
+        text: V,
+        ordering: i32,
+    ) -> Self {
+        let amount = amount.to_string();
+        Ingredient {
+            id,
+            quantity: Quantity::parse(&amount),
+            amount,
+            unit: unit.to_string(),
+            text: text.to_string(),
+            creation_time: Utc::now(),
+            recipe_reference: None,
+            recipe_id,
+            ordering,
+            filter_text: None,
+            density: None,
+            sub_ingredients: Vec::new(),
+        }
+    }
+
     pub fn id(&self) -> Uuid {
         self.id
     }
@@ -51,7 +106,96 @@ impl Ingredient {
     }
 
     pub fn filter_text(&self) -> Option<&str> {
-        self.filter_text.as_ref().map(|value| value.as_str())
+        self.filter_text.as_deref()
+    }
+
+    /// Returns the density of this `Ingredient` in grams per millilitre as entered.
+    pub fn density(&self) -> Option<&str> {
+        self.density.as_deref()
+    }
+
+    /// Returns the exact density of this `Ingredient` if a valid density is known.
+    pub fn density_fraction(&self) -> Option<Fraction> {
+        self.density()
+            .and_then(Fraction::parse)
+            .filter(|density| !density.is_zero())
+    }
+
+    /// Returns the exact quantity parsed from the amount of this `Ingredient`
+    /// or `None` if the amount does not describe a quantity.
+    pub fn quantity(&self) -> Option<Quantity> {
+        // Deserialized ingredients have not been parsed yet.
+        self.quantity.or_else(|| Quantity::parse(self.amount()))
+    }
+
+    /// Returns the expanded ingredients of the sub-recipe referenced by this `Ingredient`.
+    pub fn into_sub_ingredients(self) -> Vec<Ingredient> {
+        self.sub_ingredients
+    }
+
+    /// Multiplies the quantity of this `Ingredient` and of its sub-ingredients by the factor
+    /// and replaces the amount with the scaled quantity. Amounts that do not describe
+    /// a quantity are kept as they are.
+    ///
+    /// # Parameters
+    ///
+    /// * `factor` - the factor to multiply the quantity with
+    pub fn scale(&mut self, factor: &Fraction) -> Result<(), HomeworkError> {
+        for sub_ingredient in self.sub_ingredients.iter_mut() {
+            sub_ingredient.scale(factor)?;
+        }
+        if let Some(quantity) = self.quantity() {
+            let scaled = quantity.checked_scale(factor).ok_or_else(|| {
+                HomeworkError::BadRequestError(InternalError::new(
+                    "Invalid scaling",
+                    format!(
+                        "The quantity {} of ingredient {} cannot be scaled by {}.",
+                        quantity,
+                        self.id(),
+                        factor
+                    ),
+                    "The ingredient quantity is too large to be scaled.",
+                ))
+            })?;
+            self.amount = scaled.to_string();
+            self.quantity = Some(scaled);
+        }
+        Ok(())
+    }
+
+    /// Converts the quantity of this `Ingredient` and of its sub-ingredients into the most
+    /// readable unit of the specified system and replaces the amount and unit accordingly.
+    /// Ingredients without a quantity or with an unknown unit are kept as they are.
+    ///
+    /// # Parameters
+    ///
+    /// * `system` - the system of units to convert to
+    pub fn convert_units(&mut self, system: UnitSystem) -> Result<(), HomeworkError> {
+        for sub_ingredient in self.sub_ingredients.iter_mut() {
+            sub_ingredient.convert_units(system)?;
+        }
+        if let (Some(quantity), Some(unit)) = (self.quantity(), Unit::parse(self.unit())) {
+            let measure = Measure::new(quantity, unit)
+                .convert_to_system(system, self.density_fraction().as_ref())
+                .ok_or_else(|| {
+                    HomeworkError::BadRequestError(InternalError::new(
+                        "Invalid conversion",
+                        format!(
+                            "The quantity {} {} of ingredient {} cannot be converted.",
+                            quantity,
+                            unit,
+                            self.id()
+                        ),
+                        "The ingredient quantity is too large to be converted.",
+                    ))
+                })?;
+            if measure != Measure::new(quantity, unit) {
+                self.amount = measure.quantity().to_string();
+                self.unit = measure.unit().to_string();
+                self.quantity = Some(measure.quantity());
+            }
+        }
+        Ok(())
     }
 
     pub fn set_id(&mut self, id: Uuid) {
@@ -62,18 +206,23 @@ impl Ingredient {
         self.recipe_id = recipe_id;
     }
 
+    /// Sets the expanded ingredients of the sub-recipe referenced by this `Ingredient`.
+    pub fn set_sub_ingredients(&mut self, sub_ingredients: Vec<Ingredient>) {
+        self.sub_ingredients = sub_ingredients;
+    }
+
     pub fn select_from_database_by_recipe_id(
         recipe_id: Uuid,
         connection: &Connection,
     ) -> Result<Vec<Ingredient>, rusqlite::Error> {
         let mut ingredient_stmt = connection.prepare(
             "
-                SELECT id, amount, unit, text, creation_time, recipe_reference, recipe_id, ordering, filter_text
+                SELECT id, amount, unit, text, creation_time, recipe_reference, recipe_id, ordering, filter_text, density
                 FROM ingredient
                 WHERE recipe_id = ?1",
         )?;
         let ingredient_rows =
-            ingredient_stmt.query_map([recipe_id], |row| Ok(Ingredient::try_from(row)?))?;
+            ingredient_stmt.query_map([recipe_id], |row| Ingredient::try_from(row))?;
 
         let mut ingredients = Vec::new();
         for ingredient in ingredient_rows {
@@ -82,6 +231,29 @@ impl Ingredient {
         Ok(ingredients)
     }
 
+    /// Returns the ingredients of all specified recipes grouped by the ID of their recipe
+    /// with a single query.
+    ///
+    /// # Parameters
+    ///
+    /// * `recipe_ids` - the IDs of the recipes
+    /// * `connection` - the database connection
+    pub fn select_from_database_by_recipe_ids(
+        recipe_ids: &[Uuid],
+        connection: &Connection,
+    ) -> Result<HashMap<Uuid, Vec<Ingredient>>, rusqlite::Error> {
+        load_grouped_by_ids(
+            "
+                SELECT id, amount, unit, text, creation_time, recipe_reference, recipe_id, ordering, filter_text, density
+                FROM ingredient
+                WHERE recipe_id IN ({ids})
+                ORDER BY rowid",
+            recipe_ids,
+            connection,
+            |row| Ok((row.get(6)?, Ingredient::try_from(row)?)),
+        )
+    }
+
     pub fn insert_into_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
         if Self::exists_in_database_by_id(self.id(), connection)? {
             return Err(HomeworkError::NotFoundError(InternalError::new(
@@ -103,16 +275,22 @@ impl Ingredient {
             )));
         }
 
+        self.validate_density()?;
+        if let Some(sub_recipe_id) = self.recipe_reference() {
+            validate_sub_recipe(self.recipe_id(), sub_recipe_id, connection)?;
+        }
+
         connection.execute(
-            "INSERT INTO ingredient (id, amount, unit, text, creation_time, recipe_reference, recipe_id, ordering, filter_text) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            params![self.id(), self.amount(), self.unit(), self.text(), chrono::Utc::now(), self.recipe_reference(), self.recipe_id(), self.ordering(), self.filter_text()],
+            "INSERT INTO ingredient (id, amount, unit, text, creation_time, recipe_reference, recipe_id, ordering, filter_text, density) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![self.id(), self.amount(), self.unit(), self.text(), chrono::Utc::now(), self.recipe_reference(), self.recipe_id(), self.ordering(), self.filter_text(), self.density()],
         )?;
+        index_recipe(self.recipe_id(), connection)?;
 
         Ok(())
     }
 
     pub fn update_in_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(self.id(), &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(self.id(), connection)?;
 
         if !Recipe::exists_in_database_by_id(self.recipe_id(), connection)? {
             return Err(HomeworkError::NotFoundError(InternalError::new(
@@ -126,10 +304,16 @@ impl Ingredient {
             )));
         }
 
+        self.validate_density()?;
+        if let Some(sub_recipe_id) = self.recipe_reference() {
+            validate_sub_recipe(self.recipe_id(), sub_recipe_id, connection)?;
+        }
+
         connection.execute(
-            "UPDATE ingredient SET amount = ?1, unit = ?2, text = ?3, recipe_reference = ?4, ordering = ?5, filter_text = ?6 WHERE id = ?7",
-            params![self.amount(), self.unit(), self.text(), self.recipe_reference(), self.ordering(), self.filter_text(), self.id()],
+            "UPDATE ingredient SET amount = ?1, unit = ?2, text = ?3, recipe_reference = ?4, ordering = ?5, filter_text = ?6, density = ?7 WHERE id = ?8",
+            params![self.amount(), self.unit(), self.text(), self.recipe_reference(), self.ordering(), self.filter_text(), self.density(), self.id()],
         )?;
+        index_recipe(self.recipe_id(), connection)?;
 
         Ok(())
     }
@@ -139,7 +323,7 @@ impl Ingredient {
         id: Uuid,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
         connection
             .execute("UPDATE ingredient SET ordering = ?1 WHERE id = ?2", params![ordering, id])?;
@@ -151,9 +335,15 @@ impl Ingredient {
         id: Uuid,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
+        let recipe_id: Uuid = connection.query_row(
+            "SELECT recipe_id FROM ingredient WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
         connection.execute("DELETE FROM ingredient WHERE id = ?1", params![id])?;
+        index_recipe(recipe_id, connection)?;
         Ok(())
     }
 
@@ -165,6 +355,24 @@ impl Ingredient {
         stmt.exists([ingredient_id])
     }
 
+    /// Checks that the density is a positive number if specified.
+    fn validate_density(&self) -> Result<(), HomeworkError> {
+        match self.density() {
+            Some(density) if !density.trim().is_empty() && self.density_fraction().is_none() => {
+                Err(HomeworkError::BadRequestError(InternalError::new(
+                    "Invalid density",
+                    format!(
+                        "The density \"{}\" of ingredient {} is not a positive number.",
+                        density,
+                        self.id()
+                    ),
+                    "The density must be a positive number.",
+                )))
+            },
+            _ => Ok(()),
+        }
+    }
+
     /// Automatically throws a ```Not Found``` if the entry does not exist.
     /// Returns an ```Ok``` otherwise.
     ///
@@ -176,7 +384,7 @@ impl Ingredient {
         ingredient_id: Uuid,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        if !Self::exists_in_database_by_id(ingredient_id, &connection)? {
+        if !Self::exists_in_database_by_id(ingredient_id, connection)? {
             Err(HomeworkError::NotFoundError(InternalError::new(
                 "Ingredient not found",
                 format!("The ingredient {} does not exist.", ingredient_id),
@@ -192,9 +400,11 @@ impl TryFrom<&Row<'_>> for Ingredient {
     type Error = rusqlite::Error;
 
     fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let amount: String = row.get(1)?;
         Ok(Ingredient {
             id: row.get(0)?,
-            amount: row.get(1)?,
+            quantity: Quantity::parse(&amount),
+            amount,
             unit: row.get(2)?,
             text: row.get(3)?,
             creation_time: row.get(4)?,
@@ -202,6 +412,8 @@ impl TryFrom<&Row<'_>> for Ingredient {
             recipe_id: row.get(6)?,
             ordering: row.get(7)?,
             filter_text: row.get(8)?,
+            density: row.get(9)?,
+            sub_ingredients: Vec::new(),
         })
     }
 }
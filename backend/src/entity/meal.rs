@@ -0,0 +1,323 @@
+use chrono::{NaiveDate, NaiveTime};
+use rusqlite::{params, Connection, Params, Row};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::application::error::{HomeworkError, InternalError};
+
+use super::recipe::Recipe;
+
+const SLOT_BREAKFAST: &str = "Breakfast";
+const SLOT_LUNCH: &str = "Lunch";
+const SLOT_DINNER: &str = "Dinner";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A [`Recipe`] planned for a meal at a certain date.
+pub struct Meal {
+    #[serde(default)]
+    id: Uuid,
+    date: NaiveDate,
+    slot: MealSlot,
+    recipe_id: Uuid,
+    /// The title of the recipe, which is only provided when reading meals.
+    #[serde(default, skip_deserializing)]
+    recipe_title: String,
+    servings: Option<u32>,
+}
+
+impl Meal {
+    /// Returns the ID of this `Meal`.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the date of this `Meal`.
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// Returns the slot of this `Meal`.
+    pub fn slot(&self) -> MealSlot {
+        self.slot
+    }
+
+    /// Returns the ID of the [`Recipe`] planned for this `Meal`.
+    pub fn recipe_id(&self) -> Uuid {
+        self.recipe_id
+    }
+
+    /// Returns the title of the [`Recipe`] planned for this `Meal`.
+    pub fn recipe_title(&self) -> &str {
+        &self.recipe_title
+    }
+
+    /// Returns the number of servings planned for this `Meal`.
+    /// If not specified, the servings of the recipe are cooked.
+    pub fn servings(&self) -> Option<u32> {
+        self.servings
+    }
+
+    pub fn set_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+
+    pub fn select_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<Meal, HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        connection.query_row_and_then(
+            "SELECT meal.id, meal.date, meal.slot, meal.recipe_id, recipe.title, meal.servings
+                FROM meal
+                INNER JOIN recipe ON recipe.id = meal.recipe_id
+                WHERE meal.id = ?1",
+            [id],
+            |row| Meal::try_from(row),
+        )
+    }
+
+    /// Returns all meals ordered by date and slot.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn select_all_from_database(connection: &Connection) -> Result<Vec<Meal>, HomeworkError> {
+        Self::select_from_database_where("1 = 1", [], connection)
+    }
+
+    /// Returns all meals within the inclusive date range ordered by date and slot.
+    ///
+    /// # Parameters
+    ///
+    /// * `from` - the first date of the range
+    /// * `to` - the last date of the range
+    /// * `connection` - the database connection
+    pub fn select_from_database_by_date_range(
+        from: NaiveDate,
+        to: NaiveDate,
+        connection: &Connection,
+    ) -> Result<Vec<Meal>, HomeworkError> {
+        if to < from {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid date range",
+                format!("The end {} of the date range lies before its start {}.", to, from),
+                "The end of the date range must not lie before its start.",
+            )));
+        }
+        Self::select_from_database_where(
+            "meal.date BETWEEN ?1 AND ?2",
+            params![from, to],
+            connection,
+        )
+    }
+
+    /// Returns all meals matching the condition ordered by date and slot.
+    fn select_from_database_where<P: Params>(
+        condition: &str,
+        params: P,
+        connection: &Connection,
+    ) -> Result<Vec<Meal>, HomeworkError> {
+        let mut stmt = connection.prepare(&format!(
+            "SELECT meal.id, meal.date, meal.slot, meal.recipe_id, recipe.title, meal.servings
+                FROM meal
+                INNER JOIN recipe ON recipe.id = meal.recipe_id
+                WHERE {}",
+            condition
+        ))?;
+        let meal_query = stmt.query_and_then(params, |row| Meal::try_from(row))?;
+        let mut meals = Vec::new();
+        for meal in meal_query {
+            meals.push(meal?);
+        }
+        meals.sort_by_key(|meal| (meal.date(), meal.slot()));
+        Ok(meals)
+    }
+
+    /// Validates the meal and inserts it into the database.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn insert_into_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        if Self::exists_in_database_by_id(self.id(), connection)? {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Meal already exists",
+                format!("The meal {} already exists.", self.id()),
+                "The meal already exists.",
+            )));
+        }
+        self.validate(connection)?;
+
+        connection.execute(
+            "INSERT INTO meal (id, date, slot, recipe_id, servings, creation_time) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![self.id(), self.date(), self.slot().to_string(), self.recipe_id(), self.servings(), chrono::Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    /// Validates the meal and replaces the stored meal with the same ID.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn update_in_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(self.id(), connection)?;
+        self.validate(connection)?;
+
+        connection.execute(
+            "UPDATE meal SET date = ?1, slot = ?2, recipe_id = ?3, servings = ?4 WHERE id = ?5",
+            params![
+                self.date(),
+                self.slot().to_string(),
+                self.recipe_id(),
+                self.servings(),
+                self.id()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+
+        connection.execute("DELETE FROM meal WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn exists_in_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<bool, rusqlite::Error> {
+        let mut stmt = connection.prepare("SELECT 1 FROM meal WHERE id = ?1")?;
+        stmt.exists([id])
+    }
+
+    /// Automatically throws a ```Not Found``` if the entry does not exist.
+    /// Returns an ```Ok``` otherwise.
+    ///
+    /// Parameters
+    ///
+    /// * ```id``` - the ID of the meal
+    /// * ```connection``` - the database connection
+    pub fn exists_in_database_by_id_throw_not_found(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        if !Self::exists_in_database_by_id(id, connection)? {
+            Err(HomeworkError::NotFoundError(InternalError::new(
+                "Meal not found",
+                format!("The meal {} does not exist.", id),
+                "The meal does not exist.",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that the planned recipe exists and that the number of servings is positive.
+    /// Servings may only be planned for recipes that specify servings, as the recipe
+    /// cannot be scaled otherwise.
+    fn validate(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        Recipe::exists_in_database_by_id_throw_not_found(self.recipe_id(), connection)?;
+        if self.servings() == Some(0) {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid servings",
+                format!("The meal {:?} has zero servings.", self),
+                "The number of servings must be positive.",
+            )));
+        }
+        if self.servings().is_some() {
+            let recipe_servings: Option<u32> = connection.query_row(
+                "SELECT servings FROM recipe WHERE id = ?1",
+                [self.recipe_id()],
+                |row| row.get(0),
+            )?;
+            if recipe_servings.is_none() {
+                return Err(HomeworkError::BadRequestError(InternalError::new(
+                    "Invalid servings",
+                    format!(
+                        "The meal {:?} plans servings of the recipe {}, which does not specify servings.",
+                        self,
+                        self.recipe_id()
+                    ),
+                    "Servings can only be planned for recipes that specify a number of servings.",
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&Row<'_>> for Meal {
+    type Error = HomeworkError;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let slot: String = row.get(2)?;
+        Ok(Meal {
+            id: row.get(0)?,
+            date: row.get(1)?,
+            slot: MealSlot::try_from(slot.as_str())?,
+            recipe_id: row.get(3)?,
+            recipe_title: row.get(4)?,
+            servings: row.get(5)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+/// The time of day a [`Meal`] is eaten at.
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+}
+
+impl MealSlot {
+    /// Returns the usual start time of meals of this slot.
+    pub fn start_time(&self) -> NaiveTime {
+        let hour = match self {
+            MealSlot::Breakfast => 8,
+            MealSlot::Lunch => 12,
+            MealSlot::Dinner => 18,
+        };
+        NaiveTime::from_hms_opt(hour, 0, 0).expect("The start times are valid.")
+    }
+}
+
+impl std::fmt::Display for MealSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MealSlot::Breakfast => SLOT_BREAKFAST,
+                MealSlot::Lunch => SLOT_LUNCH,
+                MealSlot::Dinner => SLOT_DINNER,
+            }
+        )
+    }
+}
+
+impl TryFrom<&str> for MealSlot {
+    type Error = HomeworkError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            SLOT_BREAKFAST => Ok(MealSlot::Breakfast),
+            SLOT_LUNCH => Ok(MealSlot::Lunch),
+            SLOT_DINNER => Ok(MealSlot::Dinner),
+            _ => Err(HomeworkError::InternalServerError(InternalError::new(
+                "Invalid meal slot",
+                format!("\"{}\" is not a valid meal slot.", value),
+                "A stored meal slot is invalid.",
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,167 @@
+use serde_json::json;
+
+use crate::{
+    application::config::Configuration,
+    entity::ingredient::Ingredient,
+    service::shopping_list_service::{expand_ingredients, merge_ingredients, RecipeSelection},
+};
+
+use super::*;
+
+/// Creates a meal of the recipe.
+fn meal(id: u128, date: &str, slot: &str, recipe_id: Uuid, servings: Option<u32>) -> Meal {
+    serde_json::from_value(json!({
+        "id": Uuid::from_u128(id),
+        "date": date,
+        "slot": slot,
+        "recipeId": recipe_id,
+        "servings": servings,
+    }))
+    .unwrap()
+}
+
+/// Inserts a recipe with the servings and a single ingredient.
+fn insert_recipe(
+    id: Uuid,
+    title: &str,
+    servings: Option<u32>,
+    amount: &str,
+    ingredient: &str,
+    connection: &Connection,
+) {
+    Recipe::insert_into_database_new_entry(id, title, connection).unwrap();
+    Recipe::update_in_database_servings(id, servings, connection).unwrap();
+    let ingredient: Ingredient = serde_json::from_value(json!({
+        "id": Configuration::generate_uuid(),
+        "amount": amount,
+        "unit": "g",
+        "text": ingredient,
+        "creationTime": "2022-01-01T00:00:00Z",
+        "recipeId": id,
+        "ordering": 0,
+    }))
+    .unwrap();
+    ingredient.insert_into_database(connection).unwrap();
+}
+
+#[test]
+/// Tests if meals are validated on insert and update.
+fn test_validate() {
+    let connection = Configuration::in_memory_database_connection();
+    let (scalable, unscalable) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    insert_recipe(scalable, "Pasta", Some(2), "200", "Pasta", &connection);
+    insert_recipe(unscalable, "Bread", None, "500", "Flour", &connection);
+
+    assert!(matches!(
+        meal(1, "2023-01-02", "Lunch", Uuid::from_u128(3), None).insert_into_database(&connection),
+        Err(HomeworkError::NotFoundError(_))
+    ));
+    assert!(matches!(
+        meal(1, "2023-01-02", "Lunch", scalable, Some(0)).insert_into_database(&connection),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    assert!(matches!(
+        meal(1, "2023-01-02", "Lunch", unscalable, Some(4)).insert_into_database(&connection),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    meal(1, "2023-01-02", "Lunch", unscalable, None)
+        .insert_into_database(&connection)
+        .unwrap();
+    assert!(matches!(
+        meal(1, "2023-01-02", "Lunch", unscalable, None).insert_into_database(&connection),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+
+    assert!(matches!(
+        meal(1, "2023-01-02", "Lunch", unscalable, Some(4)).update_in_database(&connection),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    assert!(matches!(
+        meal(2, "2023-01-02", "Lunch", scalable, Some(4)).update_in_database(&connection),
+        Err(HomeworkError::NotFoundError(_))
+    ));
+    meal(1, "2023-01-02", "Lunch", scalable, Some(4))
+        .update_in_database(&connection)
+        .unwrap();
+    let stored = Meal::select_from_database_by_id(Uuid::from_u128(1), &connection).unwrap();
+    assert_eq!(stored.recipe_id(), scalable);
+    assert_eq!(stored.recipe_title(), "Pasta");
+    assert_eq!(stored.servings(), Some(4));
+}
+
+#[test]
+/// Tests if the `select_from_database_by_date_range` function returns the meals of the
+/// inclusive range ordered by date and slot.
+fn test_select_from_database_by_date_range() {
+    let connection = Configuration::in_memory_database_connection();
+    let recipe = Uuid::from_u128(1);
+    insert_recipe(recipe, "Pasta", Some(2), "200", "Pasta", &connection);
+    for (id, date, slot) in [
+        (1, "2023-01-01", "Dinner"),
+        (2, "2023-01-03", "Dinner"),
+        (3, "2023-01-02", "Dinner"),
+        (4, "2023-01-02", "Breakfast"),
+        (5, "2023-01-04", "Lunch"),
+    ] {
+        meal(id, date, slot, recipe, None)
+            .insert_into_database(&connection)
+            .unwrap();
+    }
+
+    let meals = Meal::select_from_database_by_date_range(
+        NaiveDate::from_ymd_opt(2023, 1, 2).unwrap(),
+        NaiveDate::from_ymd_opt(2023, 1, 3).unwrap(),
+        &connection,
+    )
+    .unwrap();
+    assert_eq!(
+        meals.iter().map(Meal::id).collect::<Vec<_>>(),
+        vec![Uuid::from_u128(4), Uuid::from_u128(3), Uuid::from_u128(2)]
+    );
+
+    assert!(matches!(
+        Meal::select_from_database_by_date_range(
+            NaiveDate::from_ymd_opt(2023, 1, 3).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 1, 2).unwrap(),
+            &connection,
+        ),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+}
+
+#[test]
+/// Tests if the shopping list of a meal plan scales each meal to its planned servings.
+fn test_meal_plan_shopping_list() {
+    let connection = Configuration::in_memory_database_connection();
+    let (pasta, bread) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    insert_recipe(pasta, "Pasta", Some(2), "200", "Pasta", &connection);
+    insert_recipe(bread, "Bread", None, "500", "Flour", &connection);
+    for (id, date, recipe, servings) in [
+        (1, "2023-01-02", pasta, Some(3)),
+        (2, "2023-01-03", pasta, None),
+        (3, "2023-01-03", bread, None),
+        (4, "2023-01-05", pasta, Some(8)),
+    ] {
+        meal(id, date, "Dinner", recipe, servings)
+            .insert_into_database(&connection)
+            .unwrap();
+    }
+
+    let selections: Vec<RecipeSelection> = Meal::select_from_database_by_date_range(
+        NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+        NaiveDate::from_ymd_opt(2023, 1, 4).unwrap(),
+        &connection,
+    )
+    .unwrap()
+    .iter()
+    .map(|meal| RecipeSelection::new(meal.recipe_id(), meal.servings()))
+    .collect();
+    let items = merge_ingredients(&expand_ingredients(&selections, &connection).unwrap());
+    assert_eq!(
+        items
+            .iter()
+            .map(|item| (item.name(), item.amount(), item.unit()))
+            .collect::<Vec<_>>(),
+        vec![("Pasta", "500", "g"), ("Flour", "500", "g")]
+    );
+}
@@ -1,14 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, Signed};
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::application::error::{HomeworkError, InternalError};
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    service::{
+        batch_loader_service::load_grouped_by_ids,
+        currency_service::validate_currency_code,
+        pagination_service::{select_page, Conditions, Page, Pagination, SortDirection},
+        split_service,
+    },
+};
 
-use super::attachment::Attachment;
+use super::{attachment::Attachment, person::Person};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -19,53 +27,202 @@ pub struct Payment {
     paid: HashMap<String, BigDecimal>,
     involved: HashMap<String, BigDecimal>,
     payment_type: PaymentType,
+    split: Option<SplitMode>,
+    currency: String,
+    #[serde(default)]
+    transfer: bool,
     tags: Vec<String>,
     attachments: Vec<Attachment>,
     creation_time: DateTime<Utc>,
 }
 
 impl Payment {
+    /// Returns the ID of this `Payment`.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the target of this `Payment`.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the amounts paid by each person.
+    pub fn paid(&self) -> &HashMap<String, BigDecimal> {
+        &self.paid
+    }
+
+    /// Returns the amounts each person is involved with.
+    pub fn involved(&self) -> &HashMap<String, BigDecimal> {
+        &self.involved
+    }
+
+    /// Returns the [`PaymentType`] of this `Payment`.
+    pub fn payment_type(&self) -> &PaymentType {
+        &self.payment_type
+    }
+
+    /// Returns the [`SplitMode`] of this `Payment` if any.
+    pub fn split(&self) -> Option<&SplitMode> {
+        self.split.as_ref()
+    }
+
+    /// Returns the currency of all amounts of this `Payment`.
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// Returns the IDs of all persons this `Payment` references.
+    pub fn persons(&self) -> HashSet<String> {
+        self.paid
+            .keys()
+            .chain(self.involved.keys())
+            .cloned()
+            .chain(
+                self.split
+                    .iter()
+                    .flat_map(|split| split.persons().into_iter().map(str::to_string)),
+            )
+            .collect()
+    }
+
+    /// Returns the tags of this `Payment`.
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+
+    /// Returns `true` if this `Payment` transfers money between persons to settle
+    /// their balances instead of spending it.
+    pub fn is_transfer(&self) -> bool {
+        self.transfer
+    }
+
     pub fn select_from_database_by_id(
         payment_id: Uuid,
         connection: &Connection,
     ) -> Result<Payment, HomeworkError> {
-        if !Payment::exists_in_database_by_id(payment_id, &connection)? {
+        if !Payment::exists_in_database_by_id(payment_id, connection)? {
             return Err(HomeworkError::NotFoundError(InternalError::new(
                 "Payment not found",
                 format!("The payment {} does not exist.", payment_id),
                 "The payment does not exist.",
             )));
         }
-        connection
-            .query_row_and_then("SELECT id, target, note, paid, involved, payment_type, creation_time FROM payment WHERE id = ?1",
+        let mut payment = connection
+            .query_row_and_then("SELECT id, target, note, paid, involved, payment_type, creation_time, split, currency, transfer FROM payment WHERE id = ?1",
             [payment_id],
-            |row| Payment::try_from((row, connection)))
+            |row| Payment::try_from(row))?;
+        Payment::load_relations(std::slice::from_mut(&mut payment), connection)?;
+        Ok(payment)
     }
 
     pub fn select_all_from_database(
         connection: &Connection,
     ) -> Result<Vec<Payment>, HomeworkError> {
         let mut stmt_payment = connection.prepare(
-            "SELECT id, target, note, paid, involved, payment_type, creation_time FROM payment",
+            "SELECT id, target, note, paid, involved, payment_type, creation_time, split, currency, transfer FROM payment",
         )?;
-        let payment_query =
-            stmt_payment.query_and_then([], |row| Payment::try_from((row, connection)))?;
+        let payment_query = stmt_payment.query_and_then([], |row| Payment::try_from(row))?;
         let mut payments = Vec::new();
         for payment in payment_query {
             payments.push(payment?);
         }
+        Payment::load_relations(&mut payments, connection)?;
         Ok(payments)
     }
 
+    /// Returns a page of all payments matching the filter.
+    ///
+    /// # Parameters
+    ///
+    /// * `filter` - the filters and sort order
+    /// * `pagination` - the page to select
+    /// * `connection` - the database connection
+    pub fn select_page_from_database(
+        filter: &PaymentFilter,
+        pagination: &Pagination,
+        connection: &Connection,
+    ) -> Result<Page<Payment>, HomeworkError> {
+        let mut conditions = Conditions::default();
+        if let Some(tag) = &filter.tag {
+            conditions.push(
+                "EXISTS (SELECT 1 FROM tag_payment_mapping WHERE payment_id = payment.id AND tag = ?)",
+                tag.trim().to_string(),
+            );
+        }
+        if let Some(from) = filter.from {
+            conditions.push(&format!("{} >= julianday(?)", PAYMENT_DATE_SQL), from);
+        }
+        if let Some(to) = filter.to {
+            conditions.push(&format!("{} <= julianday(?)", PAYMENT_DATE_SQL), to);
+        }
+        let order = match filter.sort {
+            PaymentSortField::Date => format!("{} {}, id", PAYMENT_DATE_SQL, filter.direction),
+            PaymentSortField::CreationTime => format!("creation_time {}, id", filter.direction),
+            PaymentSortField::Target => {
+                format!("target COLLATE NOCASE {}, {}, id", filter.direction, PAYMENT_DATE_SQL)
+            },
+        };
+        let mut page = select_page(
+            "SELECT id, target, note, paid, involved, payment_type, creation_time, split, currency, transfer FROM payment",
+            "payment",
+            &conditions,
+            &order,
+            pagination,
+            connection,
+            |row| Payment::try_from(row),
+        )?;
+        Payment::load_relations(page.items_mut(), connection)?;
+        Ok(page)
+    }
+
+    /// Loads the tags and attachments of all payments with a single query per relation
+    /// instead of one query per payment.
+    ///
+    /// # Parameters
+    ///
+    /// * `payments` - the payments without relations
+    /// * `connection` - the database connection
+    fn load_relations(
+        payments: &mut [Payment],
+        connection: &Connection,
+    ) -> Result<(), rusqlite::Error> {
+        let ids: Vec<Uuid> = payments.iter().map(|payment| payment.id).collect();
+        let mut tags = load_grouped_by_ids(
+            "SELECT payment_id, tag FROM tag_payment_mapping WHERE payment_id IN ({ids}) ORDER BY id",
+            &ids,
+            connection,
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let mut attachments = load_grouped_by_ids(
+            "
+                SELECT attachment.id, attachment.name, attachment.creation_time, attachment_payment_mapping.payment_id
+                FROM attachment
+                INNER JOIN attachment_payment_mapping
+                    ON attachment.id = attachment_payment_mapping.attachment_id
+                WHERE attachment_payment_mapping.payment_id IN ({ids})
+                ORDER BY attachment_payment_mapping.id",
+            &ids,
+            connection,
+            |row| Ok((row.get(3)?, Attachment::try_from(row)?)),
+        )?;
+        for payment in payments.iter_mut() {
+            payment.tags = tags.remove(&payment.id).unwrap_or_default();
+            payment.attachments = attachments.remove(&payment.id).unwrap_or_default();
+        }
+        Ok(())
+    }
+
     pub fn insert_into_database_new_entry(
         id: Uuid,
         target: &str,
+        currency: &str,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
         let paid_empty: HashMap<String, BigDecimal> = HashMap::new();
         let involved_empty: HashMap<String, BigDecimal> = HashMap::new();
         connection.execute(
-            "INSERT INTO payment (id, target, note, paid, involved, payment_type, creation_time) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO payment (id, target, note, paid, involved, payment_type, creation_time, currency) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![
                 id,
                 target,
@@ -73,7 +230,92 @@ impl Payment {
                 serde_json::to_value(&paid_empty)?,
                 serde_json::to_value(&involved_empty)?,
                 serde_json::to_value(&PaymentType::OneOff{ start: chrono::Utc::now() })?,
-                chrono::Utc::now()
+                chrono::Utc::now(),
+                validate_currency_code(currency)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Inserts a one-off payment transferring money from one person to another
+    /// to settle their balances.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the payment
+    /// * `from` - the person paying
+    /// * `to` - the person receiving the money
+    /// * `amount` - the transferred amount
+    /// * `currency` - the currency of the amount
+    /// * `date` - the date of the transfer
+    /// * `connection` - the database connection
+    pub fn insert_into_database_transfer(
+        id: Uuid,
+        from: &str,
+        to: &str,
+        amount: &BigDecimal,
+        currency: &str,
+        date: DateTime<Utc>,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        let paid =
+            validate_amounts(&HashMap::from([(from.to_string(), amount.clone())]), connection)?;
+        let involved =
+            validate_amounts(&HashMap::from([(to.to_string(), amount.clone())]), connection)?;
+        if !amount.is_positive() || paid.keys().eq(involved.keys()) {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid transfer",
+                format!("The transfer of {} from {} to {} is invalid.", amount, from, to),
+                "A transfer must move a positive amount between two different persons.",
+            )));
+        }
+        let recipient =
+            Person::select_from_database_by_id(Person::resolve_id(to, connection)?, connection)?;
+        connection.execute(
+            "INSERT INTO payment (id, target, note, paid, involved, payment_type, creation_time, currency, transfer) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                id,
+                recipient.display_name(),
+                "",
+                serde_json::to_value(&paid)?,
+                serde_json::to_value(&involved)?,
+                serde_json::to_value(&PaymentType::OneOff{ start: date })?,
+                chrono::Utc::now(),
+                validate_currency_code(currency)?,
+                true
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Replaces every person reference of the payment with the reference returned by
+    /// the mapping function. Amounts of persons that are mapped to the same reference are
+    /// summed up. The references are not validated.
+    ///
+    /// # Parameters
+    ///
+    /// * `map` - the function mapping old references to new references
+    /// * `connection` - the database connection
+    pub fn update_in_database_person_references<F: FnMut(&str) -> Result<String, HomeworkError>>(
+        &self,
+        mut map: F,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        let paid = map_amounts(self.paid(), &mut map)?;
+        let involved = map_amounts(self.involved(), &mut map)?;
+        let split = self
+            .split()
+            .map(|split| split.map_persons(&mut map))
+            .transpose()?;
+        connection.execute(
+            "UPDATE payment SET paid = ?1, involved = ?2, split = ?3 WHERE id = ?4",
+            params![
+                serde_json::to_value(&paid)?,
+                serde_json::to_value(&involved)?,
+                split
+                    .map(|split| serde_json::to_value(&split))
+                    .transpose()?,
+                self.id()
             ],
         )?;
         Ok(())
@@ -101,6 +343,142 @@ impl Payment {
         Ok(())
     }
 
+    /// Updates the amounts paid by each person.
+    /// If a [`SplitMode`] is set for the payment, the involved amounts are recomputed
+    /// based on the new total.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the payment
+    /// * `paid` - the amounts paid by each person
+    /// * `connection` - the database connection
+    pub fn update_in_database_paid(
+        id: Uuid,
+        paid: &HashMap<String, BigDecimal>,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        let payment = Self::select_from_database_by_id(id, connection)?;
+        let paid = validate_amounts(paid, connection)?;
+
+        if let Some(split) = payment.split() {
+            let involved = split_service::split(split, &split_service::total(&paid))?;
+            connection.execute(
+                "UPDATE payment SET paid = ?1, involved = ?2 WHERE id = ?3",
+                params![
+                    serde_json::to_value(&paid)?,
+                    serde_json::to_value(&involved)?,
+                    id
+                ],
+            )?;
+        } else {
+            connection.execute(
+                "UPDATE payment SET paid = ?1 WHERE id = ?2",
+                params![serde_json::to_value(&paid)?, id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Updates the [`SplitMode`] of the payment and recomputes the involved amounts
+    /// based on the total amount paid.
+    /// If no split mode is specified the current involved amounts are kept.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the payment
+    /// * `split` - the new split mode
+    /// * `connection` - the database connection
+    pub fn update_in_database_split(
+        id: Uuid,
+        split: Option<&SplitMode>,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        let payment = Self::select_from_database_by_id(id, connection)?;
+
+        if let Some(split) = split {
+            // Validate the split before resolving the persons to keep error messages readable.
+            split_service::split(split, &split_service::total(payment.paid()))?;
+            let split = split.resolve_persons(connection)?;
+            let involved = split_service::split(&split, &split_service::total(payment.paid()))?;
+            connection.execute(
+                "UPDATE payment SET split = ?1, involved = ?2 WHERE id = ?3",
+                params![
+                    serde_json::to_value(&split)?,
+                    serde_json::to_value(&involved)?,
+                    id
+                ],
+            )?;
+        } else {
+            connection.execute("UPDATE payment SET split = NULL WHERE id = ?1", params![id])?;
+        }
+        Ok(())
+    }
+
+    /// Updates the amounts each person is involved with.
+    /// This removes the [`SplitMode`] of the payment as the amounts are set manually.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the payment
+    /// * `involved` - the amounts each person is involved with
+    /// * `connection` - the database connection
+    pub fn update_in_database_involved(
+        id: Uuid,
+        involved: &HashMap<String, BigDecimal>,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        let involved = validate_amounts(involved, connection)?;
+
+        connection.execute(
+            "UPDATE payment SET involved = ?1, split = NULL WHERE id = ?2",
+            params![serde_json::to_value(&involved)?, id],
+        )?;
+        Ok(())
+    }
+
+    /// Updates the currency of all amounts of the payment.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the payment
+    /// * `currency` - the currency code
+    /// * `connection` - the database connection
+    pub fn update_in_database_currency(
+        id: Uuid,
+        currency: &str,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        let currency = validate_currency_code(currency)?;
+
+        connection
+            .execute("UPDATE payment SET currency = ?1 WHERE id = ?2", params![currency, id])?;
+        Ok(())
+    }
+
+    /// Updates the [`PaymentType`] of the payment.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the payment
+    /// * `payment_type` - the new payment type
+    /// * `connection` - the database connection
+    pub fn update_in_database_payment_type(
+        id: Uuid,
+        payment_type: &PaymentType,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        payment_type.validate()?;
+
+        connection.execute(
+            "UPDATE payment SET payment_type = ?1 WHERE id = ?2",
+            params![serde_json::to_value(payment_type)?, id],
+        )?;
+        Ok(())
+    }
+
     pub fn update_in_database_insert_tag(
         id: Uuid,
         tag: &str,
@@ -185,60 +563,48 @@ impl Payment {
         Ok(())
     }
 
-    pub fn tags_by_id(
+    pub fn exists_in_database_by_id(
         payment_id: Uuid,
         connection: &Connection,
-    ) -> Result<Vec<String>, rusqlite::Error> {
-        let mut tag_stmt =
-            connection.prepare("SELECT tag FROM tag_payment_mapping WHERE payment_id = ?1")?;
-        let tag_rows = tag_stmt.query_map([payment_id], |row| Ok(row.get(0)?))?;
-        let mut tags = Vec::new();
-        for tag in tag_rows {
-            tags.push(tag?);
-        }
-        Ok(tags)
+    ) -> Result<bool, rusqlite::Error> {
+        let mut stmt = connection.prepare("SELECT 1 FROM payment WHERE id = ?1")?;
+        stmt.exists([payment_id])
     }
 
-    pub fn attachments_by_id(
+    /// Automatically throws a ```Not Found``` if the entry does not exist.
+    /// Returns an ```Ok``` otherwise.
+    ///
+    /// Parameters
+    ///
+    /// * ```payment_id``` - the ID of the payment
+    /// * ```connection``` - the database connection
+    pub fn exists_in_database_by_id_throw_not_found(
         payment_id: Uuid,
         connection: &Connection,
-    ) -> Result<Vec<Attachment>, rusqlite::Error> {
-        let mut attachment_stmt = connection.prepare(
-            "
-                SELECT attachment.id, attachment.name, attachment.creation_time 
-                FROM attachment 
-                INNER JOIN attachment_payment_mapping 
-                    ON attachment.id = attachment_payment_mapping.attachment_id      
-                WHERE attachment_payment_mapping.payment_id = ?1",
-        )?;
-        let attachment_rows =
-            attachment_stmt.query_map([payment_id], |row| Ok(Attachment::try_from(row)?))?;
-
-        let mut attachments = Vec::new();
-        for attachment in attachment_rows {
-            attachments.push(attachment?);
+    ) -> Result<(), HomeworkError> {
+        if !Self::exists_in_database_by_id(payment_id, connection)? {
+            Err(HomeworkError::NotFoundError(InternalError::new(
+                "Payment not found",
+                format!("The payment {} does not exist.", payment_id),
+                "The payment does not exist.",
+            )))
+        } else {
+            Ok(())
         }
-        Ok(attachments)
-    }
-
-    pub fn exists_in_database_by_id(
-        payment_id: Uuid,
-        connection: &Connection,
-    ) -> Result<bool, rusqlite::Error> {
-        let mut stmt = connection.prepare("SELECT 1 FROM payment WHERE id = ?1")?;
-        stmt.exists([payment_id])
     }
 }
 
-impl TryFrom<(&Row<'_>, &Connection)> for Payment {
+impl TryFrom<&Row<'_>> for Payment {
     type Error = HomeworkError;
 
-    fn try_from(data: (&Row, &Connection)) -> Result<Self, Self::Error> {
-        let (row, connection) = data;
+    /// Converts the row into a payment without its relations,
+    /// which are loaded by [`Payment::load_relations`].
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
         let id = row.get(0)?;
         let paid = serde_json::from_value(row.get(3)?)?;
         let involved = serde_json::from_value(row.get(4)?)?;
         let payment_type = serde_json::from_value(row.get(5)?)?;
+        let split: Option<serde_json::Value> = row.get(7)?;
         Ok(Payment {
             id,
             target: row.get(1)?,
@@ -246,13 +612,94 @@ impl TryFrom<(&Row<'_>, &Connection)> for Payment {
             paid,
             involved,
             payment_type,
-            tags: Payment::tags_by_id(id, connection)?,
-            attachments: Payment::attachments_by_id(id, connection)?,
+            split: split.map(serde_json::from_value).transpose()?,
+            currency: row.get(8)?,
+            transfer: row.get(9)?,
+            tags: Vec::new(),
+            attachments: Vec::new(),
             creation_time: row.get(6)?,
         })
     }
 }
 
+/// Validates the amounts associated with each person and returns them with the persons
+/// resolved to their IDs. Amounts must not be negative and persons must neither be empty
+/// nor reference the same person multiple times.
+///
+/// # Parameters
+///
+/// * `amounts` - the amounts to validate
+/// * `connection` - the database connection
+fn validate_amounts(
+    amounts: &HashMap<String, BigDecimal>,
+    connection: &Connection,
+) -> Result<HashMap<String, BigDecimal>, HomeworkError> {
+    let mut validated_amounts = HashMap::new();
+    for (person, amount) in amounts {
+        let person = person.trim();
+        if person.is_empty() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid person",
+                format!("An empty person name was supplied with the amount {}.", amount),
+                "The name of a person must not be empty.",
+            )));
+        }
+        if amount.is_negative() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Negative amount",
+                format!("The amount {} of {} is negative.", amount, person),
+                "Amounts must not be negative.",
+            )));
+        }
+        if validated_amounts
+            .insert(Person::resolve_id(person, connection)?.to_string(), amount.clone())
+            .is_some()
+        {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Duplicate person",
+                format!("The person {} was supplied multiple times.", person),
+                "Every person must only be supplied once.",
+            )));
+        }
+    }
+    Ok(validated_amounts)
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The query parameters filtering and sorting a list of payments.
+pub struct PaymentFilter {
+    /// A tag the payments must be tagged with.
+    tag: Option<String>,
+    /// The inclusive start of the time window the first occurrence of the payments lies in.
+    from: Option<DateTime<Utc>>,
+    /// The inclusive end of the time window the first occurrence of the payments lies in.
+    to: Option<DateTime<Utc>>,
+    /// The field the payments are sorted by. Defaults to the payment date.
+    #[serde(default)]
+    sort: PaymentSortField,
+    /// The direction the payments are sorted in. Defaults to ascending.
+    #[serde(default)]
+    direction: SortDirection,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The fields a list of payments can be sorted by.
+pub enum PaymentSortField {
+    /// The date of the first occurrence of the payment.
+    #[default]
+    Date,
+    CreationTime,
+    Target,
+}
+
+/// The SQL expression of the date of the first occurrence of a payment as julian day.
+/// The date is the `start` of the single variant of the stored [`PaymentType`], whose
+/// ISO 8601 representation is converted as the fractional seconds do not sort as text.
+const PAYMENT_DATE_SQL: &str =
+    "julianday((SELECT json_extract(value, '$.start') FROM json_each(payment.payment_type)))";
+
 const COLUMN_STRING_TARGET: &str = "target";
 const COLUMN_STRING_NOTE: &str = "note";
 
@@ -316,3 +763,172 @@ pub enum PaymentType {
         end: DateTime<Utc>,
     },
 }
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The mode used to split the total amount paid between the involved persons.
+pub enum SplitMode {
+    /// The total is split equally between all persons.
+    Equal { persons: Vec<String> },
+    /// The total is split proportionally to the share weight of each person.
+    Shares { shares: HashMap<String, BigDecimal> },
+    /// The total is split by the percentage of each person.
+    Percentage {
+        percentages: HashMap<String, BigDecimal>,
+    },
+    /// Each person is involved with an exact amount.
+    Exact {
+        amounts: HashMap<String, BigDecimal>,
+    },
+}
+
+/// Replaces the person keys of the amounts with the keys returned by the mapping function.
+/// Amounts of persons that are mapped to the same key are summed up.
+///
+/// # Parameters
+///
+/// * `amounts` - the amounts to map
+/// * `map` - the function mapping old keys to new keys
+fn map_amounts<F: FnMut(&str) -> Result<String, HomeworkError>>(
+    amounts: &HashMap<String, BigDecimal>,
+    map: &mut F,
+) -> Result<HashMap<String, BigDecimal>, HomeworkError> {
+    let mut mapped_amounts: HashMap<String, BigDecimal> = HashMap::new();
+    for (person, amount) in amounts {
+        *mapped_amounts
+            .entry(map(person)?)
+            .or_insert_with(|| BigDecimal::from(0)) += amount;
+    }
+    Ok(mapped_amounts)
+}
+
+impl PaymentType {
+    /// Returns the date of the first occurrence.
+    pub fn start(&self) -> DateTime<Utc> {
+        match self {
+            PaymentType::OneOff { start }
+            | PaymentType::Daily { start, .. }
+            | PaymentType::Weekly { start, .. }
+            | PaymentType::Monthly { start, .. }
+            | PaymentType::Annualy { start, .. } => *start,
+        }
+    }
+
+    /// Validates the `PaymentType` and returns an error if the distance between
+    /// occurrences is zero or if the end lies before the start.
+    pub fn validate(&self) -> Result<(), HomeworkError> {
+        match self {
+            PaymentType::OneOff { .. } => Ok(()),
+            PaymentType::Daily {
+                distance,
+                start,
+                end,
+            }
+            | PaymentType::Weekly {
+                distance,
+                start,
+                end,
+            }
+            | PaymentType::Monthly {
+                distance,
+                start,
+                end,
+            }
+            | PaymentType::Annualy {
+                distance,
+                start,
+                end,
+            } => {
+                if *distance == 0 {
+                    Err(HomeworkError::BadRequestError(InternalError::new(
+                        "Invalid distance",
+                        format!("The payment type {:?} has a distance of 0.", self),
+                        "The distance between recurring payments must be at least 1.",
+                    )))
+                } else if end < start {
+                    Err(HomeworkError::BadRequestError(InternalError::new(
+                        "Invalid time range",
+                        format!("The end {} lies before the start {} of {:?}.", end, start, self),
+                        "The end of a recurring payment must not lie before its start.",
+                    )))
+                } else {
+                    Ok(())
+                }
+            },
+        }
+    }
+}
+
+impl SplitMode {
+    /// Returns all persons this `SplitMode` references.
+    pub fn persons(&self) -> Vec<&str> {
+        match self {
+            SplitMode::Equal { persons } => persons.iter().map(String::as_str).collect(),
+            SplitMode::Shares { shares: amounts }
+            | SplitMode::Percentage {
+                percentages: amounts,
+            }
+            | SplitMode::Exact { amounts } => amounts.keys().map(String::as_str).collect(),
+        }
+    }
+
+    /// Returns a copy of this `SplitMode` with every person replaced by the person returned
+    /// by the mapping function. Values of persons that are mapped to the same person are
+    /// summed up.
+    ///
+    /// # Parameters
+    ///
+    /// * `map` - the function mapping old persons to new persons
+    pub fn map_persons<F: FnMut(&str) -> Result<String, HomeworkError>>(
+        &self,
+        map: &mut F,
+    ) -> Result<SplitMode, HomeworkError> {
+        Ok(match self {
+            SplitMode::Equal { persons } => {
+                let mut mapped_persons: Vec<String> = Vec::new();
+                for person in persons {
+                    let person = map(person)?;
+                    if !mapped_persons.contains(&person) {
+                        mapped_persons.push(person);
+                    }
+                }
+                SplitMode::Equal {
+                    persons: mapped_persons,
+                }
+            },
+            SplitMode::Shares { shares } => SplitMode::Shares {
+                shares: map_amounts(shares, map)?,
+            },
+            SplitMode::Percentage { percentages } => SplitMode::Percentage {
+                percentages: map_amounts(percentages, map)?,
+            },
+            SplitMode::Exact { amounts } => SplitMode::Exact {
+                amounts: map_amounts(amounts, map)?,
+            },
+        })
+    }
+
+    /// Returns a copy of this `SplitMode` with all persons resolved to their IDs.
+    /// Fails if a person does not exist or is referenced multiple times.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    fn resolve_persons(&self, connection: &Connection) -> Result<SplitMode, HomeworkError> {
+        let resolved = self
+            .map_persons(&mut |person| Ok(Person::resolve_id(person, connection)?.to_string()))?;
+        if resolved.persons().len() != self.persons().len() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Duplicate person",
+                format!("The split {:?} references a person multiple times.", self),
+                "Every person must only be supplied once.",
+            )));
+        }
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+pub mod test_builder;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+use chrono::TimeZone;
+
+use super::*;
+
+/// Creates an in-memory database containing the persons with the specified names.
+fn connection_with_persons(names: &[&str]) -> Connection {
+    let connection = crate::application::config::Configuration::in_memory_database_connection();
+    for (index, name) in names.iter().enumerate() {
+        Person::new(Uuid::from_u128(index as u128 + 1), name)
+            .insert_into_database(&connection)
+            .unwrap();
+    }
+    connection
+}
+
+#[test]
+/// Tests if the `validate_amounts` function resolves persons and rejects invalid amounts.
+fn test_validate_amounts() {
+    let connection = connection_with_persons(&["Anna", "Ben"]);
+    let anna = Uuid::from_u128(1).to_string();
+    let ben = Uuid::from_u128(2).to_string();
+    let mut amounts = HashMap::new();
+    amounts.insert(" anna ".to_string(), BigDecimal::from_str("12.40").unwrap());
+    amounts.insert(ben.clone(), BigDecimal::from_str("0").unwrap());
+    let validated = validate_amounts(&amounts, &connection).unwrap();
+    assert_eq!(validated.get(&anna), Some(&BigDecimal::from_str("12.40").unwrap()));
+    assert!(validated.contains_key(&ben));
+
+    amounts.insert("Ben".to_string(), BigDecimal::from_str("1").unwrap());
+    assert!(validate_amounts(&amounts, &connection).is_err());
+
+    let mut negative = HashMap::new();
+    negative.insert("Anna".to_string(), BigDecimal::from_str("-0.01").unwrap());
+    assert!(validate_amounts(&negative, &connection).is_err());
+
+    let mut unknown = HashMap::new();
+    unknown.insert("Carl".to_string(), BigDecimal::from_str("1").unwrap());
+    assert!(validate_amounts(&unknown, &connection).is_err());
+
+    let mut empty_name = HashMap::new();
+    empty_name.insert("  ".to_string(), BigDecimal::from_str("1").unwrap());
+    assert!(validate_amounts(&empty_name, &connection).is_err());
+}
+
+#[test]
+/// Tests if the `validate` function rejects invalid payment types.
+fn test_payment_type_validate() {
+    let start = Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2022, 12, 31, 0, 0, 0).unwrap();
+    assert!(PaymentType::OneOff { start }.validate().is_ok());
+    assert!(PaymentType::Monthly {
+        distance: 1,
+        start,
+        end
+    }
+    .validate()
+    .is_ok());
+    assert!(PaymentType::Monthly {
+        distance: 1,
+        start: end,
+        end: start
+    }
+    .validate()
+    .is_err());
+    assert!(PaymentType::Weekly {
+        distance: 0,
+        start,
+        end
+    }
+    .validate()
+    .is_err());
+}
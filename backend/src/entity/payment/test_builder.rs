@@ -0,0 +1,91 @@
+//! The `test_builder` module creates [`Payment`]s and their values for tests
+//! without repeating the complete JSON representation of a payment.
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use super::Payment;
+
+/// Builds a one-off `Payment` in EUR without tags, which can be adjusted as needed.
+pub struct PaymentBuilder {
+    payment: Value,
+}
+
+impl PaymentBuilder {
+    /// Creates a builder of a `Payment` with the specified paid and involved amounts.
+    ///
+    /// # Parameters
+    ///
+    /// * `paid` - the amounts paid by each person, e.g. `{"Anna": "10.00"}`
+    /// * `involved` - the amounts each person is involved with
+    pub fn new(paid: Value, involved: Value) -> Self {
+        PaymentBuilder {
+            payment: json!({
+                "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "target": "Target",
+                "note": "",
+                "paid": paid,
+                "involved": involved,
+                "paymentType": {"OneOff": {"start": "2022-01-01T00:00:00Z"}},
+                "currency": "EUR",
+                "tags": [],
+                "attachments": [],
+                "creationTime": "2022-01-01T00:00:00Z"
+            }),
+        }
+    }
+
+    /// Sets the target of the `Payment`.
+    pub fn target(self, target: &str) -> Self {
+        self.set("target", json!(target))
+    }
+
+    /// Sets the payment type of the `Payment`, e.g. `{"Monthly": {...}}`.
+    pub fn payment_type(self, payment_type: Value) -> Self {
+        self.set("paymentType", payment_type)
+    }
+
+    /// Makes the `Payment` a one-off payment at the specified date.
+    pub fn one_off(self, date: &str) -> Self {
+        self.payment_type(json!({"OneOff": {"start": date}}))
+    }
+
+    /// Sets the currency of all amounts of the `Payment`.
+    pub fn currency(self, currency: &str) -> Self {
+        self.set("currency", json!(currency))
+    }
+
+    /// Sets the tags of the `Payment`.
+    pub fn tags(self, tags: &[&str]) -> Self {
+        self.set("tags", json!(tags))
+    }
+
+    /// Makes the `Payment` a transfer settling balances.
+    pub fn transfer(self) -> Self {
+        self.set("transfer", json!(true))
+    }
+
+    /// Creates the `Payment`.
+    pub fn build(self) -> Payment {
+        serde_json::from_value(self.payment).unwrap()
+    }
+
+    /// Sets a field of the JSON representation of the `Payment`.
+    fn set(mut self, field: &str, value: Value) -> Self {
+        self.payment[field] = value;
+        self
+    }
+}
+
+/// Parses a decimal.
+pub fn decimal(value: &str) -> BigDecimal {
+    BigDecimal::from_str(value).unwrap()
+}
+
+/// Parses a date.
+pub fn date(value: &str) -> DateTime<Utc> {
+    DateTime::<Utc>::from_str(value).unwrap()
+}
@@ -0,0 +1,299 @@
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::application::error::{HomeworkError, InternalError};
+
+use super::{budget::Budget, payment::Payment};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A person that pays for or is involved with [`Payment`]s.
+/// Payments reference persons by their ID, while the display name and the aliases
+/// can be used to refer to a person when entering amounts.
+pub struct Person {
+    #[serde(default)]
+    id: Uuid,
+    display_name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default = "default_active")]
+    active: bool,
+}
+
+/// Persons are active unless specified otherwise.
+fn default_active() -> bool {
+    true
+}
+
+impl Person {
+    /// Creates a new active `Person` without aliases.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the person
+    /// * `display_name` - the name the person is displayed with
+    pub fn new<T: ToString>(id: Uuid, display_name: T) -> Self {
+        Person {
+            id,
+            display_name: display_name.to_string(),
+            aliases: Vec::new(),
+            active: true,
+        }
+    }
+
+    /// Returns the ID of this `Person`.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the name this `Person` is displayed with.
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Returns the alternative names of this `Person`.
+    pub fn aliases(&self) -> &Vec<String> {
+        &self.aliases
+    }
+
+    /// Returns `true` if this `Person` is still active.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns a copy of this `Person` with the additional aliases.
+    ///
+    /// # Parameters
+    ///
+    /// * `aliases` - the aliases to add
+    pub fn with_aliases<'a, T: IntoIterator<Item = &'a str>>(&self, aliases: T) -> Person {
+        let mut person = self.clone();
+        person
+            .aliases
+            .extend(aliases.into_iter().map(str::to_string));
+        person
+    }
+
+    pub fn set_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+
+    /// Returns `true` if the name matches the display name or one of the aliases
+    /// of this `Person` ignoring case and surrounding whitespace.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - the name to match
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        self.names()
+            .any(|known| known.trim().to_lowercase() == name)
+    }
+
+    /// Returns the display name and all aliases of this `Person`.
+    fn names(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.display_name).chain(self.aliases.iter())
+    }
+
+    pub fn select_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<Person, HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        connection.query_row_and_then(
+            "SELECT id, display_name, aliases, active FROM person WHERE id = ?1",
+            [id],
+            |row| Person::try_from(row),
+        )
+    }
+
+    pub fn select_all_from_database(connection: &Connection) -> Result<Vec<Person>, HomeworkError> {
+        let mut stmt = connection.prepare(
+            "SELECT id, display_name, aliases, active FROM person ORDER BY display_name",
+        )?;
+        let person_query = stmt.query_and_then([], |row| Person::try_from(row))?;
+        let mut persons = Vec::new();
+        for person in person_query {
+            persons.push(person?);
+        }
+        Ok(persons)
+    }
+
+    /// Validates the person and inserts it into the database.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn insert_into_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        if Self::exists_in_database_by_id(self.id(), connection)? {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Person already exists",
+                format!("The person {} already exists.", self.id()),
+                "The person already exists.",
+            )));
+        }
+        let person = self.validate(connection)?;
+
+        connection.execute(
+            "INSERT INTO person (id, display_name, aliases, active, creation_time) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![person.id(), person.display_name(), serde_json::to_value(person.aliases())?, person.is_active(), chrono::Utc::now()],
+        )?;
+        Ok(())
+    }
+
+    /// Validates the person and replaces the stored person with the same ID.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn update_in_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(self.id(), connection)?;
+        let person = self.validate(connection)?;
+
+        connection.execute(
+            "UPDATE person SET display_name = ?1, aliases = ?2, active = ?3 WHERE id = ?4",
+            params![
+                person.display_name(),
+                serde_json::to_value(person.aliases())?,
+                person.is_active(),
+                person.id()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes the person from the database.
+    /// Persons that are still referenced by payments or budgets cannot be deleted,
+    /// but can be deactivated or merged into another person instead.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the person
+    /// * `connection` - the database connection
+    pub fn delete_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        let key = id.to_string();
+        let referenced_by_payment = Payment::select_all_from_database(connection)?
+            .iter()
+            .any(|payment| payment.persons().contains(&key));
+        let referenced_by_budget = Budget::select_all_from_database(connection)?
+            .iter()
+            .any(|budget| budget.person() == Some(key.as_str()));
+        if referenced_by_payment || referenced_by_budget {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Person in use",
+                format!("The person {} is still referenced.", id),
+                "The person is still referenced by payments or budgets. Deactivate or merge the person instead.",
+            )));
+        }
+
+        connection.execute("DELETE FROM person WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn exists_in_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<bool, rusqlite::Error> {
+        let mut stmt = connection.prepare("SELECT 1 FROM person WHERE id = ?1")?;
+        stmt.exists([id])
+    }
+
+    /// Automatically throws a ```Not Found``` if the entry does not exist.
+    /// Returns an ```Ok``` otherwise.
+    ///
+    /// Parameters
+    ///
+    /// * ```id``` - the ID of the person
+    /// * ```connection``` - the database connection
+    pub fn exists_in_database_by_id_throw_not_found(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        if !Self::exists_in_database_by_id(id, connection)? {
+            Err(HomeworkError::NotFoundError(InternalError::new(
+                "Person not found",
+                format!("The person {} does not exist.", id),
+                "The person does not exist.",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves a reference to a person, which is either the ID, the display name or
+    /// an alias of the person, and returns the ID of the person.
+    ///
+    /// # Parameters
+    ///
+    /// * `reference` - the ID or name of the person
+    /// * `connection` - the database connection
+    pub fn resolve_id(reference: &str, connection: &Connection) -> Result<Uuid, HomeworkError> {
+        if let Ok(id) = Uuid::parse_str(reference.trim()) {
+            if Self::exists_in_database_by_id(id, connection)? {
+                return Ok(id);
+            }
+        }
+        Self::select_all_from_database(connection)?
+            .iter()
+            .find(|person| person.is_named(reference))
+            .map(Person::id)
+            .ok_or_else(|| {
+                HomeworkError::BadRequestError(InternalError::new(
+                    "Unknown person",
+                    format!("\"{}\" does not reference any person.", reference),
+                    format!("The person \"{}\" does not exist.", reference.trim()),
+                ))
+            })
+    }
+
+    /// Returns a copy of the person with trimmed and deduplicated names after checking
+    /// that the display name is not empty and that no name is used by another person.
+    fn validate(&self, connection: &Connection) -> Result<Person, HomeworkError> {
+        let display_name = self.display_name().trim();
+        if display_name.is_empty() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid person",
+                format!("The person {:?} has an empty display name.", self),
+                "The display name of a person must not be empty.",
+            )));
+        }
+        let mut person = Person::new(self.id(), display_name);
+        person.active = self.is_active();
+        for alias in self.aliases().iter().map(|alias| alias.trim()) {
+            if !alias.is_empty() && !person.is_named(alias) {
+                person.aliases.push(alias.to_string());
+            }
+        }
+        for other in Self::select_all_from_database(connection)?
+            .iter()
+            .filter(|other| other.id() != person.id())
+        {
+            if let Some(name) = person.names().find(|name| other.is_named(name)) {
+                return Err(HomeworkError::BadRequestError(InternalError::new(
+                    "Duplicate person name",
+                    format!("The name \"{}\" is already used by person {}.", name, other.id()),
+                    format!("The name \"{}\" is already used by another person.", name),
+                )));
+            }
+        }
+        Ok(person)
+    }
+}
+
+impl TryFrom<&Row<'_>> for Person {
+    type Error = HomeworkError;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        Ok(Person {
+            id: row.get(0)?,
+            display_name: row.get(1)?,
+            aliases: serde_json::from_value(row.get(2)?)?,
+            active: row.get(3)?,
+        })
+    }
+}
@@ -3,7 +3,17 @@ use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::application::error::{HomeworkError, InternalError};
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    service::{
+        batch_loader_service::load_grouped_by_ids,
+        pagination_service::{select_page, Conditions, Page, Pagination, SortDirection},
+        quantity_service::Fraction,
+        search_service::index_recipe,
+        sub_recipe_service::expand_sub_recipes,
+        unit_service::UnitSystem,
+    },
+};
 
 use super::{attachment::Attachment, ingredient::Ingredient};
 
@@ -15,6 +25,7 @@ pub struct Recipe {
     instructions: String,
     reference: String,
     rating: u8,
+    servings: Option<u32>,
     thumbnail: Option<Attachment>,
     tags: Vec<String>,
     attachments: Vec<Attachment>,
@@ -23,39 +34,251 @@ pub struct Recipe {
 }
 
 impl Recipe {
+    /// Returns the title of this `Recipe`.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the instructions of this `Recipe` as HTML.
+    pub fn instructions(&self) -> &str {
+        &self.instructions
+    }
+
+    /// Returns the reference to the source of this `Recipe`.
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    /// Returns the rating of this `Recipe`.
+    pub fn rating(&self) -> u8 {
+        self.rating
+    }
+
+    /// Returns the [`Attachment`] used as thumbnail of this `Recipe` if any.
+    pub fn thumbnail(&self) -> Option<&Attachment> {
+        self.thumbnail.as_ref()
+    }
+
+    /// Returns the tags of this `Recipe`.
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+
+    /// Returns all [`Ingredient`]s of this `Recipe`.
+    pub fn ingredients(&self) -> &Vec<Ingredient> {
+        &self.ingredients
+    }
+
     /// Returns all [`Attachment`]s referring to this `Recipe`.
     pub fn attachments(&self) -> &Vec<Attachment> {
         &self.attachments
     }
 
+    /// Returns the number of servings this `Recipe` yields if specified.
+    pub fn servings(&self) -> Option<u32> {
+        self.servings
+    }
+
+    /// Returns the factor the quantities of this `Recipe` need to be multiplied with
+    /// to yield the specified number of servings.
+    ///
+    /// # Parameters
+    ///
+    /// * `servings` - the number of servings to scale the recipe to
+    pub fn scaling_factor(&self, servings: u32) -> Result<Fraction, HomeworkError> {
+        let base_servings = self.servings().ok_or_else(|| {
+            HomeworkError::BadRequestError(InternalError::new(
+                "Invalid scaling",
+                format!("The recipe {} does not specify a number of servings.", self.id),
+                "The recipe cannot be scaled as it does not specify a number of servings.",
+            ))
+        })?;
+        if servings == 0 {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid scaling",
+                format!("The recipe {} cannot be scaled to zero servings.", self.id),
+                "The number of servings must be positive.",
+            )));
+        }
+        Ok(Fraction::new(servings.into(), base_servings.into())
+            .expect("The number of servings was checked to be positive before."))
+    }
+
+    /// Scales the quantities of all [`Ingredient`]s of this `Recipe` to the
+    /// specified number of servings.
+    ///
+    /// # Parameters
+    ///
+    /// * `servings` - the number of servings to scale the recipe to
+    pub fn scale(&mut self, servings: u32) -> Result<(), HomeworkError> {
+        let factor = self.scaling_factor(servings)?;
+        for ingredient in self.ingredients.iter_mut() {
+            ingredient.scale(&factor)?;
+        }
+        self.servings = Some(servings);
+        Ok(())
+    }
+
+    /// Converts the quantities of all [`Ingredient`]s of this `Recipe` into the
+    /// specified system of units.
+    ///
+    /// # Parameters
+    ///
+    /// * `system` - the system of units to convert to
+    pub fn convert_units(&mut self, system: UnitSystem) -> Result<(), HomeworkError> {
+        for ingredient in self.ingredients.iter_mut() {
+            ingredient.convert_units(system)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the [`Ingredient`]s of this `Recipe` by the ingredients containing
+    /// the expanded ingredients of their sub-recipes.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn expand_sub_recipes(&mut self, connection: &Connection) -> Result<(), HomeworkError> {
+        self.ingredients = expand_sub_recipes(self.id, &Fraction::from_integer(1), connection)?;
+        Ok(())
+    }
+
     pub fn select_from_database_by_id(
         recipe_id: Uuid,
         connection: &Connection,
     ) -> Result<Recipe, HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(recipe_id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(recipe_id, connection)?;
         let mut stmt_recipe = connection
-            .prepare("SELECT id, title, instructions, reference, rating, creation_time FROM recipe WHERE id = ?1")?;
-        let recipe = stmt_recipe
-            .query_map([recipe_id], |row| Ok(Recipe::try_from((row, connection))?))?
+            .prepare("SELECT id, title, instructions, reference, rating, creation_time, servings FROM recipe WHERE id = ?1")?;
+        let mut recipe = stmt_recipe
+            .query_map([recipe_id], |row| Recipe::try_from(row))?
             .last()
             .expect("The validity of the query was checked before.")?;
+        Recipe::load_relations(std::slice::from_mut(&mut recipe), connection)?;
 
         Ok(recipe)
     }
 
-    pub fn select_all_from_database(connection: &Connection) -> Result<Vec<Recipe>, HomeworkError> {
-        let mut stmt_recipe = connection.prepare(
-            "SELECT id, title, instructions, reference, rating, creation_time FROM recipe",
+    /// Returns all recipes using the recipe as sub-recipe in one of their ingredients.
+    ///
+    /// # Parameters
+    ///
+    /// * `recipe_id` - the ID of the sub-recipe
+    /// * `connection` - the database connection
+    pub fn select_from_database_by_sub_recipe(
+        recipe_id: Uuid,
+        connection: &Connection,
+    ) -> Result<Vec<Recipe>, HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(recipe_id, connection)?;
+        let mut stmt = connection.prepare(
+            "
+                SELECT id, title, instructions, reference, rating, creation_time, servings
+                FROM recipe
+                WHERE id IN (SELECT recipe_id FROM ingredient WHERE recipe_reference = ?1)
+                ORDER BY title COLLATE NOCASE, id",
         )?;
-        let recipe_query =
-            stmt_recipe.query_map([], |row| Ok(Recipe::try_from((row, connection))?))?;
-        let mut recipes = Vec::new();
-        for recipe in recipe_query {
-            recipes.push(recipe?);
-        }
+        let mut recipes = stmt
+            .query_map([recipe_id], |row| Recipe::try_from(row))?
+            .collect::<Result<Vec<Recipe>, rusqlite::Error>>()?;
+        Recipe::load_relations(&mut recipes, connection)?;
         Ok(recipes)
     }
 
+    /// Returns a page of all recipes matching the filter.
+    ///
+    /// # Parameters
+    ///
+    /// * `filter` - the filters and sort order
+    /// * `pagination` - the page to select
+    /// * `connection` - the database connection
+    pub fn select_page_from_database(
+        filter: &RecipeFilter,
+        pagination: &Pagination,
+        connection: &Connection,
+    ) -> Result<Page<Recipe>, HomeworkError> {
+        let mut conditions = Conditions::default();
+        if let Some(tag) = &filter.tag {
+            conditions.push(
+                "EXISTS (SELECT 1 FROM tag_recipe_mapping WHERE recipe_id = recipe.id AND tag = ?)",
+                tag.trim().to_string(),
+            );
+        }
+        if let Some(minimum_rating) = filter.minimum_rating {
+            conditions.push("rating >= ?", minimum_rating);
+        }
+        let order = match filter.sort {
+            RecipeSortField::Title => format!("title COLLATE NOCASE {}, id", filter.direction),
+            RecipeSortField::Rating => {
+                format!("rating {}, title COLLATE NOCASE, id", filter.direction)
+            },
+            RecipeSortField::CreationTime => format!("creation_time {}, id", filter.direction),
+        };
+        let mut page = select_page(
+            "SELECT id, title, instructions, reference, rating, creation_time, servings FROM recipe",
+            "recipe",
+            &conditions,
+            &order,
+            pagination,
+            connection,
+            |row| Recipe::try_from(row),
+        )?;
+        Recipe::load_relations(page.items_mut(), connection)?;
+        Ok(page)
+    }
+
+    /// Loads the tags, thumbnails, attachments and ingredients of all recipes
+    /// with a single query per relation instead of one query per recipe.
+    ///
+    /// # Parameters
+    ///
+    /// * `recipes` - the recipes without relations
+    /// * `connection` - the database connection
+    fn load_relations(
+        recipes: &mut [Recipe],
+        connection: &Connection,
+    ) -> Result<(), rusqlite::Error> {
+        let ids: Vec<Uuid> = recipes.iter().map(|recipe| recipe.id).collect();
+        let mut tags = load_grouped_by_ids(
+            "SELECT recipe_id, tag FROM tag_recipe_mapping WHERE recipe_id IN ({ids}) ORDER BY id",
+            &ids,
+            connection,
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let mut thumbnails = load_grouped_by_ids(
+            "
+                SELECT attachment.id, attachment.name, attachment.creation_time, recipe.id
+                FROM attachment
+                INNER JOIN recipe
+                    ON attachment.id = recipe.thumbnail
+                WHERE recipe.id IN ({ids})",
+            &ids,
+            connection,
+            |row| Ok((row.get(3)?, Attachment::try_from(row)?)),
+        )?;
+        let mut attachments = load_grouped_by_ids(
+            "
+                SELECT attachment.id, attachment.name, attachment.creation_time, attachment_recipe_mapping.recipe_id
+                FROM attachment
+                INNER JOIN attachment_recipe_mapping
+                    ON attachment.id = attachment_recipe_mapping.attachment_id
+                WHERE attachment_recipe_mapping.recipe_id IN ({ids})
+                ORDER BY attachment_recipe_mapping.id",
+            &ids,
+            connection,
+            |row| Ok((row.get(3)?, Attachment::try_from(row)?)),
+        )?;
+        let mut ingredients = Ingredient::select_from_database_by_recipe_ids(&ids, connection)?;
+        for recipe in recipes.iter_mut() {
+            recipe.tags = tags.remove(&recipe.id).unwrap_or_default();
+            recipe.thumbnail = thumbnails
+                .remove(&recipe.id)
+                .and_then(|thumbnail| thumbnail.into_iter().next());
+            recipe.attachments = attachments.remove(&recipe.id).unwrap_or_default();
+            recipe.ingredients = ingredients.remove(&recipe.id).unwrap_or_default();
+        }
+        Ok(())
+    }
+
     pub fn insert_into_database_new_entry(
         id: Uuid,
         title: &str,
@@ -72,6 +295,7 @@ impl Recipe {
                 chrono::Utc::now()
             ],
         )?;
+        index_recipe(id, connection)?;
         Ok(())
     }
 
@@ -81,13 +305,14 @@ impl Recipe {
         value: &str,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
         let column = StringColumns::try_from(column)?;
         connection.execute(
             &format!("UPDATE recipe SET {} = ?1 WHERE id = ?2", column),
             params![value, id],
         )?;
+        index_recipe(id, connection)?;
         Ok(())
     }
 
@@ -96,10 +321,10 @@ impl Recipe {
         attachment_id: Option<Uuid>,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
         if let Some(thumbnail_id) = attachment_id {
-            Attachment::exists_in_database_by_id_throw_not_found(thumbnail_id, &connection)?;
+            Attachment::exists_in_database_by_id_throw_not_found(thumbnail_id, connection)?;
             connection.execute(
                 "UPDATE recipe SET thumbnail = ?1 WHERE id = ?2",
                 params![thumbnail_id, id],
@@ -116,23 +341,50 @@ impl Recipe {
         value: u8,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
         connection.execute("UPDATE recipe SET rating = ?1 WHERE id = ?2", params![value, id])?;
         Ok(())
     }
 
+    /// Sets the number of servings the recipe yields.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the recipe
+    /// * `servings` - the number of servings or `None` if unknown
+    /// * `connection` - the database connection
+    pub fn update_in_database_servings(
+        id: Uuid,
+        servings: Option<u32>,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        if servings == Some(0) {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid servings",
+                format!("Recipe {} cannot yield zero servings.", id),
+                "The number of servings must be positive.",
+            )));
+        }
+
+        connection
+            .execute("UPDATE recipe SET servings = ?1 WHERE id = ?2", params![servings, id])?;
+        Ok(())
+    }
+
     pub fn update_in_database_insert_tag(
         id: Uuid,
         tag: &str,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
         connection.execute(
             "INSERT INTO tag_recipe_mapping (tag, recipe_id) VALUES (?1, ?2)",
             params![tag.trim(), id],
         )?;
+        index_recipe(id, connection)?;
         Ok(())
     }
 
@@ -141,12 +393,13 @@ impl Recipe {
         tag: &str,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
         connection.execute(
             "DELETE FROM tag_recipe_mapping WHERE tag = ?1 AND recipe_id = ?2",
             params![tag.trim(), id],
         )?;
+        index_recipe(id, connection)?;
         Ok(())
     }
 
@@ -155,7 +408,7 @@ impl Recipe {
         attachment_id: Uuid,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(recipe_id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(recipe_id, connection)?;
         Attachment::exists_in_database_by_id_throw_not_found(attachment_id, connection)?;
 
         connection.execute(
@@ -169,71 +422,13 @@ impl Recipe {
         id: Uuid,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        Self::exists_in_database_by_id_throw_not_found(id, &connection)?;
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
 
         connection.execute("DELETE FROM recipe WHERE id = ?1", params![id])?;
+        index_recipe(id, connection)?;
         Ok(())
     }
 
-    pub fn tags_by_id(
-        recipe_id: Uuid,
-        connection: &Connection,
-    ) -> Result<Vec<String>, rusqlite::Error> {
-        let mut tag_stmt =
-            connection.prepare("SELECT tag FROM tag_recipe_mapping WHERE recipe_id = ?1")?;
-        let tag_rows = tag_stmt.query_map([recipe_id], |row| Ok(row.get(0)?))?;
-        let mut tags = Vec::new();
-        for tag in tag_rows {
-            tags.push(tag?);
-        }
-        Ok(tags)
-    }
-
-    pub fn attachments_by_id(
-        recipe_id: Uuid,
-        connection: &Connection,
-    ) -> Result<Vec<Attachment>, rusqlite::Error> {
-        let mut attachment_stmt = connection.prepare(
-            "
-                SELECT attachment.id, attachment.name, attachment.creation_time 
-                FROM attachment 
-                INNER JOIN attachment_recipe_mapping 
-                    ON attachment.id = attachment_recipe_mapping.attachment_id      
-                WHERE attachment_recipe_mapping.recipe_id = ?1",
-        )?;
-        let attachment_rows =
-            attachment_stmt.query_map([recipe_id], |row| Ok(Attachment::try_from(row)?))?;
-
-        let mut attachments = Vec::new();
-        for attachment in attachment_rows {
-            attachments.push(attachment?);
-        }
-        Ok(attachments)
-    }
-
-    pub fn thumbnail_by_id(
-        recipe_id: Uuid,
-        connection: &Connection,
-    ) -> Result<Option<Attachment>, rusqlite::Error> {
-        let mut attachment_stmt = connection.prepare(
-            "
-                SELECT id, name, creation_time 
-                FROM attachment 
-                WHERE id = (
-                    SELECT thumbnail FROM recipe WHERE id = ?1
-                )",
-        )?;
-        let attachment_option = attachment_stmt
-            .query_map([recipe_id], |row| Ok(Attachment::try_from(row)?))?
-            .next();
-
-        if attachment_option.is_some() {
-            Ok(Some(attachment_option.unwrap()?))
-        } else {
-            Ok(None)
-        }
-    }
-
     pub fn exists_in_database_by_id(
         recipe_id: Uuid,
         connection: &Connection,
@@ -253,7 +448,7 @@ impl Recipe {
         recipe_id: Uuid,
         connection: &Connection,
     ) -> Result<(), HomeworkError> {
-        if !Self::exists_in_database_by_id(recipe_id, &connection)? {
+        if !Self::exists_in_database_by_id(recipe_id, connection)? {
             Err(HomeworkError::NotFoundError(InternalError::new(
                 "Recipe not found",
                 format!("The recipe {} does not exist.", recipe_id),
@@ -265,27 +460,61 @@ impl Recipe {
     }
 }
 
-impl TryFrom<(&Row<'_>, &Connection)> for Recipe {
+impl TryFrom<&Row<'_>> for Recipe {
     type Error = rusqlite::Error;
 
-    fn try_from(data: (&Row, &Connection)) -> Result<Self, Self::Error> {
-        let (row, connection) = data;
-        let id = row.get(0)?;
+    /// Converts the row into a recipe without its relations,
+    /// which are loaded by [`Recipe::load_relations`].
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
         Ok(Recipe {
-            id,
+            id: row.get(0)?,
             title: row.get(1)?,
             instructions: row.get(2)?,
             reference: row.get(3)?,
             rating: row.get(4)?,
-            tags: Recipe::tags_by_id(id, connection)?,
-            thumbnail: Recipe::thumbnail_by_id(id, connection)?,
-            attachments: Recipe::attachments_by_id(id, connection)?,
-            ingredients: Ingredient::select_from_database_by_recipe_id(id, connection)?,
+            servings: row.get(6)?,
+            tags: Vec::new(),
+            thumbnail: None,
+            attachments: Vec::new(),
+            ingredients: Vec::new(),
             creation_time: row.get(5)?,
         })
     }
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The query parameters filtering and sorting a list of recipes.
+pub struct RecipeFilter {
+    /// A tag the recipes must be tagged with.
+    tag: Option<String>,
+    /// The minimum rating of the recipes.
+    minimum_rating: Option<u8>,
+    /// The field the recipes are sorted by. Defaults to the title.
+    #[serde(default)]
+    sort: RecipeSortField,
+    /// The direction the recipes are sorted in. Defaults to ascending.
+    #[serde(default)]
+    direction: SortDirection,
+}
+
+impl RecipeFilter {
+    /// Returns the tag the recipes must be tagged with if filtered by tag.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The fields a list of recipes can be sorted by.
+pub enum RecipeSortField {
+    #[default]
+    Title,
+    Rating,
+    CreationTime,
+}
+
 const COLUMN_STRING_TITLE: &str = "title";
 const COLUMN_STRING_INSTRUCTIONS: &str = "instructions";
 const COLUMN_STRING_REFERENCE: &str = "reference";
@@ -326,3 +555,6 @@ impl TryFrom<&str> for StringColumns {
         }
     }
 }
+
+#[cfg(test)]
+mod test;
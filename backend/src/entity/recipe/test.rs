@@ -0,0 +1,120 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    application::config::Configuration, service::batch_loader_service::MAXIMUM_IDS_PER_QUERY,
+};
+
+use super::*;
+
+/// The number of recipes generated to count the queries, which spans several batches.
+const QUERY_COUNT_RECIPES: u128 = 3000;
+
+/// The number of statements executed on connections traced by `count_query`.
+static EXECUTED_QUERIES: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts an executed statement.
+fn count_query(_: &str) {
+    EXECUTED_QUERIES.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Generates recipes with ingredients, tags, attachments and thumbnails.
+fn generate_recipes(connection: &Connection, count: u128) {
+    for index in 0..count {
+        let recipe_id = Uuid::from_u128(index + 1);
+        let attachment_id = Uuid::from_u128(u128::MAX - index);
+        connection
+            .execute(
+                "INSERT INTO attachment (id, name, creation_time) VALUES (?1, ?2, ?3)",
+                params![attachment_id, format!("image {}.png", index), Utc::now()],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO recipe (id, title, instructions, reference, rating, creation_time, thumbnail) VALUES (?1, ?2, '', '', 0, ?3, ?4)",
+                params![recipe_id, format!("Recipe {}", index), Utc::now(), attachment_id],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO attachment_recipe_mapping (recipe_id, attachment_id) VALUES (?1, ?2)",
+                params![recipe_id, attachment_id],
+            )
+            .unwrap();
+        for tag in ["Dinner", "Quick"] {
+            connection
+                .execute(
+                    "INSERT INTO tag_recipe_mapping (tag, recipe_id) VALUES (?1, ?2)",
+                    params![tag, recipe_id],
+                )
+                .unwrap();
+        }
+        for ordering in 0..3 {
+            connection
+                .execute(
+                    "INSERT INTO ingredient (id, amount, unit, text, creation_time, recipe_id, ordering) VALUES (?1, '1', 'g', ?2, ?3, ?4, ?5)",
+                    params![
+                        Configuration::generate_uuid(),
+                        format!("Ingredient {}", ordering),
+                        Utc::now(),
+                        recipe_id,
+                        ordering
+                    ],
+                )
+                .unwrap();
+        }
+    }
+}
+
+/// Selects all recipes without relations.
+fn select_recipes(connection: &Connection) -> Vec<Recipe> {
+    let mut stmt = connection
+        .prepare("SELECT id, title, instructions, reference, rating, creation_time, servings FROM recipe")
+        .unwrap();
+    let rows = stmt.query_map([], |row| Recipe::try_from(row)).unwrap();
+    rows.map(Result::unwrap).collect()
+}
+
+#[test]
+/// Tests if the relations of recipes are assembled from the batched queries.
+fn test_load_relations() {
+    let connection = Configuration::in_memory_database_connection();
+    generate_recipes(&connection, 3);
+    let mut recipes = select_recipes(&connection);
+    Recipe::load_relations(&mut recipes, &connection).unwrap();
+
+    for recipe in &recipes {
+        assert_eq!(recipe.tags, vec!["Dinner", "Quick"]);
+        assert_eq!(recipe.attachments.len(), 1);
+        assert_eq!(recipe.thumbnail.as_ref(), recipe.attachments.first());
+        let ingredients: Vec<&str> = recipe
+            .ingredients
+            .iter()
+            .map(|ingredient| ingredient.text())
+            .collect();
+        assert_eq!(ingredients, vec!["Ingredient 0", "Ingredient 1", "Ingredient 2"]);
+        assert!(recipe
+            .ingredients
+            .iter()
+            .all(|ingredient| ingredient.recipe_id() == recipe.id));
+    }
+}
+
+#[test]
+/// Tests if loading the relations of recipes issues one query per relation and batch
+/// of IDs instead of one query per relation and recipe.
+fn test_load_relations_query_count() {
+    let mut connection = Configuration::in_memory_database_connection();
+    generate_recipes(&connection, QUERY_COUNT_RECIPES);
+    let mut recipes = select_recipes(&connection);
+
+    connection.trace(Some(count_query));
+    Recipe::load_relations(&mut recipes, &connection).unwrap();
+    connection.trace(None);
+
+    // Tags, thumbnails, attachments and ingredients are loaded per batch.
+    let batches = (QUERY_COUNT_RECIPES as usize).div_ceil(MAXIMUM_IDS_PER_QUERY);
+    assert_eq!(EXECUTED_QUERIES.load(Ordering::SeqCst), 4 * batches);
+    assert!(recipes
+        .iter()
+        .all(|recipe| recipe.tags.len() == 2 && recipe.ingredients.len() == 3));
+}
@@ -0,0 +1,279 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::application::error::{HomeworkError, InternalError};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A list of items to buy, which is usually generated from the ingredients of recipes.
+pub struct ShoppingList {
+    id: Uuid,
+    title: String,
+    items: Vec<ShoppingListItem>,
+    creation_time: DateTime<Utc>,
+}
+
+impl ShoppingList {
+    /// Creates a new `ShoppingList`.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the shopping list
+    /// * `title` - the title of the shopping list
+    /// * `items` - the items to buy in the order they are displayed
+    pub fn new<T: ToString>(id: Uuid, title: T, items: Vec<ShoppingListItem>) -> Self {
+        ShoppingList {
+            id,
+            title: title.to_string(),
+            items,
+            creation_time: Utc::now(),
+        }
+    }
+
+    /// Returns the ID of this `ShoppingList`.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the title of this `ShoppingList`.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the items of this `ShoppingList` in the order they are displayed.
+    pub fn items(&self) -> &Vec<ShoppingListItem> {
+        &self.items
+    }
+
+    pub fn select_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<ShoppingList, HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+        Ok(connection.query_row(
+            "SELECT id, title, creation_time FROM shopping_list WHERE id = ?1",
+            [id],
+            |row| ShoppingList::try_from((row, connection)),
+        )?)
+    }
+
+    pub fn select_all_from_database(
+        connection: &Connection,
+    ) -> Result<Vec<ShoppingList>, HomeworkError> {
+        let mut stmt = connection.prepare(
+            "SELECT id, title, creation_time FROM shopping_list ORDER BY creation_time DESC",
+        )?;
+        let list_query = stmt.query_map([], |row| ShoppingList::try_from((row, connection)))?;
+        let mut lists = Vec::new();
+        for list in list_query {
+            lists.push(list?);
+        }
+        Ok(lists)
+    }
+
+    /// Inserts the shopping list including all items into the database.
+    ///
+    /// # Parameters
+    ///
+    /// * `connection` - the database connection
+    pub fn insert_into_database(&self, connection: &Connection) -> Result<(), HomeworkError> {
+        if Self::exists_in_database_by_id(self.id(), connection)? {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Shopping list already exists",
+                format!("The shopping list {} already exists.", self.id()),
+                "The shopping list already exists.",
+            )));
+        }
+
+        connection.execute(
+            "INSERT INTO shopping_list (id, title, creation_time) VALUES (?1, ?2, ?3)",
+            params![self.id(), self.title().trim(), self.creation_time],
+        )?;
+        for (ordering, item) in self.items().iter().enumerate() {
+            connection.execute(
+                "INSERT INTO shopping_list_item (id, shopping_list_id, name, amount, unit, checked, ordering) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![item.id(), self.id(), item.name(), item.amount(), item.unit(), item.is_checked(), ordering],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Checks or unchecks an item of the shopping list.
+    /// The state is set instead of toggled, so concurrent shoppers do not revert each other.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the shopping list
+    /// * `item_id` - the ID of the item
+    /// * `checked` - if the item has been bought
+    /// * `connection` - the database connection
+    pub fn update_in_database_item_checked(
+        id: Uuid,
+        item_id: Uuid,
+        checked: bool,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+
+        let updated = connection.execute(
+            "UPDATE shopping_list_item SET checked = ?1 WHERE id = ?2 AND shopping_list_id = ?3",
+            params![checked, item_id, id],
+        )?;
+        if updated == 0 {
+            return Err(HomeworkError::NotFoundError(InternalError::new(
+                "Shopping list item not found",
+                format!("The item {} does not exist on shopping list {}.", item_id, id),
+                "The item does not exist on the shopping list.",
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn delete_from_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        Self::exists_in_database_by_id_throw_not_found(id, connection)?;
+
+        connection.execute("DELETE FROM shopping_list WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn exists_in_database_by_id(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<bool, rusqlite::Error> {
+        let mut stmt = connection.prepare("SELECT 1 FROM shopping_list WHERE id = ?1")?;
+        stmt.exists([id])
+    }
+
+    /// Automatically throws a ```Not Found``` if the entry does not exist.
+    /// Returns an ```Ok``` otherwise.
+    ///
+    /// Parameters
+    ///
+    /// * ```id``` - the ID of the shopping list
+    /// * ```connection``` - the database connection
+    pub fn exists_in_database_by_id_throw_not_found(
+        id: Uuid,
+        connection: &Connection,
+    ) -> Result<(), HomeworkError> {
+        if !Self::exists_in_database_by_id(id, connection)? {
+            Err(HomeworkError::NotFoundError(InternalError::new(
+                "Shopping list not found",
+                format!("The shopping list {} does not exist.", id),
+                "The shopping list does not exist.",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl TryFrom<(&Row<'_>, &Connection)> for ShoppingList {
+    type Error = rusqlite::Error;
+
+    fn try_from(data: (&Row, &Connection)) -> Result<Self, Self::Error> {
+        let (row, connection) = data;
+        let id = row.get(0)?;
+        Ok(ShoppingList {
+            id,
+            title: row.get(1)?,
+            items: ShoppingListItem::select_from_database_by_shopping_list_id(id, connection)?,
+            creation_time: row.get(2)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A single item of a [`ShoppingList`].
+pub struct ShoppingListItem {
+    id: Uuid,
+    name: String,
+    amount: String,
+    unit: String,
+    checked: bool,
+}
+
+impl ShoppingListItem {
+    /// Creates a new unchecked `ShoppingListItem`.
+    ///
+    /// # Parameters
+    ///
+    /// * `id` - the ID of the item
+    /// * `name` - the name of the item
+    /// * `amount` - the amount to buy
+    /// * `unit` - the unit of the amount
+    pub fn new<T: ToString, U: ToString, V: ToString>(
+        id: Uuid,
+        name: T,
+        amount: U,
+        unit: V,
+    ) -> Self {
+        ShoppingListItem {
+            id,
+            name: name.to_string(),
+            amount: amount.to_string(),
+            unit: unit.to_string(),
+            checked: false,
+        }
+    }
+
+    /// Returns the ID of this `ShoppingListItem`.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the name of this `ShoppingListItem`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the amount to buy of this `ShoppingListItem`.
+    pub fn amount(&self) -> &str {
+        &self.amount
+    }
+
+    /// Returns the unit of the amount of this `ShoppingListItem`.
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    /// Returns `true` if this `ShoppingListItem` has already been bought.
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    pub fn select_from_database_by_shopping_list_id(
+        shopping_list_id: Uuid,
+        connection: &Connection,
+    ) -> Result<Vec<ShoppingListItem>, rusqlite::Error> {
+        let mut stmt = connection.prepare(
+            "SELECT id, name, amount, unit, checked FROM shopping_list_item WHERE shopping_list_id = ?1 ORDER BY ordering",
+        )?;
+        let item_rows =
+            stmt.query_map([shopping_list_id], |row| ShoppingListItem::try_from(row))?;
+        let mut items = Vec::new();
+        for item in item_rows {
+            items.push(item?);
+        }
+        Ok(items)
+    }
+}
+
+impl TryFrom<&Row<'_>> for ShoppingListItem {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        Ok(ShoppingListItem {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            amount: row.get(2)?,
+            unit: row.get(3)?,
+            checked: row.get(4)?,
+        })
+    }
+}
@@ -1,23 +1,59 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
-use actix_web::{middleware, App, HttpServer};
+use actix_web::{dev::Service, middleware, App, HttpServer};
 use application::{config::Configuration, error::HomeworkError};
 use controller::routing::routing_config;
+use futures_util::{future, FutureExt};
 use log::error;
 use parking_lot::Mutex;
-use service::backup_service::BackupService;
+use service::{
+    application_service::is_modifying_request,
+    backup_service::{BackupService, WriteGate},
+};
+
+/// The usage of the command line interface.
+const USAGE: &str = "Usage:
+    backend                                        Starts the server.
+    backend restore <archive> [--key-file <file>]  Restores a backup archive while the server is stopped.
+    backend verify <archive> [--key-file <file>]   Checks that a backup archive can be restored.";
 
 #[actix_web::main]
 async fn main() -> Result<(), HomeworkError> {
-    let app_config = Arc::new(init_config());
+    let mut app_config = init_config();
     env_logger::Builder::from_env(
         env_logger::Env::default().default_filter_or(app_config.log_level().as_str()),
     )
     .init();
+    app_config.validate_base_currency();
+    let app_config = Arc::new(app_config);
     let app_config_internal = Arc::clone(&app_config);
-    Configuration::initialise_database()?;
     // Create a backup service and check on a regular basis if any backups need to be performed.
     let backup_service = Arc::new(Mutex::new(BackupService::new(Arc::clone(&app_config))));
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let arguments: Vec<&str> = arguments.iter().map(String::as_str).collect();
+    match arguments.as_slice() {
+        [] => (),
+        ["restore", archive] | ["restore", archive, "--key-file", _] => {
+            let secret = key_file_secret(&arguments)?;
+            BackupService::restore_backup(&backup_service, Path::new(archive), secret.as_deref())?;
+            println!("Restored backup {}.", archive);
+            return Ok(());
+        },
+        ["verify", archive] | ["verify", archive, "--key-file", _] => {
+            let secret = key_file_secret(&arguments)?;
+            backup_service
+                .lock()
+                .verify_backup(Path::new(archive), secret.as_deref())?;
+            println!("Verified backup {}.", archive);
+            return Ok(());
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        },
+    }
+    app_config.initialise_database(&backup_service.lock())?;
+    let write_gate = backup_service.lock().write_gate();
     let backup_service_schedule = Arc::clone(&backup_service);
     actix_rt::spawn(async move {
         let mut interval = actix_rt::time::interval(std::time::Duration::from_secs(3600));
@@ -31,8 +67,27 @@ async fn main() -> Result<(), HomeworkError> {
     });
 
     Ok(HttpServer::new(move || {
+        let write_gate = Arc::clone(&write_gate);
         App::new()
             .wrap(middleware::Logger::default())
+            // Reject modifying requests while a backup is restored.
+            .wrap_fn(move |request, service| {
+                let permit = if is_modifying_request(&request) {
+                    WriteGate::begin_write(&write_gate).map(Some)
+                } else {
+                    Ok(None)
+                };
+                match permit {
+                    Ok(permit) => service
+                        .call(request)
+                        .map(move |response| {
+                            drop(permit);
+                            response
+                        })
+                        .boxed_local(),
+                    Err(err) => future::ready(Err(err.into())).boxed_local(),
+                }
+            })
             .app_data(Arc::clone(&app_config_internal))
             .app_data(Arc::clone(&backup_service))
             .configure(routing_config)
@@ -60,6 +115,18 @@ fn init_config() -> Configuration {
     config
 }
 
+/// Reads the key file of the `--key-file` option if specified.
+///
+/// # Parameters
+///
+/// * `arguments` - the command line arguments
+fn key_file_secret(arguments: &[&str]) -> Result<Option<Vec<u8>>, HomeworkError> {
+    match arguments {
+        [.., "--key-file", key_file] => Ok(Some(std::fs::read(key_file)?)),
+        _ => Ok(None),
+    }
+}
+
 mod application;
 mod controller;
 mod entity;
@@ -1,2 +1,24 @@
 pub mod application_service;
-pub mod backup_service;
\ No newline at end of file
+pub mod backup_service;
+pub mod batch_loader_service;
+pub mod bank_import_service;
+pub mod budget_service;
+pub mod calendar_service;
+pub mod cookbook_service;
+pub mod currency_service;
+pub mod encryption_service;
+pub mod html_service;
+pub mod migration_service;
+pub mod occurrence_service;
+pub mod pagination_service;
+pub mod person_service;
+pub mod quantity_service;
+pub mod recipe_export_service;
+pub mod recipe_import_service;
+pub mod report_service;
+pub mod search_service;
+pub mod settlement_service;
+pub mod shopping_list_service;
+pub mod split_service;
+pub mod sub_recipe_service;
+pub mod unit_service;
\ No newline at end of file
@@ -1,12 +1,15 @@
 use std::sync::Arc;
 
-use actix_web::HttpRequest;
+use actix_web::{dev::ServiceRequest, http::Method, HttpRequest};
 use parking_lot::Mutex;
 
 use crate::application::config::Configuration;
 
 use super::backup_service::BackupService;
 
+/// The path prefix of administrative requests, which are not blocked during a restore.
+const ADMIN_PATH_PREFIX: &str = "/api/admin/";
+
 
     /// Extracts the [`BackupService`] from a request.
     /// 
@@ -40,4 +43,15 @@ use super::backup_service::BackupService;
                 .app_data::<Arc<Configuration>>()
                 .expect("The backup service must be accessible."),
         )
-    }
\ No newline at end of file
+    }
+
+    /// Checks if a request may modify data, so it needs to be blocked while a backup is restored.
+    /// Administrative requests are excluded, as they perform the restore themselves.
+    ///
+    /// # Parameters
+    ///
+    /// * `request` - the HTTP request to check
+    pub fn is_modifying_request(request: &ServiceRequest) -> bool {
+        !matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS)
+            && !request.path().starts_with(ADMIN_PATH_PREFIX)
+    }
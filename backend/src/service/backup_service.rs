@@ -1,25 +1,226 @@
-use std::{io::Write, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{Read, Seek},
+    os::raw::c_int,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use chrono::{DateTime, Utc};
-use log::info;
-use zip::write::FileOptions;
+use log::{info, warn};
+use openssl::hash::{Hasher, MessageDigest};
+use parking_lot::Mutex;
+use rusqlite::{backup::Backup, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zip::{result::ZipError, write::FileOptions, ZipArchive};
 
-use crate::application::config::Configuration;
+use crate::application::{
+    config::Configuration,
+    error::{HomeworkError, InternalError},
+};
+
+use super::{
+    encryption_service::{EncryptionKey, EncryptionParameters},
+    migration_service::{latest_schema_version, schema_version},
+};
 
 /// The file extension of backup archives.
 const BACKUP_FILE_EXTENSION: &str = "zip";
 /// The database file inside the archive.
 const ARCHIVE_DATABASE_FILE: &str = "database.sqlite";
-/// The attachment folder inside the archive.
+/// The attachment folder inside archives of earlier versions, which contain all attachments.
 const ARCHIVE_ATTACHMENT_FOLDER: &str = "attachments";
+/// The manifest inside the archive, which lists the stored content of all attachments.
+const ARCHIVE_MANIFEST_FILE: &str = "manifest.json";
+/// The folder next to the archives in which the content of attachments is stored once.
+const BLOB_FOLDER: &str = "blobs";
+/// The suffix of a blob that is not completely written yet.
+const BLOB_PARTIAL_SUFFIX: &str = "partial";
+/// The unencrypted parameters of the key encrypted archives and their blobs are encrypted with,
+/// both inside the archive and as current parameters of new backups in the backup folder.
+const ENCRYPTION_FILE: &str = "encryption.json";
+/// The suffix of the encrypted database extracted from an archive before it is decrypted.
+const ENCRYPTED_SUFFIX: &str = "encrypted";
 /// The intervall in hours that backups are checked.
 const BACKUP_UPDATE_INTERVALL: i64 = 3;
+/// The suffix of the database copy written into a new archive.
+const DATABASE_COPY_SUFFIX: &str = "sqlite";
+/// The pause before the database is copied again if it is locked by a write.
+const DATABASE_COPY_BUSY_PAUSE: Duration = Duration::from_millis(50);
+/// The suffix of the files extracted from an archive before they are restored.
+const RESTORE_SUFFIX: &str = "restore";
+/// The suffix of the attachment folder replaced by a restore until it is removed.
+const REPLACED_SUFFIX: &str = "replaced";
+/// The maximum time a restore waits for running modifying requests to finish.
+const RESTORE_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+/// The intervall in which a restore checks if modifying requests are still running.
+const RESTORE_WRITE_POLL_INTERVALL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Default)]
+/// Keeps track of modifying requests, so a restore only replaces the database after
+/// all running requests finished and no new modifying requests start during the restore.
+pub struct WriteGate {
+    /// The number of modifying requests currently processed.
+    running_writes: AtomicUsize,
+    /// If a backup is currently restored.
+    restoring: AtomicBool,
+}
+
+impl WriteGate {
+    /// Registers a modifying request, which is finished when the returned permit is dropped.
+    /// Fails if a backup is currently restored.
+    ///
+    /// # Parameters
+    ///
+    /// * `gate` - the gate to register the request at
+    pub fn begin_write(gate: &Arc<WriteGate>) -> Result<WritePermit, HomeworkError> {
+        // Register the request before checking, so a restore starting concurrently waits for it.
+        gate.running_writes.fetch_add(1, Ordering::SeqCst);
+        let permit = WritePermit(Arc::clone(gate));
+        if gate.restoring.load(Ordering::SeqCst) {
+            return Err(HomeworkError::ServiceUnavailableError(InternalError::new(
+                "Restore in progress",
+                "A modifying request was rejected as a backup is currently restored.",
+                "A backup is currently restored. Please try again in a moment.",
+            )));
+        }
+        Ok(permit)
+    }
+
+    /// Blocks new modifying requests and waits for the running ones to finish.
+    /// Modifying requests are allowed again when the returned lock is dropped.
+    /// Fails if the gate is already closed by another restore.
+    fn close(&self) -> Result<WriteLock<'_>, HomeworkError> {
+        if self
+            .restoring
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(HomeworkError::ServiceUnavailableError(InternalError::new(
+                "Restore in progress",
+                "A restore was rejected as another backup is currently restored.",
+                "Another backup is currently restored. Please try again in a moment.",
+            )));
+        }
+        let lock = WriteLock(self);
+        let start = Instant::now();
+        while self.running_writes.load(Ordering::SeqCst) > 0 {
+            if start.elapsed() > RESTORE_WRITE_TIMEOUT {
+                return Err(HomeworkError::ServiceUnavailableError(InternalError::new(
+                    "Restore in progress",
+                    format!(
+                        "{} modifying requests did not finish within {:?}.",
+                        self.running_writes.load(Ordering::SeqCst),
+                        RESTORE_WRITE_TIMEOUT
+                    ),
+                    "The backup could not be restored as other changes are still running.",
+                )));
+            }
+            std::thread::sleep(RESTORE_WRITE_POLL_INTERVALL);
+        }
+        Ok(lock)
+    }
+}
+
+/// A modifying request registered at a [`WriteGate`].
+pub struct WritePermit(Arc<WriteGate>);
+
+impl Drop for WritePermit {
+    fn drop(&mut self) {
+        self.0.running_writes.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Blocks modifying requests at a [`WriteGate`] until dropped.
+struct WriteLock<'a>(&'a WriteGate);
+
+impl Drop for WriteLock<'_> {
+    fn drop(&mut self) {
+        self.0.restoring.store(false, Ordering::SeqCst);
+    }
+}
+
+/// The temporary files of a restore, which are removed when dropped.
+struct RestoreFiles {
+    /// The database extracted from the archive.
+    database: PathBuf,
+    /// The encrypted database extracted from the archive.
+    encrypted_database: PathBuf,
+    /// The attachment folder extracted from the archive.
+    attachments: PathBuf,
+    /// The attachment folder replaced by the restored one.
+    replaced_attachments: PathBuf,
+}
+
+impl RestoreFiles {
+    /// Creates the temporary files next to the files they replace, so they can be
+    /// moved into place without copying. The file names are unique, so restores and
+    /// verifications running at the same time do not use the same files.
+    ///
+    /// # Parameters
+    ///
+    /// * `database` - the database file to restore
+    /// * `attachments` - the attachment folder to restore
+    fn new(database: &Path, attachments: &Path) -> Self {
+        let id = Configuration::generate_uuid();
+        let restore_suffix = format!("{}-{}", RESTORE_SUFFIX, id);
+        let database_path = suffixed_path(database, &restore_suffix);
+        RestoreFiles {
+            encrypted_database: suffixed_path(&database_path, ENCRYPTED_SUFFIX),
+            database: database_path,
+            attachments: suffixed_path(attachments, &restore_suffix),
+            replaced_attachments: suffixed_path(
+                attachments,
+                &format!("{}-{}", REPLACED_SUFFIX, id),
+            ),
+        }
+    }
+
+    /// Removes all temporary files that still exist.
+    fn remove(&self) {
+        for file in [&self.database, &self.encrypted_database] {
+            if file.exists() {
+                if let Err(err) = std::fs::remove_file(file) {
+                    warn!("Could not remove {}: {}", file.to_string_lossy(), err);
+                }
+            }
+        }
+        for folder in [&self.attachments, &self.replaced_attachments] {
+            if folder.exists() {
+                if let Err(err) = std::fs::remove_dir_all(folder) {
+                    warn!("Could not remove {}: {}", folder.to_string_lossy(), err);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for RestoreFiles {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+/// The attachments of a backup archive, whose content is stored in the blob folder.
+struct BackupManifest {
+    /// The SHA-256 hashes of the attachment contents by attachment file name.
+    attachments: BTreeMap<String, String>,
+}
 
 #[derive(Debug)]
 /// A service that automatically creates backups of the application.
 pub struct BackupService {
     configuration: Arc<Configuration>,
+    database_path: PathBuf,
     next_timed_backup: Option<DateTime<Utc>>,
+    write_gate: Arc<WriteGate>,
 }
 
 impl BackupService {
@@ -31,10 +232,18 @@ impl BackupService {
     pub fn new(configuration: Arc<Configuration>) -> Self {
         BackupService {
             configuration,
+            database_path: Configuration::application_database_file_path(),
             next_timed_backup: None,
+            write_gate: Arc::new(WriteGate::default()),
         }
     }
 
+    /// Returns the [`WriteGate`] modifying requests need to pass, so they are blocked
+    /// while a backup is restored.
+    pub fn write_gate(&self) -> Arc<WriteGate> {
+        Arc::clone(&self.write_gate)
+    }
+
     /// Schedules or re-schedules a timed backup.
     pub fn request_timed_backup(&mut self) {
         self.next_timed_backup =
@@ -44,7 +253,7 @@ impl BackupService {
 
     /// Checks if a timed backup is scheduled and executes the backup if necessary.
     /// Deletes old backups afterwards.
-    pub fn check_timed_backup(&mut self) -> Result<(), std::io::Error> {
+    pub fn check_timed_backup(&mut self) -> Result<(), HomeworkError> {
         info!("Performing timed backup check...");
         if let Some(scheduled_backup_time) = self.next_timed_backup {
             if chrono::Utc::now() >= scheduled_backup_time {
@@ -57,68 +266,145 @@ impl BackupService {
     }
 
     /// Creates a backup of the current state.
-    pub fn create_backup(&self) -> Result<(), std::io::Error> {
+    pub fn create_backup(&self) -> Result<(), HomeworkError> {
         let attachments_path = self.configuration().application_attachments_folder_path();
-        let database_path = Configuration::application_database_file_path();
         let timestamp = chrono::Utc::now().timestamp();
         info!("Creating new backup {}.", timestamp);
         let mut backup_file_path = self.configuration().application_backup_folder_path();
         std::fs::create_dir_all(&backup_file_path)?;
+        let key = match self.configuration().backup_secret()? {
+            Some(secret) => Some(current_encryption_key(&backup_file_path, &secret)?),
+            None => None,
+        };
         backup_file_path.push(timestamp.to_string());
         backup_file_path.set_extension(BACKUP_FILE_EXTENSION);
-        let mut backup_archive = zip::ZipWriter::new(std::fs::File::create(backup_file_path)?);
-        let archive_options = FileOptions::default()
-            .compression_method(zip::CompressionMethod::Zstd)
-            .compression_level(Some(3));
-        backup_archive.start_file(ARCHIVE_DATABASE_FILE, archive_options)?;
-        backup_archive.write(&std::fs::read(database_path)?)?;
-        backup_archive.add_directory(ARCHIVE_ATTACHMENT_FOLDER, archive_options)?;
-        for attachment_entry in std::fs::read_dir(attachments_path)? {
-            let attachment_entry = attachment_entry?;
-            let attachment_file_name = format!(
-                "{}/{}",
-                ARCHIVE_ATTACHMENT_FOLDER,
-                attachment_entry.file_name().to_string_lossy()
-            );
-            backup_archive.start_file(attachment_file_name, archive_options)?;
-            backup_archive.write(&std::fs::read(attachment_entry.path())?)?;
-        }
-        backup_archive.finish()?;
-        Ok(())
+        write_backup_archive(
+            &self.database_path,
+            &attachments_path,
+            &backup_file_path,
+            key.as_ref(),
+        )
     }
 
-    /// Removes old backup archives.
+    /// Removes old backup archives and the attachment contents only they referenced.
     pub fn clean_backups(&self) -> Result<(), std::io::Error> {
-        let mut backups = self.current_backups()?;
-        if backups.len() > self.configuration().maximum_backups() {
-            // Backup archives are named by timestamp.
-            backups.sort();
-            for backup_path in &backups[0..(backups.len() - self.configuration().maximum_backups())]
-            {
-                info!("Removing old backup archive: {}", backup_path.to_string_lossy());
-                std::fs::remove_file(backup_path)?;
-            }
-        }
-        Ok(())
+        prune_backups(
+            &self.configuration().application_backup_folder_path(),
+            self.configuration().maximum_backups(),
+        )
     }
 
     /// Returns the currently present backup archives.
     pub fn current_backups(&self) -> Result<Vec<PathBuf>, std::io::Error> {
-        let backup_folder = self.configuration().application_backup_folder_path();
-        if !backup_folder.exists() {
-            return Ok(Vec::new());
-        } else {
-            let mut backups = Vec::new();
-            for entry in std::fs::read_dir(backup_folder)? {
-                let entry_path = entry?.path();
-                if entry_path.is_file()
-                    && entry_path.extension() == Some(BACKUP_FILE_EXTENSION.as_ref())
-                {
-                    backups.push(entry_path);
+        backup_archives(&self.configuration().application_backup_folder_path())
+    }
+
+    /// Returns the backup archive with the specified file name if it is present.
+    ///
+    /// # Parameters
+    ///
+    /// * `name` - the file name of the archive
+    pub fn current_backup(&self, name: &str) -> Result<PathBuf, HomeworkError> {
+        self.current_backups()?
+            .into_iter()
+            .find(|backup_path| backup_path.file_name() == Some(name.as_ref()))
+            .ok_or_else(|| {
+                HomeworkError::NotFoundError(InternalError::new(
+                    "Missing backup",
+                    format!("The backup archive {} does not exist.", name),
+                    "The backup does not exist.",
+                ))
+            })
+    }
+
+    /// Replaces the database and attachments with the content of a backup archive.
+    /// The archive is validated and its database is migrated to the current schema
+    /// before anything is replaced and the current state is backed up, so it can be
+    /// restored in turn. Modifying requests and other restores are rejected while the
+    /// files are replaced. Thumbnails are removed, so they are generated again from
+    /// the restored attachments on demand.
+    ///
+    /// The backup of the current state is always encrypted with the configured secret.
+    /// The service is not locked while waiting for running modifying requests,
+    /// as they lock it themselves to schedule a backup before they finish.
+    ///
+    /// # Parameters
+    ///
+    /// * `service` - the service restoring the backup
+    /// * `archive_path` - the path of the backup archive
+    /// * `secret` - the secret to decrypt the archive with instead of the configured one
+    pub fn restore_backup(
+        service: &Mutex<BackupService>,
+        archive_path: &Path,
+        secret: Option<&[u8]>,
+    ) -> Result<(), HomeworkError> {
+        info!("Restoring backup {}.", archive_path.to_string_lossy());
+        let (configuration, database_path, write_gate) = {
+            let service = service.lock();
+            (service.configuration(), service.database_path.clone(), service.write_gate())
+        };
+        let secret = match secret {
+            Some(secret) => Some(secret.to_vec()),
+            None => configuration.backup_secret()?,
+        };
+        let attachments_path = configuration.application_attachments_folder_path();
+        let files = RestoreFiles::new(&database_path, &attachments_path);
+        extract_archive(archive_path, &files, secret.as_deref())?;
+        validate_database(&files.database, &files.attachments)?;
+        // Backups of earlier versions are migrated before, so a failing migration
+        // leaves the current state untouched.
+        configuration.upgrade_database_file(&files.database)?;
+
+        let _write_lock = write_gate.close()?;
+        if database_path.is_file() {
+            service.lock().create_backup()?;
+        }
+        if attachments_path.exists() {
+            std::fs::rename(&attachments_path, &files.replaced_attachments)?;
+        }
+        if let Err(err) = std::fs::rename(&files.attachments, &attachments_path)
+            .and_then(|_| std::fs::rename(&files.database, &database_path))
+        {
+            // Move the previous attachments back, the database is only replaced last.
+            if files.replaced_attachments.exists() {
+                if attachments_path.exists() {
+                    std::fs::rename(&attachments_path, &files.attachments)?;
                 }
+                std::fs::rename(&files.replaced_attachments, &attachments_path)?;
             }
-            Ok(backups)
+            return Err(err.into());
         }
+        let thumbnails_path = configuration.application_thumbnail_folder_path();
+        if thumbnails_path.exists() {
+            std::fs::remove_dir_all(thumbnails_path)?;
+        }
+        info!("Restored backup {}.", archive_path.to_string_lossy());
+        Ok(())
+    }
+
+    /// Checks that a backup archive can be restored without restoring it.
+    ///
+    /// # Parameters
+    ///
+    /// * `archive_path` - the path of the backup archive
+    /// * `secret` - the secret to decrypt the archive with instead of the configured one
+    pub fn verify_backup(
+        &self,
+        archive_path: &Path,
+        secret: Option<&[u8]>,
+    ) -> Result<(), HomeworkError> {
+        let secret = match secret {
+            Some(secret) => Some(secret.to_vec()),
+            None => self.configuration().backup_secret()?,
+        };
+        let files = RestoreFiles::new(
+            &self.database_path,
+            &self.configuration().application_attachments_folder_path(),
+        );
+        extract_archive(archive_path, &files, secret.as_deref())?;
+        validate_database(&files.database, &files.attachments)?;
+        info!("Verified backup {}.", archive_path.to_string_lossy());
+        Ok(())
     }
 
     /// Returns a reference to the underlying [`Configuration`].
@@ -126,3 +412,482 @@ impl BackupService {
         Arc::clone(&self.configuration)
     }
 }
+
+/// Writes a backup archive of the database and attachments. The database is copied
+/// with the online backup API first, so writes during the backup cannot lead to an
+/// inconsistent copy. Incomplete archives are removed if the backup fails.
+///
+/// # Parameters
+///
+/// * `database_path` - the path of the database
+/// * `attachments_path` - the path of the attachment folder
+/// * `archive_path` - the path of the archive to create
+/// * `key` - the key to encrypt the database and attachments with if any
+fn write_backup_archive(
+    database_path: &Path,
+    attachments_path: &Path,
+    archive_path: &Path,
+    key: Option<&EncryptionKey>,
+) -> Result<(), HomeworkError> {
+    let database_copy_path = suffixed_path(archive_path, DATABASE_COPY_SUFFIX);
+    let result = copy_database(database_path, &database_copy_path)
+        .map_err(HomeworkError::from)
+        .and_then(|_| write_archive(&database_copy_path, attachments_path, archive_path, key));
+    if database_copy_path.exists() {
+        std::fs::remove_file(&database_copy_path)?;
+    }
+    if result.is_err() && archive_path.exists() {
+        std::fs::remove_file(archive_path)?;
+    }
+    result
+}
+
+/// Copies a database that may be in use with the online backup API.
+///
+/// # Parameters
+///
+/// * `database_path` - the path of the database
+/// * `copy_path` - the path of the copy
+fn copy_database(database_path: &Path, copy_path: &Path) -> Result<(), rusqlite::Error> {
+    let database = Connection::open_with_flags(database_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut copy = Connection::open(copy_path)?;
+    let backup = Backup::new(&database, &mut copy)?;
+    // Copying all pages in a single step prevents concurrent writes from restarting the copy.
+    backup.run_to_completion(c_int::MAX, DATABASE_COPY_BUSY_PAUSE, None)
+}
+
+/// Writes the database and the manifest of the attachments into a new archive.
+/// The content of attachments is stored in the blob folder next to the archive
+/// unless a previous backup already stored the same content. All files are streamed
+/// instead of being loaded into memory. If a key is specified, the database and blobs
+/// are encrypted and the archive contains the parameters to derive the key again.
+///
+/// # Parameters
+///
+/// * `database_path` - the path of the database copy
+/// * `attachments_path` - the path of the attachment folder
+/// * `archive_path` - the path of the archive to create
+/// * `key` - the key to encrypt the database and attachments with if any
+fn write_archive(
+    database_path: &Path,
+    attachments_path: &Path,
+    archive_path: &Path,
+    key: Option<&EncryptionKey>,
+) -> Result<(), HomeworkError> {
+    let blob_folder = blob_folder(archive_path);
+    std::fs::create_dir_all(&blob_folder)?;
+    let mut manifest = BackupManifest::default();
+    // The attachment folder is only created with the first attachment.
+    if attachments_path.exists() {
+        for attachment_entry in std::fs::read_dir(attachments_path)? {
+            let attachment_entry = attachment_entry?;
+            let content_id = store_blob(&attachment_entry.path(), &blob_folder, key)?;
+            manifest
+                .attachments
+                .insert(attachment_entry.file_name().to_string_lossy().to_string(), content_id);
+        }
+    }
+    let mut backup_archive = zip::ZipWriter::new(File::create(archive_path)?);
+    let archive_options = FileOptions::default()
+        .compression_method(zip::CompressionMethod::Zstd)
+        .compression_level(Some(3));
+    if let Some(key) = key {
+        backup_archive.start_file(ENCRYPTION_FILE, archive_options)?;
+        serde_json::to_writer(&mut backup_archive, key.parameters())?;
+    }
+    backup_archive.start_file(ARCHIVE_DATABASE_FILE, archive_options)?;
+    match key {
+        Some(key) => key.encrypt(&mut File::open(database_path)?, &mut backup_archive)?,
+        None => {
+            std::io::copy(&mut File::open(database_path)?, &mut backup_archive)?;
+        },
+    }
+    backup_archive.start_file(ARCHIVE_MANIFEST_FILE, archive_options)?;
+    serde_json::to_writer(&mut backup_archive, &manifest)?;
+    backup_archive.finish()?;
+    Ok(())
+}
+
+/// Stores the content of a file in the blob folder if it is not stored yet
+/// and returns its content ID, which is the name of the blob.
+///
+/// # Parameters
+///
+/// * `path` - the path of the file
+/// * `blob_folder` - the folder containing all blobs
+/// * `key` - the key to encrypt the blob with if any
+fn store_blob(
+    path: &Path,
+    blob_folder: &Path,
+    key: Option<&EncryptionKey>,
+) -> Result<String, HomeworkError> {
+    let content_id = content_id(path, key)?;
+    let blob_path = blob_folder.join(&content_id);
+    if !blob_path.exists() {
+        // Blobs are moved into place when complete, so an interrupted backup
+        // cannot leave an incomplete blob behind that later backups would reference.
+        let partial_path = suffixed_path(&blob_path, BLOB_PARTIAL_SUFFIX);
+        match key {
+            Some(key) => key.encrypt(&mut File::open(path)?, &mut File::create(&partial_path)?)?,
+            None => {
+                std::fs::copy(path, &partial_path)?;
+            },
+        }
+        std::fs::rename(&partial_path, &blob_path)?;
+    }
+    Ok(content_id)
+}
+
+/// Returns the ID of the file content, which is the SHA-256 hash for unencrypted
+/// backups. Encrypted backups use an ID that does not reveal the hash of the content.
+///
+/// # Parameters
+///
+/// * `path` - the path of the file
+/// * `key` - the key the content is encrypted with if any
+fn content_id(path: &Path, key: Option<&EncryptionKey>) -> Result<String, HomeworkError> {
+    match key {
+        Some(key) => key.content_id(&mut File::open(path)?),
+        None => Ok(file_hash(path)?),
+    }
+}
+
+/// Returns the key new backups are encrypted with. The parameters of the key are kept
+/// in the backup folder, so blobs of earlier backups can be reused. If the secret changed,
+/// a new key is derived, which later backups and their blobs are encrypted with.
+///
+/// # Parameters
+///
+/// * `backup_folder` - the folder containing the backups
+/// * `secret` - the passphrase or key file content
+fn current_encryption_key(
+    backup_folder: &Path,
+    secret: &[u8],
+) -> Result<EncryptionKey, HomeworkError> {
+    let parameters_path = backup_folder.join(ENCRYPTION_FILE);
+    if parameters_path.exists() {
+        let parameters: EncryptionParameters =
+            serde_json::from_reader(File::open(&parameters_path)?)?;
+        match EncryptionKey::from_parameters(secret, &parameters) {
+            Ok(key) => return Ok(key),
+            Err(HomeworkError::BadRequestError(_)) => {
+                info!("The backup secret changed, deriving a new backup key.")
+            },
+            Err(err) => return Err(err),
+        }
+    }
+    let key = EncryptionKey::generate(secret)?;
+    serde_json::to_writer_pretty(File::create(&parameters_path)?, key.parameters())?;
+    Ok(key)
+}
+
+/// Returns the hexadecimal SHA-256 hash of the file content.
+///
+/// # Parameters
+///
+/// * `path` - the path of the file
+fn file_hash(path: &Path) -> Result<String, std::io::Error> {
+    let mut hasher = Hasher::new(MessageDigest::sha256())?;
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finish()?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Returns the blob folder belonging to the backup archive.
+///
+/// # Parameters
+///
+/// * `archive_path` - the path of the backup archive
+fn blob_folder(archive_path: &Path) -> PathBuf {
+    archive_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(BLOB_FOLDER)
+}
+
+/// Returns the manifest of the backup archive or `None` for archives of earlier versions
+/// containing the attachments themselves.
+///
+/// # Parameters
+///
+/// * `archive` - the backup archive
+fn read_manifest<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<BackupManifest>, std::io::Error> {
+    match archive.by_name(ARCHIVE_MANIFEST_FILE) {
+        Ok(entry) => Ok(Some(serde_json::from_reader(entry)?)),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns the parameters of the key the backup archive is encrypted with
+/// or `None` if the archive is not encrypted.
+///
+/// # Parameters
+///
+/// * `archive` - the backup archive
+fn read_encryption_parameters<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<EncryptionParameters>, std::io::Error> {
+    match archive.by_name(ENCRYPTION_FILE) {
+        Ok(entry) => Ok(Some(serde_json::from_reader(entry)?)),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns the backup archives in the folder.
+///
+/// # Parameters
+///
+/// * `backup_folder` - the folder containing the backups
+fn backup_archives(backup_folder: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut backups = Vec::new();
+    if backup_folder.exists() {
+        for entry in std::fs::read_dir(backup_folder)? {
+            let entry_path = entry?.path();
+            if entry_path.is_file()
+                && entry_path.extension() == Some(BACKUP_FILE_EXTENSION.as_ref())
+            {
+                backups.push(entry_path);
+            }
+        }
+    }
+    Ok(backups)
+}
+
+/// Removes the oldest backup archives exceeding the maximum number of backups
+/// and all blobs no remaining archive references.
+///
+/// # Parameters
+///
+/// * `backup_folder` - the folder containing the backups
+/// * `maximum_backups` - the maximum number of backups to keep
+fn prune_backups(backup_folder: &Path, maximum_backups: usize) -> Result<(), std::io::Error> {
+    let mut backups = backup_archives(backup_folder)?;
+    // Backup archives are named by timestamp.
+    backups.sort();
+    let removed_backups = backups.len().saturating_sub(maximum_backups);
+    for backup_path in backups.drain(0..removed_backups) {
+        info!("Removing old backup archive: {}", backup_path.to_string_lossy());
+        std::fs::remove_file(backup_path)?;
+    }
+    let blob_folder = backup_folder.join(BLOB_FOLDER);
+    if !blob_folder.exists() {
+        return Ok(());
+    }
+    let mut referenced_blobs = HashSet::new();
+    for backup_path in &backups {
+        // Archives that cannot be read abort the pruning, so no blob they reference is lost.
+        let mut archive = ZipArchive::new(File::open(backup_path)?)?;
+        if let Some(manifest) = read_manifest(&mut archive)? {
+            referenced_blobs.extend(manifest.attachments.into_values());
+        }
+    }
+    for blob_entry in std::fs::read_dir(blob_folder)? {
+        let blob_entry = blob_entry?;
+        if !referenced_blobs.contains(blob_entry.file_name().to_string_lossy().as_ref()) {
+            info!("Removing unreferenced backup blob: {}", blob_entry.path().to_string_lossy());
+            std::fs::remove_file(blob_entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Extracts and decrypts the database and attachments of a backup archive
+/// into the temporary files.
+///
+/// # Parameters
+///
+/// * `archive_path` - the path of the backup archive
+/// * `files` - the temporary files to extract into
+/// * `secret` - the passphrase or key file content to decrypt encrypted archives with
+fn extract_archive(
+    archive_path: &Path,
+    files: &RestoreFiles,
+    secret: Option<&[u8]>,
+) -> Result<(), HomeworkError> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)
+        .map_err(|err| invalid_archive(archive_path, err))?;
+    let key = match read_encryption_parameters(&mut archive)
+        .map_err(|err| invalid_archive(archive_path, err))?
+    {
+        Some(parameters) => {
+            let secret = secret.ok_or_else(|| {
+                HomeworkError::BadRequestError(InternalError::new(
+                    "Missing backup key",
+                    format!(
+                        "The backup {} is encrypted, but no passphrase or key file is configured.",
+                        archive_path.to_string_lossy()
+                    ),
+                    "The backup is encrypted, but no passphrase or key file is configured.",
+                ))
+            })?;
+            Some(EncryptionKey::from_parameters(secret, &parameters)?)
+        },
+        None => None,
+    };
+    std::fs::create_dir_all(&files.attachments)?;
+    let mut contains_database = false;
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|err| invalid_archive(archive_path, err))?;
+        if entry.is_dir() {
+            continue;
+        }
+        // Entries are only written to the expected locations, independent of their names.
+        let target_path = match entry.enclosed_name() {
+            Some(name) if name == Path::new(ARCHIVE_DATABASE_FILE) => {
+                contains_database = true;
+                match key {
+                    Some(_) => files.encrypted_database.clone(),
+                    None => files.database.clone(),
+                }
+            },
+            Some(name)
+                if name == Path::new(ARCHIVE_MANIFEST_FILE)
+                    || name == Path::new(ENCRYPTION_FILE) =>
+            {
+                continue
+            },
+            Some(name) if name.parent() == Some(Path::new(ARCHIVE_ATTACHMENT_FOLDER)) => {
+                files.attachments.join(name.file_name().unwrap_or_default())
+            },
+            _ => {
+                warn!("Skipping unknown archive entry {}.", entry.name());
+                continue;
+            },
+        };
+        std::io::copy(&mut entry, &mut File::create(target_path)?)?;
+    }
+    if !contains_database {
+        return Err(invalid_archive(
+            archive_path,
+            format!("The entry {} is missing.", ARCHIVE_DATABASE_FILE),
+        ));
+    }
+    if let Some(key) = &key {
+        key.decrypt(
+            &mut File::open(&files.encrypted_database)?,
+            &mut File::create(&files.database)?,
+        )?;
+    }
+    let manifest = read_manifest(&mut archive).map_err(|err| invalid_archive(archive_path, err))?;
+    let blob_folder = blob_folder(archive_path);
+    for (name, content_id) in manifest.unwrap_or_default().attachments {
+        if Path::new(&name).file_name() != Some(name.as_ref()) {
+            return Err(invalid_archive(
+                archive_path,
+                format!("The attachment name {} is invalid.", name),
+            ));
+        }
+        let blob_path = blob_folder.join(&content_id);
+        if Path::new(&content_id).file_name() != Some(content_id.as_ref()) || !blob_path.is_file() {
+            return Err(invalid_archive(
+                archive_path,
+                format!("The content {} of attachment {} is missing.", content_id, name),
+            ));
+        }
+        let attachment_path = files.attachments.join(&name);
+        match &key {
+            Some(key) => {
+                key.decrypt(&mut File::open(&blob_path)?, &mut File::create(&attachment_path)?)?
+            },
+            None => {
+                std::fs::copy(&blob_path, &attachment_path)?;
+            },
+        }
+        if self::content_id(&attachment_path, key.as_ref())? != content_id {
+            return Err(invalid_archive(
+                archive_path,
+                format!("The content {} of attachment {} is damaged.", content_id, name),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the integrity of the database, that its schema is not newer than known
+/// and that a file exists for every attachment.
+///
+/// # Parameters
+///
+/// * `database_path` - the path of the database
+/// * `attachments_path` - the path of the attachment folder
+fn validate_database(database_path: &Path, attachments_path: &Path) -> Result<(), HomeworkError> {
+    let connection = Connection::open_with_flags(database_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let integrity: String = connection
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|err| invalid_archive(database_path, err))?;
+    if integrity != "ok" {
+        return Err(invalid_archive(database_path, integrity));
+    }
+    let version = schema_version(&connection)?;
+    if version > latest_schema_version() {
+        return Err(invalid_archive(
+            database_path,
+            format!(
+                "The schema version {} is newer than the latest known version {}.",
+                version,
+                latest_schema_version()
+            ),
+        ));
+    }
+    let contains_attachments: bool = connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'attachment')",
+        [],
+        |row| row.get(0),
+    )?;
+    if contains_attachments {
+        let mut stmt = connection.prepare("SELECT id FROM attachment")?;
+        let mut missing_attachments = Vec::new();
+        for attachment_id in stmt.query_map([], |row| row.get::<_, Uuid>(0))? {
+            let attachment_id = attachment_id?;
+            if !attachments_path.join(attachment_id.to_string()).is_file() {
+                missing_attachments.push(attachment_id);
+            }
+        }
+        if !missing_attachments.is_empty() {
+            return Err(invalid_archive(
+                database_path,
+                format!("The files of the attachments {:?} are missing.", missing_attachments),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Creates the error for a backup archive that cannot be restored.
+///
+/// # Parameters
+///
+/// * `path` - the path of the archive or of the file extracted from it
+/// * `reason` - the reason the archive cannot be restored
+fn invalid_archive<T: std::fmt::Display>(path: &Path, reason: T) -> HomeworkError {
+    HomeworkError::BadRequestError(InternalError::new(
+        "Invalid backup",
+        format!("The backup {} cannot be restored: {}", path.to_string_lossy(), reason),
+        "The backup is damaged or incomplete and cannot be restored.",
+    ))
+}
+
+/// Returns the path with a suffix appended to its file name.
+///
+/// # Parameters
+///
+/// * `path` - the path to append the suffix to
+/// * `suffix` - the suffix without separator
+fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,676 @@
+use std::io::{Read, Write};
+
+use super::*;
+
+/// Creates an empty temporary folder.
+fn temporary_folder() -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("homework_backup_test_{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+/// Creates a database file with all tables and an attachment.
+fn database_file(path: &Path, attachment_id: Uuid) -> Vec<u8> {
+    let connection = Connection::open(path).unwrap();
+    Configuration::default()
+        .initialise_database_schema(&connection)
+        .unwrap();
+    connection
+        .execute(
+            "INSERT INTO attachment (id, name, creation_time) VALUES (?1, 'photo.png', '2022-01-01T00:00:00Z')",
+            [attachment_id],
+        )
+        .unwrap();
+    drop(connection);
+    std::fs::read(path).unwrap()
+}
+
+/// Writes a backup archive with the specified entries.
+fn archive(path: &Path, entries: &[(&str, &[u8])]) {
+    let mut archive = zip::ZipWriter::new(File::create(path).unwrap());
+    for (name, content) in entries {
+        archive.start_file(*name, FileOptions::default()).unwrap();
+        archive.write_all(content).unwrap();
+    }
+    archive.finish().unwrap();
+}
+
+/// Creates a backup service restoring into the temporary folder, which contains a
+/// database and attachment folder with the attachment as well as a thumbnail.
+fn restore_service(folder: &Path, attachment_id: Uuid) -> Arc<Mutex<BackupService>> {
+    let configuration: Configuration = serde_json::from_value(serde_json::json!({
+        "attachment_path": folder.join("attachments"),
+        "thumbnail_path": folder.join("thumbnails"),
+        "backup_path": folder.join("backups"),
+    }))
+    .unwrap();
+    let database_path = folder.join("database.sqlite");
+    database_file(&database_path, attachment_id);
+    std::fs::create_dir_all(folder.join("attachments")).unwrap();
+    std::fs::write(folder.join("attachments").join(attachment_id.to_string()), b"current").unwrap();
+    std::fs::create_dir_all(folder.join("thumbnails")).unwrap();
+    std::fs::write(folder.join("thumbnails").join(attachment_id.to_string()), b"thumbnail")
+        .unwrap();
+    Arc::new(Mutex::new(BackupService {
+        database_path,
+        ..BackupService::new(Arc::new(configuration))
+    }))
+}
+
+/// Writes a backup archive of an unversioned database of earlier versions
+/// with the attachment.
+fn restore_archive(folder: &Path, attachment_id: Uuid) -> PathBuf {
+    let database_path = folder.join("source.sqlite");
+    let connection = Connection::open(&database_path).unwrap();
+    connection
+        .execute(
+            "CREATE TABLE attachment (id TEXT PRIMARY KEY, name TEXT NOT NULL, creation_time TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+    connection
+        .execute(
+            "INSERT INTO attachment (id, name, creation_time) VALUES (?1, 'photo.png', '2022-01-01T00:00:00Z')",
+            [attachment_id],
+        )
+        .unwrap();
+    drop(connection);
+    let archive_path = folder.join("backup.zip");
+    archive(
+        &archive_path,
+        &[
+            (ARCHIVE_DATABASE_FILE, &std::fs::read(&database_path).unwrap()),
+            (&format!("attachments/{}", attachment_id), b"restored"),
+        ],
+    );
+    archive_path
+}
+
+/// Returns the temporary files of restores left in the folder.
+fn restore_leftovers(folder: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(folder)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let file_name = path.file_name().unwrap().to_string_lossy();
+            file_name.contains(&format!(".{}", RESTORE_SUFFIX))
+                || file_name.contains(&format!(".{}", REPLACED_SUFFIX))
+        })
+        .collect()
+}
+
+#[test]
+/// Tests if the `WriteGate` rejects modifying requests and other restores while it
+/// is closed and waits for running requests before closing.
+fn test_write_gate() {
+    let gate = Arc::new(WriteGate::default());
+    let permit = WriteGate::begin_write(&gate).unwrap();
+    let running_gate = Arc::clone(&gate);
+    let running = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        drop(permit);
+        running_gate.running_writes.load(Ordering::SeqCst)
+    });
+
+    let start = Instant::now();
+    let lock = gate.close().unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(running.join().unwrap(), 0);
+    assert!(matches!(
+        WriteGate::begin_write(&gate),
+        Err(HomeworkError::ServiceUnavailableError(_))
+    ));
+    assert_eq!(gate.running_writes.load(Ordering::SeqCst), 0);
+    assert!(matches!(gate.close(), Err(HomeworkError::ServiceUnavailableError(_))));
+    // The rejected restore does not open the gate of the running one.
+    assert!(WriteGate::begin_write(&gate).is_err());
+
+    drop(lock);
+    assert!(WriteGate::begin_write(&gate).is_ok());
+}
+
+#[test]
+/// Tests if the `extract_archive` and `validate_database` functions accept a complete backup
+/// of earlier versions containing the attachments and only extract entries to the expected
+/// locations.
+fn test_extract_archive() {
+    let folder = temporary_folder();
+    let attachment_id = Uuid::new_v4();
+    let database = database_file(&folder.join("source.sqlite"), attachment_id);
+    let archive_path = folder.join("backup.zip");
+    archive(
+        &archive_path,
+        &[
+            (ARCHIVE_DATABASE_FILE, &database),
+            (&format!("attachments/{}", attachment_id), b"image"),
+            ("../outside", b"ignored"),
+            ("other/file", b"ignored"),
+        ],
+    );
+
+    let files = RestoreFiles::new(&folder.join("database.sqlite"), &folder.join("attachments"));
+    extract_archive(&archive_path, &files, None).unwrap();
+    validate_database(&files.database, &files.attachments).unwrap();
+    assert_eq!(std::fs::read(files.attachments.join(attachment_id.to_string())).unwrap(), b"image");
+    assert!(!folder.join("outside").exists());
+    assert!(!folder.join("other").exists());
+
+    // The temporary files are removed when dropped.
+    drop(files);
+    assert!(!folder.join("database.sqlite.restore").exists());
+    assert!(!folder.join("attachments.restore").exists());
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if incomplete or damaged backups are rejected.
+fn test_validate_invalid_backup() {
+    let folder = temporary_folder();
+    let attachment_id = Uuid::new_v4();
+    let database = database_file(&folder.join("source.sqlite"), attachment_id);
+    let files = RestoreFiles::new(&folder.join("database.sqlite"), &folder.join("attachments"));
+    let archive_path = folder.join("backup.zip");
+
+    // The attachment file is missing.
+    archive(&archive_path, &[(ARCHIVE_DATABASE_FILE, &database)]);
+    extract_archive(&archive_path, &files, None).unwrap();
+    assert!(matches!(
+        validate_database(&files.database, &files.attachments),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+
+    // The database is missing.
+    archive(&archive_path, &[("attachments/file", b"image")]);
+    assert!(matches!(
+        extract_archive(&archive_path, &files, None),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+
+    // The database is damaged.
+    archive(&archive_path, &[(ARCHIVE_DATABASE_FILE, &database[..1024])]);
+    extract_archive(&archive_path, &files, None).unwrap();
+    assert!(validate_database(&files.database, &files.attachments).is_err());
+
+    // The file is no archive.
+    std::fs::write(&archive_path, b"no archive").unwrap();
+    assert!(matches!(
+        extract_archive(&archive_path, &files, None),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+
+    drop(files);
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if the `restore_backup` function backs up the current state, replaces the database
+/// and attachments, removes the thumbnails and migrates the restored database.
+fn test_restore_backup() {
+    let folder = temporary_folder();
+    let current_id = Uuid::new_v4();
+    let restored_id = Uuid::new_v4();
+    let service = restore_service(&folder, current_id);
+    let archive_path = restore_archive(&folder, restored_id);
+
+    BackupService::restore_backup(&service, &archive_path, None).unwrap();
+
+    let connection = Connection::open(folder.join("database.sqlite")).unwrap();
+    assert_eq!(schema_version(&connection).unwrap(), latest_schema_version());
+    let attachment_ids: Vec<Uuid> = connection
+        .prepare("SELECT id FROM attachment")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(attachment_ids, vec![restored_id]);
+    let attachments: Vec<PathBuf> = std::fs::read_dir(folder.join("attachments"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(attachments, vec![folder.join("attachments").join(restored_id.to_string())]);
+    assert_eq!(std::fs::read(&attachments[0]).unwrap(), b"restored");
+    assert!(!folder.join("thumbnails").exists());
+    assert!(restore_leftovers(&folder).is_empty());
+    // The replaced state is backed up, so it can be restored in turn.
+    assert_eq!(service.lock().current_backups().unwrap().len(), 1);
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if the `restore_backup` function moves the previous attachments back
+/// if the database cannot be replaced.
+fn test_restore_backup_failed_swap() {
+    let folder = temporary_folder();
+    let current_id = Uuid::new_v4();
+    let service = restore_service(&folder, current_id);
+    let archive_path = restore_archive(&folder, Uuid::new_v4());
+    // A folder in place of the database cannot be replaced by the restored database.
+    let database_path = folder.join("database.sqlite");
+    std::fs::remove_file(&database_path).unwrap();
+    std::fs::create_dir_all(database_path.join("blocked")).unwrap();
+
+    assert!(BackupService::restore_backup(&service, &archive_path, None).is_err());
+
+    assert!(database_path.join("blocked").is_dir());
+    let attachments: Vec<PathBuf> = std::fs::read_dir(folder.join("attachments"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(attachments, vec![folder.join("attachments").join(current_id.to_string())]);
+    assert_eq!(std::fs::read(&attachments[0]).unwrap(), b"current");
+    assert!(folder.join("thumbnails").exists());
+    assert!(restore_leftovers(&folder).is_empty());
+    assert!(WriteGate::begin_write(&service.lock().write_gate()).is_ok());
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if the `restore_backup` function leaves the current state untouched
+/// if the database of the archive cannot be migrated.
+fn test_restore_backup_failed_migration() {
+    let folder = temporary_folder();
+    let current_id = Uuid::new_v4();
+    let service = restore_service(&folder, current_id);
+    let database_path = folder.join("database.sqlite");
+    let current_database = std::fs::read(&database_path).unwrap();
+    let source_path = folder.join("source.sqlite");
+    let connection = Connection::open(&source_path).unwrap();
+    connection
+        .execute_batch(
+            "CREATE TABLE payment (
+                id BLOB PRIMARY KEY,
+                target TEXT NOT NULL,
+                note TEXT NOT NULL,
+                paid TEXT NOT NULL,
+                involved TEXT NOT NULL,
+                payment_type TEXT NOT NULL,
+                creation_time TEXT NOT NULL
+            );
+            INSERT INTO payment VALUES (
+                x'00000000000000000000000000000001', 'Shop', '', '{}', '{}', 'invalid',
+                '2022-01-01T00:00:00Z'
+            );",
+        )
+        .unwrap();
+    drop(connection);
+    let archive_path = folder.join("backup.zip");
+    archive(&archive_path, &[(ARCHIVE_DATABASE_FILE, &std::fs::read(&source_path).unwrap())]);
+
+    assert!(BackupService::restore_backup(&service, &archive_path, None).is_err());
+
+    assert_eq!(std::fs::read(&database_path).unwrap(), current_database);
+    assert!(folder
+        .join("attachments")
+        .join(current_id.to_string())
+        .is_file());
+    assert!(folder.join("thumbnails").exists());
+    assert!(service.lock().current_backups().unwrap().is_empty());
+    assert!(restore_leftovers(&folder).is_empty());
+    assert!(WriteGate::begin_write(&service.lock().write_gate()).is_ok());
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if a modifying request running during a restore can schedule a backup,
+/// so the restore waiting for it finishes.
+fn test_restore_backup_during_write() {
+    let folder = temporary_folder();
+    let service = restore_service(&folder, Uuid::new_v4());
+    let archive_path = restore_archive(&folder, Uuid::new_v4());
+    let permit = WriteGate::begin_write(&service.lock().write_gate()).unwrap();
+    let request_service = Arc::clone(&service);
+    let request = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        request_service.lock().request_timed_backup();
+        drop(permit);
+    });
+
+    let start = Instant::now();
+    BackupService::restore_backup(&service, &archive_path, None).unwrap();
+    assert!(start.elapsed() < RESTORE_WRITE_TIMEOUT);
+    request.join().unwrap();
+    assert!(service.lock().next_timed_backup.is_some());
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if a restore with a specified secret only decrypts the archive with it and still
+/// encrypts the backup of the replaced state with the configured secret.
+fn test_restore_backup_secret() {
+    let folder = temporary_folder();
+    let service = restore_service(&folder, Uuid::new_v4());
+    let configuration: Configuration = serde_json::from_value(serde_json::json!({
+        "attachment_path": folder.join("attachments"),
+        "thumbnail_path": folder.join("thumbnails"),
+        "backup_path": folder.join("backups"),
+        "backup_passphrase": "configured",
+    }))
+    .unwrap();
+    service.lock().configuration = Arc::new(configuration);
+    let source_folder = folder.join("source");
+    std::fs::create_dir_all(source_folder.join("attachments")).unwrap();
+    let restored_id = Uuid::new_v4();
+    database_file(&source_folder.join("database.sqlite"), restored_id);
+    std::fs::write(
+        source_folder
+            .join("attachments")
+            .join(restored_id.to_string()),
+        b"image",
+    )
+    .unwrap();
+    let archive_path = source_folder.join("1.zip");
+    write_backup_archive(
+        &source_folder.join("database.sqlite"),
+        &source_folder.join("attachments"),
+        &archive_path,
+        Some(&current_encryption_key(&source_folder, b"key file").unwrap()),
+    )
+    .unwrap();
+    assert!(service.lock().verify_backup(&archive_path, None).is_err());
+    service
+        .lock()
+        .verify_backup(&archive_path, Some(b"key file"))
+        .unwrap();
+
+    BackupService::restore_backup(&service, &archive_path, Some(b"key file")).unwrap();
+
+    assert!(folder
+        .join("attachments")
+        .join(restored_id.to_string())
+        .is_file());
+    let backups = service.lock().current_backups().unwrap();
+    assert_eq!(backups.len(), 1);
+    let files = RestoreFiles::new(&folder.join("restored.sqlite"), &folder.join("restored"));
+    assert!(extract_archive(&backups[0], &files, Some(b"key file")).is_err());
+    extract_archive(&backups[0], &files, Some(b"configured")).unwrap();
+    // The key parameters of the backup folder still belong to the configured secret.
+    assert_eq!(
+        read_encryption_parameters(&mut ZipArchive::new(File::open(&backups[0]).unwrap()).unwrap())
+            .unwrap()
+            .as_ref(),
+        Some(
+            current_encryption_key(&folder.join("backups"), b"configured")
+                .unwrap()
+                .parameters()
+        )
+    );
+
+    drop(files);
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if the `write_backup_archive` function creates archives with a consistent
+/// database while the database is modified concurrently.
+fn test_write_backup_archive_concurrent_writes() {
+    let folder = temporary_folder();
+    let database_path = folder.join("database.sqlite");
+    let attachments_path = folder.join("attachments");
+    let attachment_id = Uuid::new_v4();
+    database_file(&database_path, attachment_id);
+    std::fs::create_dir_all(&attachments_path).unwrap();
+    let attachment_path = attachments_path.join(attachment_id.to_string());
+    std::fs::write(&attachment_path, b"image").unwrap();
+
+    let writing = Arc::new(AtomicBool::new(true));
+    let writer = {
+        let writing = Arc::clone(&writing);
+        let database_path = database_path.clone();
+        std::thread::spawn(move || {
+            let connection = Connection::open(database_path).unwrap();
+            connection.busy_timeout(Duration::from_secs(10)).unwrap();
+            let mut writes = 0;
+            while writing.load(Ordering::SeqCst) {
+                connection
+                    .execute(
+                        "INSERT INTO shopping_list (id, title, creation_time) VALUES (?1, ?2, '2022-01-01T00:00:00Z')",
+                        rusqlite::params![Uuid::new_v4(), "x".repeat(1000)],
+                    )
+                    .unwrap();
+                writes += 1;
+            }
+            writes
+        })
+    };
+
+    for backup in 0..5 {
+        let archive_path = folder.join(format!("{}.zip", backup));
+        write_backup_archive(&database_path, &attachments_path, &archive_path, None).unwrap();
+        assert!(!suffixed_path(&archive_path, DATABASE_COPY_SUFFIX).exists());
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut database = Vec::new();
+        archive
+            .by_name(ARCHIVE_DATABASE_FILE)
+            .unwrap()
+            .read_to_end(&mut database)
+            .unwrap();
+        let restored_path = folder.join(format!("{}.sqlite", backup));
+        std::fs::write(&restored_path, database).unwrap();
+        let restored = Connection::open(&restored_path).unwrap();
+        let integrity: String = restored
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(integrity, "ok");
+        validate_database(&restored_path, &attachments_path).unwrap();
+        assert_eq!(
+            read_manifest(&mut archive).unwrap().unwrap().attachments,
+            BTreeMap::from([(attachment_id.to_string(), file_hash(&attachment_path).unwrap())])
+        );
+    }
+    writing.store(false, Ordering::SeqCst);
+    assert!(writer.join().unwrap() > 0);
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if backups store the content of attachments once and restore it from the blobs.
+fn test_write_backup_archive_incremental() {
+    let folder = temporary_folder();
+    let database_path = folder.join("database.sqlite");
+    let attachments_path = folder.join("attachments");
+    let backup_folder = folder.join("backups");
+    std::fs::create_dir_all(&backup_folder).unwrap();
+    let attachment_id = Uuid::new_v4();
+    database_file(&database_path, attachment_id);
+    std::fs::create_dir_all(&attachments_path).unwrap();
+    std::fs::write(attachments_path.join(attachment_id.to_string()), b"receipt").unwrap();
+
+    for backup in ["1.zip", "2.zip"] {
+        write_backup_archive(&database_path, &attachments_path, &backup_folder.join(backup), None)
+            .unwrap();
+    }
+    let blobs: Vec<PathBuf> = std::fs::read_dir(backup_folder.join(BLOB_FOLDER))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(blobs.len(), 1);
+    assert_eq!(std::fs::read(&blobs[0]).unwrap(), b"receipt");
+
+    let files = RestoreFiles::new(&folder.join("restored.sqlite"), &folder.join("restored"));
+    extract_archive(&backup_folder.join("1.zip"), &files, None).unwrap();
+    validate_database(&files.database, &files.attachments).unwrap();
+    assert_eq!(
+        std::fs::read(files.attachments.join(attachment_id.to_string())).unwrap(),
+        b"receipt"
+    );
+
+    // Damaged and missing blobs are rejected.
+    std::fs::write(&blobs[0], b"damaged").unwrap();
+    assert!(matches!(
+        extract_archive(&backup_folder.join("1.zip"), &files, None),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    std::fs::remove_file(&blobs[0]).unwrap();
+    assert!(matches!(
+        extract_archive(&backup_folder.join("1.zip"), &files, None),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+
+    drop(files);
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if encrypted backups contain the key parameters, do not contain the plain content
+/// and are only restored with the right secret.
+fn test_write_backup_archive_encrypted() {
+    let folder = temporary_folder();
+    let database_path = folder.join("database.sqlite");
+    let attachments_path = folder.join("attachments");
+    let backup_folder = folder.join("backups");
+    std::fs::create_dir_all(&backup_folder).unwrap();
+    let attachment_id = Uuid::new_v4();
+    database_file(&database_path, attachment_id);
+    std::fs::create_dir_all(&attachments_path).unwrap();
+    std::fs::write(attachments_path.join(attachment_id.to_string()), b"receipt").unwrap();
+    let key = current_encryption_key(&backup_folder, b"passphrase").unwrap();
+    let archive_path = backup_folder.join("1.zip");
+    write_backup_archive(&database_path, &attachments_path, &archive_path, Some(&key)).unwrap();
+
+    let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+    assert_eq!(read_encryption_parameters(&mut archive).unwrap().as_ref(), Some(key.parameters()));
+    let mut database = Vec::new();
+    archive
+        .by_name(ARCHIVE_DATABASE_FILE)
+        .unwrap()
+        .read_to_end(&mut database)
+        .unwrap();
+    assert!(!database.starts_with(b"SQLite format 3"));
+    let blobs: Vec<PathBuf> = std::fs::read_dir(backup_folder.join(BLOB_FOLDER))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(blobs.len(), 1);
+    assert_ne!(std::fs::read(&blobs[0]).unwrap(), b"receipt");
+
+    let files = RestoreFiles::new(&folder.join("restored.sqlite"), &folder.join("restored"));
+    extract_archive(&archive_path, &files, Some(b"passphrase")).unwrap();
+    validate_database(&files.database, &files.attachments).unwrap();
+    assert_eq!(
+        std::fs::read(files.attachments.join(attachment_id.to_string())).unwrap(),
+        b"receipt"
+    );
+
+    // A missing or wrong secret is rejected.
+    for secret in [None, Some(b"other".as_slice())] {
+        assert!(matches!(
+            extract_archive(&archive_path, &files, secret),
+            Err(HomeworkError::BadRequestError(_))
+        ));
+    }
+
+    drop(files);
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if the `current_encryption_key` function reuses the stored key parameters
+/// and derives a new key if the secret changed.
+fn test_current_encryption_key() {
+    let folder = temporary_folder();
+    let key = current_encryption_key(&folder, b"passphrase").unwrap();
+    assert_eq!(
+        current_encryption_key(&folder, b"passphrase")
+            .unwrap()
+            .parameters(),
+        key.parameters()
+    );
+
+    let rotated = current_encryption_key(&folder, b"other").unwrap();
+    assert_ne!(rotated.parameters(), key.parameters());
+    assert_eq!(
+        current_encryption_key(&folder, b"other")
+            .unwrap()
+            .parameters(),
+        rotated.parameters()
+    );
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if the `prune_backups` function removes the oldest archives and the blobs
+/// only they referenced.
+fn test_prune_backups() {
+    let folder = temporary_folder();
+    let database_path = folder.join("database.sqlite");
+    let attachments_path = folder.join("attachments");
+    let backup_folder = folder.join("backups");
+    std::fs::create_dir_all(&backup_folder).unwrap();
+    std::fs::create_dir_all(&attachments_path).unwrap();
+    database_file(&database_path, Uuid::new_v4());
+    for (backup, attachments) in [
+        ("1.zip", vec![("a", "first")]),
+        ("2.zip", vec![("a", "first"), ("b", "second")]),
+        ("3.zip", vec![("b", "second"), ("c", "third")]),
+    ] {
+        std::fs::remove_dir_all(&attachments_path).unwrap();
+        std::fs::create_dir_all(&attachments_path).unwrap();
+        for (name, content) in attachments {
+            std::fs::write(attachments_path.join(name), content).unwrap();
+        }
+        write_backup_archive(&database_path, &attachments_path, &backup_folder.join(backup), None)
+            .unwrap();
+    }
+    // Backups of earlier versions do not reference any blobs.
+    archive(&backup_folder.join("0.zip"), &[("attachments/a", b"first")]);
+    assert_eq!(
+        std::fs::read_dir(backup_folder.join(BLOB_FOLDER))
+            .unwrap()
+            .count(),
+        3
+    );
+
+    prune_backups(&backup_folder, 2).unwrap();
+    let mut backups = backup_archives(&backup_folder).unwrap();
+    backups.sort();
+    assert_eq!(backups, vec![backup_folder.join("2.zip"), backup_folder.join("3.zip")]);
+    assert_eq!(
+        std::fs::read_dir(backup_folder.join(BLOB_FOLDER))
+            .unwrap()
+            .count(),
+        3
+    );
+
+    prune_backups(&backup_folder, 1).unwrap();
+    assert_eq!(backup_archives(&backup_folder).unwrap(), vec![backup_folder.join("3.zip")]);
+    let mut blobs: Vec<Vec<u8>> = std::fs::read_dir(backup_folder.join(BLOB_FOLDER))
+        .unwrap()
+        .map(|entry| std::fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    blobs.sort();
+    assert_eq!(blobs, vec![b"second".to_vec(), b"third".to_vec()]);
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+/// Tests if the `suffixed_path` function appends the suffix to the file name.
+fn test_suffixed_path() {
+    assert_eq!(
+        suffixed_path(Path::new("application/database.sqlite"), RESTORE_SUFFIX),
+        PathBuf::from("application/database.sqlite.restore")
+    );
+    assert_eq!(
+        suffixed_path(Path::new("attachments"), REPLACED_SUFFIX),
+        PathBuf::from("attachments.replaced")
+    );
+}
+
+#[test]
+/// Tests if concurrent restores and verifications use different temporary files.
+fn test_restore_files_unique() {
+    let database = Path::new("application/database.sqlite");
+    let attachments = Path::new("attachments");
+    let first = RestoreFiles::new(database, attachments);
+    let second = RestoreFiles::new(database, attachments);
+
+    assert_ne!(first.database, second.database);
+    assert_ne!(first.encrypted_database, second.encrypted_database);
+    assert_ne!(first.attachments, second.attachments);
+    assert_ne!(first.replaced_attachments, second.replaced_attachments);
+}
@@ -0,0 +1,325 @@
+//! The `bank_import_service` module parses bank statements in CSV format into
+//! [`Payment`]s and detects payments that have likely been entered before.
+
+use std::{collections::HashMap, str::FromStr};
+
+use bigdecimal::{BigDecimal, Signed};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        config::Configuration,
+        error::{HomeworkError, InternalError},
+    },
+    entity::payment::{Payment, PaymentType},
+};
+
+use super::occurrence_service::occurrences;
+
+/// The date format used if none is specified.
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+/// The column delimiter used if none is specified.
+const DEFAULT_DELIMITER: char = ',';
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The mapping of the columns of a bank statement to the fields of a payment.
+pub struct BankStatementMapping {
+    /// The header of the column containing the booking date.
+    date_column: String,
+    /// The header of the column containing the amount.
+    amount_column: String,
+    /// The header of the column containing the counterparty, which becomes the target
+    /// of the payment.
+    counterparty_column: String,
+    /// The header of the column containing the memo, which becomes the note of the payment.
+    memo_column: Option<String>,
+    /// The `chrono` format of the booking date. Defaults to `%Y-%m-%d`.
+    date_format: Option<String>,
+    /// The column delimiter. Defaults to `,`.
+    delimiter: Option<char>,
+    /// If amounts use a decimal comma like `1.234,56`. Defaults to `false`.
+    decimal_comma: Option<bool>,
+    /// The account holder who paid and is involved with the imported payments.
+    person: String,
+    /// The currency of the bank account. Defaults to the base currency.
+    currency: Option<String>,
+}
+
+impl BankStatementMapping {
+    /// Returns the account holder.
+    pub fn person(&self) -> &str {
+        &self.person
+    }
+
+    /// Returns the currency of the bank account if specified.
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A single parsed row of a bank statement.
+pub struct BankStatementRow {
+    /// The line of the record in the bank statement excluding the header.
+    line: usize,
+    /// The booking date.
+    date: DateTime<Utc>,
+    /// The booked amount. Debits are usually negative.
+    amount: BigDecimal,
+    /// The counterparty of the booking.
+    target: String,
+    /// The memo of the booking.
+    memo: String,
+    /// If the booking is a credit like a refund or salary, which is never imported.
+    credit: bool,
+    /// If the row likely duplicates an existing payment or an earlier row of the statement.
+    duplicate: bool,
+}
+
+impl BankStatementRow {
+    /// Returns `true` if the booking is a credit.
+    pub fn credit(&self) -> bool {
+        self.credit
+    }
+
+    /// Returns `true` if the row likely duplicates an existing payment or an earlier row.
+    pub fn duplicate(&self) -> bool {
+        self.duplicate
+    }
+
+    /// Returns `true` if the specified payment occurs on the same day as the booking,
+    /// pays the debited amount in the same currency and has the same target ignoring case.
+    ///
+    /// # Parameters
+    ///
+    /// * `payment` - the payment to compare to
+    /// * `currency` - the currency of the bank account
+    fn is_duplicate_of(&self, payment: &Payment, currency: &str) -> bool {
+        let paid: BigDecimal = payment.paid().values().sum();
+        payment.currency() == currency
+            && paid == -&self.amount
+            && payment.target().trim().to_lowercase() == self.target.to_lowercase()
+            && !occurrences(
+                payment.payment_type(),
+                self.date,
+                self.date + Duration::days(1) - Duration::nanoseconds(1),
+            )
+            .is_empty()
+    }
+
+    /// Returns `true` if the other row books the same amount to the same counterparty
+    /// on the same day.
+    ///
+    /// # Parameters
+    ///
+    /// * `other` - the row to compare to
+    fn is_duplicate_of_row(&self, other: &BankStatementRow) -> bool {
+        self.date == other.date
+            && self.amount == other.amount
+            && self.target.to_lowercase() == other.target.to_lowercase()
+    }
+
+    /// Inserts the debit as a new one-off payment into the database.
+    /// The debited amount is paid by and involves the account holder alone.
+    /// Credits are rejected as they are not expenses of the account holder.
+    /// Returns the ID of the created payment.
+    ///
+    /// # Parameters
+    ///
+    /// * `person` - the account holder
+    /// * `currency` - the currency of the bank account
+    /// * `connection` - the database connection
+    pub fn insert_into_database(
+        &self,
+        person: &str,
+        currency: &str,
+        connection: &Connection,
+    ) -> Result<Uuid, HomeworkError> {
+        if self.credit {
+            return Err(invalid_statement(
+                self.line,
+                "credits cannot be imported as payments".to_string(),
+            ));
+        }
+        let id = Configuration::generate_uuid();
+        let amounts: HashMap<String, BigDecimal> =
+            HashMap::from([(person.to_string(), -&self.amount)]);
+        Payment::insert_into_database_new_entry(id, &self.target, currency, connection)?;
+        Payment::update_in_database_string_column(id, "note", &self.memo, connection)?;
+        Payment::update_in_database_paid(id, &amounts, connection)?;
+        Payment::update_in_database_involved(id, &amounts, connection)?;
+        Payment::update_in_database_payment_type(
+            id,
+            &PaymentType::OneOff { start: self.date },
+            connection,
+        )?;
+        Ok(id)
+    }
+}
+
+/// Parses a bank statement in CSV format with a header row according to the
+/// specified column mapping. Empty rows are skipped.
+///
+/// # Parameters
+///
+/// * `content` - the content of the CSV file
+/// * `mapping` - the mapping of the columns
+pub fn parse_bank_statement(
+    content: &[u8],
+    mapping: &BankStatementMapping,
+) -> Result<Vec<BankStatementRow>, HomeworkError> {
+    let delimiter = mapping.delimiter.unwrap_or(DEFAULT_DELIMITER);
+    if !delimiter.is_ascii() {
+        return Err(invalid_mapping(format!(
+            "The delimiter \"{}\" is not an ASCII character.",
+            delimiter
+        )));
+    }
+    let date_format = mapping
+        .date_format
+        .as_deref()
+        .unwrap_or(DEFAULT_DATE_FORMAT);
+    let decimal_comma = mapping.decimal_comma.unwrap_or(false);
+    if mapping.person.trim().is_empty() {
+        return Err(invalid_mapping("The account holder must not be empty."));
+    }
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(content);
+    let headers: Vec<String> = reader
+        .byte_headers()
+        .map_err(|err| invalid_statement(0, err.to_string()))?
+        .iter()
+        .map(|header| String::from_utf8_lossy(header).to_string())
+        .collect();
+    let date_index = column_index(&headers, &mapping.date_column)?;
+    let amount_index = column_index(&headers, &mapping.amount_column)?;
+    let counterparty_index = column_index(&headers, &mapping.counterparty_column)?;
+    let memo_index = mapping
+        .memo_column
+        .as_deref()
+        .map(|column| column_index(&headers, column))
+        .transpose()?;
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.byte_records().enumerate() {
+        let line = index + 1;
+        let record = record.map_err(|err| invalid_statement(line, err.to_string()))?;
+        let fields: Vec<String> = record
+            .iter()
+            .map(|field| String::from_utf8_lossy(field).to_string())
+            .collect();
+        if fields.iter().all(|field| field.is_empty()) {
+            continue;
+        }
+        let field = |index: usize| fields.get(index).map(String::as_str).unwrap_or("");
+        let date = NaiveDate::parse_from_str(field(date_index), date_format).map_err(|err| {
+            invalid_statement(line, format!("date \"{}\": {}", field(date_index), err))
+        })?;
+        let amount = parse_amount(field(amount_index), decimal_comma).map_err(|err| {
+            invalid_statement(line, format!("amount \"{}\": {}", field(amount_index), err))
+        })?;
+        rows.push(BankStatementRow {
+            line,
+            date: Utc.from_utc_datetime(
+                &date
+                    .and_hms_opt(0, 0, 0)
+                    .expect("Midnight is a valid time."),
+            ),
+            credit: amount.is_positive(),
+            amount,
+            target: field(counterparty_index).to_string(),
+            memo: memo_index.map(field).unwrap_or("").to_string(),
+            duplicate: false,
+        });
+    }
+    Ok(rows)
+}
+
+/// Marks all rows that likely duplicate one of the specified payments or an earlier
+/// row of the same bank statement.
+///
+/// # Parameters
+///
+/// * `rows` - the parsed rows of the bank statement
+/// * `payments` - the existing payments
+/// * `currency` - the currency of the bank account
+pub fn mark_duplicates(rows: &mut [BankStatementRow], payments: &[Payment], currency: &str) {
+    for index in 0..rows.len() {
+        let (earlier, rest) = rows.split_at_mut(index);
+        let row = &mut rest[0];
+        row.duplicate = payments
+            .iter()
+            .any(|payment| row.is_duplicate_of(payment, currency))
+            || earlier.iter().any(|other| row.is_duplicate_of_row(other));
+    }
+}
+
+/// Parses an amount ignoring thousands separators and whitespace.
+///
+/// # Parameters
+///
+/// * `amount` - the amount to parse
+/// * `decimal_comma` - if a comma separates the decimal places
+fn parse_amount(
+    amount: &str,
+    decimal_comma: bool,
+) -> Result<BigDecimal, bigdecimal::ParseBigDecimalError> {
+    let (thousands_separator, decimal_separator) = if decimal_comma {
+        ('.', ',')
+    } else {
+        (',', '.')
+    };
+    let normalised: String = amount
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != thousands_separator && *c != '\'')
+        .map(|c| if c == decimal_separator { '.' } else { c })
+        .collect();
+    BigDecimal::from_str(&normalised)
+}
+
+/// Returns the index of the column with the specified header ignoring case.
+///
+/// # Parameters
+///
+/// * `headers` - the headers of the bank statement
+/// * `column` - the header of the column
+fn column_index(headers: &[String], column: &str) -> Result<usize, HomeworkError> {
+    headers
+        .iter()
+        .position(|header| header.eq_ignore_ascii_case(column.trim()))
+        .ok_or_else(|| {
+            invalid_mapping(format!(
+                "The column \"{}\" does not exist. Available columns are: {:?}",
+                column, headers
+            ))
+        })
+}
+
+/// Creates an error for an invalid column mapping.
+fn invalid_mapping<T: ToString>(message: T) -> HomeworkError {
+    HomeworkError::BadRequestError(InternalError::new(
+        "Invalid bank statement mapping",
+        message.to_string(),
+        message.to_string(),
+    ))
+}
+
+/// Creates an error for an invalid record of a bank statement.
+fn invalid_statement(line: usize, message: String) -> HomeworkError {
+    HomeworkError::BadRequestError(InternalError::new(
+        "Invalid bank statement record",
+        format!("Record {} of the bank statement is invalid: {}", line, message),
+        format!("Record {} is invalid: {}", line, message),
+    ))
+}
+
+#[cfg(test)]
+mod test;
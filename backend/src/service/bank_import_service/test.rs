@@ -0,0 +1,126 @@
+use crate::entity::{payment::test_builder::PaymentBuilder, person::Person};
+
+use super::*;
+
+/// Creates a column mapping with the specified date format and decimal comma setting.
+fn mapping(
+    delimiter: char,
+    date_format: &str,
+    decimal_comma: bool,
+    memo_column: Option<&str>,
+) -> BankStatementMapping {
+    serde_json::from_value(serde_json::json!({
+        "dateColumn": "Booking date",
+        "amountColumn": "Amount",
+        "counterpartyColumn": "Counterparty",
+        "memoColumn": memo_column,
+        "dateFormat": date_format,
+        "delimiter": delimiter,
+        "decimalComma": decimal_comma,
+        "person": "Anna"
+    }))
+    .unwrap()
+}
+
+#[test]
+/// Tests if the `parse_bank_statement` function maps columns and parses dates and amounts.
+fn test_parse_bank_statement() {
+    let content = "Booking date;Counterparty;Memo;Amount\n\
+                   03.01.2022;Bakery;Bread;-1.234,50\n\
+                   ;;;\n\
+                   04.01.2022;Employer;Salary;2.000,00\n";
+    let rows =
+        parse_bank_statement(content.as_bytes(), &mapping(';', "%d.%m.%Y", true, Some("memo")))
+            .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].line, 1);
+    assert_eq!(rows[0].date, "2022-01-03T00:00:00Z".parse::<DateTime<Utc>>().unwrap());
+    assert_eq!(rows[0].amount, BigDecimal::from_str("-1234.50").unwrap());
+    assert_eq!(rows[0].target, "Bakery");
+    assert_eq!(rows[0].memo, "Bread");
+    assert!(!rows[0].credit);
+    assert_eq!(rows[1].line, 3);
+    assert_eq!(rows[1].amount, BigDecimal::from_str("2000.00").unwrap());
+    assert!(rows[1].credit);
+}
+
+#[test]
+/// Tests if the `parse_bank_statement` function rejects missing columns and invalid records.
+fn test_parse_bank_statement_invalid() {
+    let content = "Booking date,Counterparty,Amount\n2022-01-03,Bakery,abc\n";
+    assert!(parse_bank_statement(
+        content.as_bytes(),
+        &mapping(',', "%Y-%m-%d", false, Some("Memo"))
+    )
+    .is_err());
+    assert!(
+        parse_bank_statement(content.as_bytes(), &mapping(',', "%Y-%m-%d", false, None)).is_err()
+    );
+    let content = "Booking date,Counterparty,Amount\n03/01/2022,Bakery,1.00\n";
+    assert!(
+        parse_bank_statement(content.as_bytes(), &mapping(',', "%Y-%m-%d", false, None)).is_err()
+    );
+}
+
+#[test]
+/// Tests if the `mark_duplicates` function compares date, debited amount, currency and target
+/// with existing payments and earlier rows.
+fn test_mark_duplicates() {
+    let content = "Booking date,Counterparty,Amount\n\
+                   2022-01-03,bakery,-12.50\n\
+                   2022-01-04,Bakery,-12.50\n\
+                   2022-01-03,Bakery,-12.00\n\
+                   2022-01-03,Butcher,-12.50\n\
+                   2022-01-03,Bakery,12.50\n\
+                   2022-01-03,BUTCHER,-12.50\n";
+    let mut rows =
+        parse_bank_statement(content.as_bytes(), &mapping(',', "%Y-%m-%d", false, None)).unwrap();
+    let payment = PaymentBuilder::new(
+        serde_json::json!({"Anna": "12.50"}),
+        serde_json::json!({"Anna": "12.50"}),
+    )
+    .target(" Bakery ")
+    .one_off("2022-01-03T18:30:00Z")
+    .build();
+    mark_duplicates(&mut rows, std::slice::from_ref(&payment), "EUR");
+    assert_eq!(
+        rows.iter()
+            .map(BankStatementRow::duplicate)
+            .collect::<Vec<_>>(),
+        vec![true, false, false, false, false, true]
+    );
+    mark_duplicates(&mut rows, &[payment], "USD");
+    assert_eq!(
+        rows.iter()
+            .map(BankStatementRow::duplicate)
+            .collect::<Vec<_>>(),
+        vec![false, false, false, false, false, true]
+    );
+}
+
+#[test]
+/// Tests if the `insert_into_database` function books debits as positive payments
+/// and rejects credits.
+fn test_insert_into_database() {
+    let connection = Configuration::in_memory_database_connection();
+    Person::new(Configuration::generate_uuid(), "Anna")
+        .insert_into_database(&connection)
+        .unwrap();
+    let content = "Booking date,Counterparty,Amount\n\
+                   2022-01-03,Bakery,-12.50\n\
+                   2022-01-04,Employer,2000.00\n";
+    let rows =
+        parse_bank_statement(content.as_bytes(), &mapping(',', "%Y-%m-%d", false, None)).unwrap();
+    let id = rows[0]
+        .insert_into_database("Anna", "EUR", &connection)
+        .unwrap();
+    let payment = Payment::select_from_database_by_id(id, &connection).unwrap();
+    assert_eq!(payment.paid().values().sum::<BigDecimal>(), BigDecimal::from_str("12.50").unwrap());
+    assert_eq!(
+        payment.involved().values().sum::<BigDecimal>(),
+        BigDecimal::from_str("12.50").unwrap()
+    );
+    assert!(rows[1]
+        .insert_into_database("Anna", "EUR", &connection)
+        .is_err());
+}
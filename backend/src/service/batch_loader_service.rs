@@ -0,0 +1,44 @@
+//! The `batch_loader_service` module loads the relations of many entities with a
+//! single query per relation instead of one query per entity.
+
+use std::collections::HashMap;
+
+use rusqlite::{params_from_iter, Connection, Row};
+use uuid::Uuid;
+
+/// The placeholder of a query that is replaced with the parameters of the IDs.
+const PLACEHOLDER_IDS: &str = "{ids}";
+/// The maximum number of IDs bound to a single query, which stays well below
+/// the maximum number of parameters supported by SQLite.
+pub const MAXIMUM_IDS_PER_QUERY: usize = 500;
+
+/// Runs the query for the IDs and groups the resulting entries by the ID of the entity
+/// they belong to. The order of the entries of an entity is the order of the query.
+///
+/// # Parameters
+///
+/// * `query` - the query containing `{ids}` in place of the parameter list of an `IN` clause
+/// * `ids` - the IDs of the entities to load the entries for
+/// * `connection` - the database connection
+/// * `map` - the conversion of a row into the ID of the entity and the entry
+pub fn load_grouped_by_ids<T, F>(
+    query: &str,
+    ids: &[Uuid],
+    connection: &Connection,
+    mut map: F,
+) -> Result<HashMap<Uuid, Vec<T>>, rusqlite::Error>
+where
+    F: FnMut(&Row<'_>) -> Result<(Uuid, T), rusqlite::Error>,
+{
+    let mut grouped: HashMap<Uuid, Vec<T>> = HashMap::new();
+    for chunk in ids.chunks(MAXIMUM_IDS_PER_QUERY) {
+        let parameters = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = connection.prepare(&query.replace(PLACEHOLDER_IDS, &parameters))?;
+        let rows = stmt.query_map(params_from_iter(chunk.iter()), |row| map(row))?;
+        for row in rows {
+            let (id, entry) = row?;
+            grouped.entry(id).or_default().push(entry);
+        }
+    }
+    Ok(grouped)
+}
@@ -0,0 +1,95 @@
+//! The `budget_service` module compares the actual spending of [`Payment`]s
+//! against [`Budget`]s.
+
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    application::error::HomeworkError,
+    entity::{budget::Budget, payment::Payment},
+};
+
+use super::{currency_service::CurrencyConverter, occurrence_service::occurrences};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The spending status of a [`Budget`] within a single period.
+pub struct BudgetStatus {
+    /// The budget.
+    budget: Budget,
+    /// The currency of all amounts.
+    currency: String,
+    /// The inclusive start of the period.
+    from: DateTime<Utc>,
+    /// The inclusive end of the period.
+    to: DateTime<Utc>,
+    /// The amount spent within the period.
+    spent: BigDecimal,
+    /// The amount that can still be spent within the period.
+    remaining: BigDecimal,
+    /// The amount spent beyond the limit.
+    overspend: BigDecimal,
+    /// If more than the limit has been spent.
+    over_budget: bool,
+}
+
+impl BudgetStatus {
+    /// Computes the status of the budget for the period containing the specified date.
+    /// Recurring payments are expanded into their single occurrences, which are converted into
+    /// the base currency at the date of the respective occurrence.
+    /// If the budget is restricted to a person, only the share the person is involved with
+    /// counts, otherwise the total amount paid. Transfers settling balances are excluded.
+    ///
+    /// # Parameters
+    ///
+    /// * `budget` - the budget
+    /// * `payments` - all payments
+    /// * `date` - the date within the period to compute the status for
+    /// * `converter` - the converter used to convert all amounts into the base currency
+    pub fn from_payments<'a, T: IntoIterator<Item = &'a Payment>>(
+        budget: Budget,
+        payments: T,
+        date: DateTime<Utc>,
+        converter: &CurrencyConverter,
+    ) -> Result<Self, HomeworkError> {
+        let (from, to) = budget.period().window(date);
+        let mut spent = BigDecimal::zero();
+        for payment in payments
+            .into_iter()
+            .filter(|payment| !payment.is_transfer())
+            .filter(|payment| payment.tags().iter().any(|tag| tag == budget.tag()))
+        {
+            let amount: BigDecimal = match budget.person() {
+                Some(person) => payment
+                    .involved()
+                    .get(person)
+                    .cloned()
+                    .unwrap_or_else(BigDecimal::zero),
+                None => payment.paid().values().sum(),
+            };
+            for occurrence in occurrences(payment.payment_type(), from, to) {
+                spent += converter.convert(&amount, payment.currency(), occurrence)?;
+            }
+        }
+        let difference = budget.limit() - &spent;
+        let (remaining, overspend) = if difference < BigDecimal::zero() {
+            (BigDecimal::zero(), -difference)
+        } else {
+            (difference, BigDecimal::zero())
+        };
+        Ok(BudgetStatus {
+            currency: converter.base_currency().to_string(),
+            from,
+            to,
+            over_budget: overspend > BigDecimal::zero(),
+            budget,
+            spent,
+            remaining,
+            overspend,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,103 @@
+use crate::entity::{
+    budget::BudgetPeriod,
+    payment::test_builder::{date, decimal, PaymentBuilder},
+};
+
+use super::*;
+
+/// Creates a budget for the specified tag, period, limit and person.
+fn budget(tag: &str, period: &str, limit: &str, person: Option<&str>) -> Budget {
+    serde_json::from_value(serde_json::json!({
+        "tag": tag,
+        "period": period,
+        "limit": limit,
+        "person": person
+    }))
+    .unwrap()
+}
+
+/// Creates the payments used by the status tests.
+fn payments() -> Vec<Payment> {
+    vec![
+        PaymentBuilder::new(
+            serde_json::json!({"Anna": "20.00"}),
+            serde_json::json!({"Anna": "10.00", "Ben": "10.00"}),
+        )
+        .payment_type(serde_json::json!({"Weekly": {
+            "distance": 1,
+            "start": "2022-01-03T12:00:00Z",
+            "end": "2022-12-31T00:00:00Z"
+        }}))
+        .tags(&["Food"])
+        .build(),
+        PaymentBuilder::new(
+            serde_json::json!({"Ben": "50.00"}),
+            serde_json::json!({"Ben": "50.00"}),
+        )
+        .one_off("2022-02-15T00:00:00Z")
+        .tags(&["Food", "Party"])
+        .build(),
+        PaymentBuilder::new(
+            serde_json::json!({"Anna": "500.00"}),
+            serde_json::json!({"Anna": "500.00"}),
+        )
+        .one_off("2022-02-16T00:00:00Z")
+        .tags(&["Rent"])
+        .build(),
+    ]
+}
+
+#[test]
+/// Tests if the `window` function returns the week, month and year containing the date.
+fn test_period_window() {
+    let day = date("2022-12-29T15:00:00Z");
+    assert_eq!(
+        BudgetPeriod::Weekly.window(day),
+        (date("2022-12-26T00:00:00Z"), date("2023-01-01T23:59:59.999999999Z"))
+    );
+    assert_eq!(
+        BudgetPeriod::Monthly.window(day),
+        (date("2022-12-01T00:00:00Z"), date("2022-12-31T23:59:59.999999999Z"))
+    );
+    assert_eq!(
+        BudgetPeriod::Yearly.window(day),
+        (date("2022-01-01T00:00:00Z"), date("2022-12-31T23:59:59.999999999Z"))
+    );
+}
+
+#[test]
+/// Tests if the `from_payments` function sums the expanded payments of the tag and reports
+/// the overspend.
+fn test_from_payments_over_budget() {
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    let status = BudgetStatus::from_payments(
+        budget("Food", "Monthly", "100", None),
+        &payments(),
+        date("2022-02-10T00:00:00Z"),
+        &converter,
+    )
+    .unwrap();
+    assert_eq!(status.from, date("2022-02-01T00:00:00Z"));
+    // Four weekly occurrences and the one-off payment.
+    assert_eq!(status.spent, decimal("130.00"));
+    assert_eq!(status.remaining, BigDecimal::zero());
+    assert_eq!(status.overspend, decimal("30.00"));
+    assert!(status.over_budget);
+}
+
+#[test]
+/// Tests if the `from_payments` function only counts the involved share of a person.
+fn test_from_payments_person() {
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    let status = BudgetStatus::from_payments(
+        budget("Food", "Monthly", "100", Some("Anna")),
+        &payments(),
+        date("2022-02-10T00:00:00Z"),
+        &converter,
+    )
+    .unwrap();
+    assert_eq!(status.spent, decimal("40.00"));
+    assert_eq!(status.remaining, decimal("60.00"));
+    assert_eq!(status.overspend, BigDecimal::zero());
+    assert!(!status.over_budget);
+}
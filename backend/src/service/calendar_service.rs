@@ -0,0 +1,90 @@
+//! The `calendar_service` module exports planned [`Meal`]s as iCalendar feed
+//! according to RFC 5545, so they can be subscribed to with calendar applications.
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+use crate::entity::meal::Meal;
+
+/// The identifier of the application generating the calendar.
+const CALENDAR_PRODUCT_ID: &str = "-//homework//meal plan//EN";
+/// The name calendar applications display for the calendar.
+const CALENDAR_NAME: &str = "Meal plan";
+/// The domain appended to the IDs of meals to make them globally unique.
+const CALENDAR_UID_DOMAIN: &str = "homework";
+/// The duration of a single meal in hours.
+const MEAL_DURATION_HOURS: i64 = 1;
+/// The maximum length of a content line in octets excluding the line break.
+const LINE_MAXIMUM_OCTETS: usize = 75;
+/// The format of date-times in local time.
+const FORMAT_LOCAL_DATE_TIME: &str = "%Y%m%dT%H%M%S";
+/// The format of date-times in UTC.
+const FORMAT_UTC_DATE_TIME: &str = "%Y%m%dT%H%M%SZ";
+
+/// Creates an iCalendar feed with one event per meal. The events start at the usual
+/// time of the meal slot in the local time of the calendar application.
+///
+/// # Parameters
+///
+/// * `meals` - the meals to export
+/// * `timestamp` - the time the feed is created at
+pub fn meal_plan_calendar(meals: &[Meal], timestamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", CALENDAR_PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", CALENDAR_NAME),
+    ];
+    for meal in meals {
+        let start = NaiveDateTime::new(meal.date(), meal.slot().start_time());
+        let end = start + Duration::hours(MEAL_DURATION_HOURS);
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@{}", meal.id(), CALENDAR_UID_DOMAIN));
+        lines.push(format!("DTSTAMP:{}", timestamp.format(FORMAT_UTC_DATE_TIME)));
+        lines.push(format!("DTSTART:{}", start.format(FORMAT_LOCAL_DATE_TIME)));
+        lines.push(format!("DTEND:{}", end.format(FORMAT_LOCAL_DATE_TIME)));
+        lines.push(format!(
+            "SUMMARY:{}",
+            escape_text(&format!("{}: {}", meal.slot(), meal.recipe_title()))
+        ));
+        if let Some(servings) = meal.servings() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&format!("{} servings", servings))));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines
+        .iter()
+        .map(|line| fold_line(line))
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+/// Escapes the characters with a special meaning in text values.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Terminates the content line with a line break and splits it into multiple lines
+/// of the maximum length without splitting characters. Continuation lines start with a space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for character in line.chars() {
+        if length + character.len_utf8() > LINE_MAXIMUM_OCTETS {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(character);
+        length += character.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod test;
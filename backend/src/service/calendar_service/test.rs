@@ -0,0 +1,69 @@
+use chrono::TimeZone;
+use serde_json::json;
+
+use super::*;
+
+/// Deserializes a meal from its JSON representation.
+fn meal(id: u128, date: &str, slot: &str, servings: Option<u32>) -> Meal {
+    serde_json::from_value(json!({
+        "id": uuid::Uuid::from_u128(id),
+        "date": date,
+        "slot": slot,
+        "recipeId": uuid::Uuid::nil(),
+        "servings": servings,
+    }))
+    .unwrap()
+}
+
+#[test]
+/// Tests if the `meal_plan_calendar` function creates one event per meal with CRLF line breaks.
+fn test_meal_plan_calendar() {
+    let timestamp = Utc.with_ymd_and_hms(2022, 3, 1, 9, 30, 0).unwrap();
+    let meals = vec![
+        meal(1, "2022-03-02", "Breakfast", None),
+        meal(2, "2022-03-02", "Dinner", Some(4)),
+    ];
+
+    let calendar = meal_plan_calendar(&meals, timestamp);
+    let lines: Vec<&str> = calendar.split_terminator("\r\n").collect();
+    assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    assert!(!calendar.replace("\r\n", "").contains('\n'));
+    assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+    assert_eq!(lines.iter().filter(|line| **line == "BEGIN:VEVENT").count(), 2);
+    assert!(lines.contains(&"UID:00000000-0000-0000-0000-000000000002@homework"));
+    assert!(lines.contains(&"DTSTAMP:20220301T093000Z"));
+    assert!(lines.contains(&"DTSTART:20220302T080000"));
+    assert!(lines.contains(&"DTSTART:20220302T180000"));
+    assert!(lines.contains(&"DTEND:20220302T190000"));
+    assert!(lines.contains(&"DESCRIPTION:4 servings"));
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|line| line.starts_with("DESCRIPTION"))
+            .count(),
+        1
+    );
+}
+
+#[test]
+/// Tests if the `escape_text` function escapes special characters of text values.
+fn test_escape_text() {
+    assert_eq!(
+        escape_text("Salt, pepper; oil\\vinegar\nServe hot"),
+        r"Salt\, pepper\; oil\\vinegar\nServe hot"
+    );
+}
+
+#[test]
+/// Tests if the `fold_line` function splits long lines without splitting characters.
+fn test_fold_line() {
+    assert_eq!(fold_line("SUMMARY:Short"), "SUMMARY:Short\r\n");
+
+    let line = format!("SUMMARY:{}", "ä".repeat(40));
+    let folded = fold_line(&line);
+    let parts: Vec<&str> = folded.split_terminator("\r\n").collect();
+    assert_eq!(parts.len(), 2);
+    assert!(parts.iter().all(|part| part.len() <= LINE_MAXIMUM_OCTETS));
+    assert!(parts[1].starts_with(' '));
+    assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+}
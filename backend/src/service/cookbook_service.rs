@@ -0,0 +1,561 @@
+//! The `cookbook_service` module renders many recipes into a single PDF with a
+//! table of contents. Only the standard fonts every PDF reader provides are used,
+//! so no fonts need to be embedded and the PDF is generated without external tools.
+
+use pdf_writer::{
+    types::{ActionType, AnnotationType, PageMode},
+    Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr,
+};
+
+use crate::entity::recipe::Recipe;
+
+use super::recipe_export_service::{
+    ingredient_line, instruction_paragraphs, ordered_ingredients, recipe_details, ExportImage,
+    TextStyle,
+};
+
+/// The width of an A4 page in points.
+const PAGE_WIDTH: f32 = 595.0;
+/// The height of an A4 page in points.
+const PAGE_HEIGHT: f32 = 842.0;
+/// The margin around the content of a page in points.
+const PAGE_MARGIN: f32 = 56.0;
+/// The width of the content of a page in points.
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * PAGE_MARGIN;
+/// The distance of the page number from the bottom of a page in points.
+const PAGE_NUMBER_OFFSET: f32 = 28.0;
+/// The height of a line relative to the font size.
+const LINE_HEIGHT: f32 = 1.35;
+/// The font size of the cookbook title.
+const FONT_SIZE_TITLE: f32 = 26.0;
+/// The font size of recipe titles.
+const FONT_SIZE_RECIPE_TITLE: f32 = 20.0;
+/// The font size of section headings.
+const FONT_SIZE_HEADING: f32 = 13.0;
+/// The font size of text.
+const FONT_SIZE_TEXT: f32 = 10.5;
+/// The font size of page numbers.
+const FONT_SIZE_PAGE_NUMBER: f32 = 9.0;
+/// The maximum width of thumbnails in points.
+const THUMBNAIL_MAXIMUM_WIDTH: f32 = 260.0;
+/// The maximum height of thumbnails in points.
+const THUMBNAIL_MAXIMUM_HEIGHT: f32 = 200.0;
+/// The indentation of list items in points.
+const LIST_INDENT: f32 = 14.0;
+/// The width reserved for page numbers in the table of contents in points.
+const CONTENTS_PAGE_NUMBER_WIDTH: f32 = 40.0;
+/// The marker of list items, which is a bullet in `WinAnsiEncoding`.
+const LIST_MARKER: &[u8] = &[0x95];
+/// The replacement of characters that are not available in `WinAnsiEncoding`.
+const UNKNOWN_CHARACTER: u8 = b'?';
+/// The widths of the printable ASCII characters of Helvetica in thousandths of the font size.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+/// The widths of the printable ASCII characters of Helvetica Bold in thousandths of the font size.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The standard fonts used by the cookbook.
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    /// Returns the name of this `Font` within the page resources.
+    fn resource_name(&self) -> Name<'static> {
+        match self {
+            Font::Regular => Name(b"F1"),
+            Font::Bold => Name(b"F2"),
+        }
+    }
+
+    /// Returns the name of the standard font.
+    fn base_font(&self) -> Name<'static> {
+        match self {
+            Font::Regular => Name(b"Helvetica"),
+            Font::Bold => Name(b"Helvetica-Bold"),
+        }
+    }
+
+    /// Returns the width in points of the `WinAnsiEncoding` encoded text.
+    fn text_width(&self, text: &[u8], size: f32) -> f32 {
+        let widths = match self {
+            Font::Regular => &HELVETICA_WIDTHS,
+            Font::Bold => &HELVETICA_BOLD_WIDTHS,
+        };
+        let units: u32 = text
+            .iter()
+            .map(|byte| match byte {
+                0x20..=0x7e => u32::from(widths[usize::from(byte - 0x20)]),
+                0x85 | 0x97 => 1000,
+                0x95 => 350,
+                0xa0 | 0xb7 => 278,
+                // Approximate the remaining characters by the width of an average letter.
+                _ => 556,
+            })
+            .sum();
+        units as f32 * size / 1000.0
+    }
+}
+
+/// A page of the cookbook while it is laid out.
+struct LayoutPage {
+    content: Content,
+    /// The indices of the images drawn on the page.
+    images: Vec<usize>,
+    /// The areas of the page linking to the page with the index.
+    links: Vec<(Rect, usize)>,
+}
+
+/// Lays out text and images from top to bottom, starting new pages as needed.
+struct Layout {
+    pages: Vec<LayoutPage>,
+    /// The vertical position of the top of the remaining space on the current page.
+    y: f32,
+}
+
+impl Layout {
+    /// Creates a new `Layout` without any pages.
+    fn new() -> Self {
+        Layout {
+            pages: Vec::new(),
+            y: 0.0,
+        }
+    }
+
+    /// Starts a new page.
+    fn start_page(&mut self) {
+        self.pages.push(LayoutPage {
+            content: Content::new(),
+            images: Vec::new(),
+            links: Vec::new(),
+        });
+        self.y = PAGE_HEIGHT - PAGE_MARGIN;
+    }
+
+    /// Returns the current page.
+    fn page(&mut self) -> &mut LayoutPage {
+        self.pages
+            .last_mut()
+            .expect("A page is started before any content is added.")
+    }
+
+    /// Starts a new page if the remaining space on the current page is too small.
+    fn reserve(&mut self, height: f32) {
+        if self.pages.is_empty() || self.y - height < PAGE_MARGIN {
+            self.start_page();
+        }
+    }
+
+    /// Adds vertical space, which is dropped at the bottom of a page.
+    fn space(&mut self, height: f32) {
+        self.y = (self.y - height).max(PAGE_MARGIN);
+    }
+
+    /// Adds the text wrapped to the content width.
+    fn text(&mut self, text: &str, font: Font, size: f32) {
+        self.lines(text, font, size, 0.0, CONTENT_WIDTH, None);
+    }
+
+    /// Adds the text as list item with a hanging indentation.
+    fn list_item(&mut self, text: &str, size: f32) {
+        self.lines(
+            text,
+            Font::Regular,
+            size,
+            LIST_INDENT,
+            CONTENT_WIDTH - LIST_INDENT,
+            Some(LIST_MARKER),
+        );
+    }
+
+    /// Adds the text wrapped to the width starting at the indentation and returns
+    /// the area of each line and the index of its page.
+    fn lines(
+        &mut self,
+        text: &str,
+        font: Font,
+        size: f32,
+        indent: f32,
+        width: f32,
+        marker: Option<&[u8]>,
+    ) -> Vec<(Rect, usize)> {
+        let line_height = size * LINE_HEIGHT;
+        let mut areas = Vec::new();
+        for (index, line) in wrap(&encode_win_ansi(text), font, size, width)
+            .iter()
+            .enumerate()
+        {
+            self.reserve(line_height);
+            self.y -= line_height;
+            let baseline = baseline(self.y, size);
+            if let (0, Some(marker)) = (index, marker) {
+                self.show(marker, font, size, PAGE_MARGIN, baseline);
+            }
+            self.show(line, font, size, PAGE_MARGIN + indent, baseline);
+            let area =
+                Rect::new(PAGE_MARGIN, self.y, PAGE_MARGIN + CONTENT_WIDTH, self.y + line_height);
+            areas.push((area, self.pages.len() - 1));
+        }
+        areas
+    }
+
+    /// Draws the encoded text with its baseline starting at the position.
+    fn show(&mut self, text: &[u8], font: Font, size: f32, x: f32, y: f32) {
+        let content = &mut self.page().content;
+        content.begin_text();
+        content.set_font(font.resource_name(), size);
+        content.next_line(x, y);
+        content.show(Str(text));
+        content.end_text();
+    }
+
+    /// Draws the image with the index scaled to fit into the maximum size.
+    fn image(
+        &mut self,
+        index: usize,
+        image: &ExportImage,
+        maximum_width: f32,
+        maximum_height: f32,
+    ) {
+        let (image_width, image_height) = (image.width() as f32, image.height() as f32);
+        let scale = (maximum_width / image_width).min(maximum_height / image_height);
+        let (width, height) = (image_width * scale, image_height * scale);
+        self.reserve(height);
+        self.y -= height;
+        let y = self.y;
+        let page = self.page();
+        page.images.push(index);
+        page.content.save_state();
+        page.content
+            .transform([width, 0.0, 0.0, height, PAGE_MARGIN, y]);
+        page.content
+            .x_object(Name(image_resource_name(index).as_bytes()));
+        page.content.restore_state();
+    }
+}
+
+/// Renders the recipes into a PDF. The PDF starts with a table of contents linking
+/// to the recipes, which start on a new page each. The recipes are also listed in the
+/// outline of the PDF.
+///
+/// # Parameters
+///
+/// * `title` - the title of the cookbook
+/// * `recipes` - the recipes in the order of the cookbook including their thumbnails
+pub fn cookbook_pdf(title: &str, recipes: &[(Recipe, Option<ExportImage>)]) -> Vec<u8> {
+    let mut body = Layout::new();
+    let mut recipe_starts = Vec::new();
+    for (index, (recipe, thumbnail)) in recipes.iter().enumerate() {
+        body.start_page();
+        recipe_starts.push(body.pages.len() - 1);
+        layout_recipe(&mut body, recipe, thumbnail.as_ref().map(|image| (index, image)));
+    }
+    // The page numbers of the recipes depend on the length of the table of contents,
+    // which does not depend on the page numbers, so it is laid out twice.
+    let contents_length = layout_contents(title, recipes, &recipe_starts, 0)
+        .pages
+        .len();
+    let contents = layout_contents(title, recipes, &recipe_starts, contents_length);
+    let recipe_starts: Vec<usize> = recipe_starts
+        .iter()
+        .map(|start| start + contents_length)
+        .collect();
+    let mut pages = contents.pages;
+    pages.extend(body.pages);
+    let page_count = pages.len();
+
+    let mut pdf = Pdf::new();
+    let mut next_id = Ref::new(1);
+    let mut allocate = || next_id.bump();
+    let catalog_id = allocate();
+    let page_tree_id = allocate();
+    let info_id = allocate();
+    let outline_id = allocate();
+    let font_ids = [(Font::Regular, allocate()), (Font::Bold, allocate())];
+    let image_ids: Vec<Option<Ref>> = recipes
+        .iter()
+        .map(|(_, thumbnail)| thumbnail.as_ref().map(|_| allocate()))
+        .collect();
+    let page_ids: Vec<Ref> = (0..page_count).map(|_| allocate()).collect();
+    let content_ids: Vec<Ref> = (0..page_count).map(|_| allocate()).collect();
+    let outline_item_ids: Vec<Ref> = recipes.iter().map(|_| allocate()).collect();
+
+    let mut catalog = pdf.catalog(catalog_id);
+    catalog.pages(page_tree_id);
+    if !recipes.is_empty() {
+        catalog
+            .outlines(outline_id)
+            .page_mode(PageMode::UseOutlines);
+    }
+    catalog.finish();
+    pdf.document_info(info_id).title(TextStr(title));
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(page_count as i32);
+    for (font, font_id) in font_ids {
+        pdf.type1_font(font_id)
+            .base_font(font.base_font())
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+    }
+    for ((_, thumbnail), image_id) in recipes.iter().zip(&image_ids) {
+        if let (Some(image), Some(image_id)) = (thumbnail, image_id) {
+            let mut image_xobject = pdf.image_xobject(*image_id, image.jpeg());
+            image_xobject.filter(Filter::DctDecode);
+            image_xobject.width(image.width() as i32);
+            image_xobject.height(image.height() as i32);
+            image_xobject.color_space().device_rgb();
+            image_xobject.bits_per_component(8);
+        }
+    }
+
+    for (index, mut layout_page) in pages.into_iter().enumerate() {
+        let number = encode_win_ansi(&(index + 1).to_string());
+        let number_width = Font::Regular.text_width(&number, FONT_SIZE_PAGE_NUMBER);
+        let content = &mut layout_page.content;
+        content.begin_text();
+        content.set_font(Font::Regular.resource_name(), FONT_SIZE_PAGE_NUMBER);
+        content.next_line((PAGE_WIDTH - number_width) / 2.0, PAGE_NUMBER_OFFSET);
+        content.show(Str(&number));
+        content.end_text();
+        pdf.stream(content_ids[index], &layout_page.content.finish());
+
+        let mut page = pdf.page(page_ids[index]);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(page_tree_id);
+        page.contents(content_ids[index]);
+        let mut resources = page.resources();
+        let mut fonts = resources.fonts();
+        for (font, font_id) in font_ids {
+            fonts.pair(font.resource_name(), font_id);
+        }
+        fonts.finish();
+        let mut x_objects = resources.x_objects();
+        for image in &layout_page.images {
+            if let Some(image_id) = image_ids[*image] {
+                x_objects.pair(Name(image_resource_name(*image).as_bytes()), image_id);
+            }
+        }
+        x_objects.finish();
+        resources.finish();
+        let mut annotations = page.annotations();
+        for (area, target) in &layout_page.links {
+            let mut annotation = annotations.push();
+            annotation.subtype(AnnotationType::Link);
+            annotation.rect(*area);
+            annotation.border(0.0, 0.0, 0.0, None);
+            annotation
+                .action()
+                .action_type(ActionType::GoTo)
+                .destination()
+                .page(page_ids[*target])
+                .xyz(0.0, PAGE_HEIGHT, None);
+        }
+    }
+
+    if let (Some(first), Some(last)) = (outline_item_ids.first(), outline_item_ids.last()) {
+        pdf.outline(outline_id)
+            .first(*first)
+            .last(*last)
+            .count(outline_item_ids.len() as i32);
+    }
+    for (index, (recipe, _)) in recipes.iter().enumerate() {
+        let mut item = pdf.outline_item(outline_item_ids[index]);
+        item.parent(outline_id);
+        item.title(TextStr(recipe.title()));
+        if index > 0 {
+            item.prev(outline_item_ids[index - 1]);
+        }
+        if let Some(next) = outline_item_ids.get(index + 1) {
+            item.next(*next);
+        }
+        item.dest()
+            .page(page_ids[recipe_starts[index]])
+            .xyz(0.0, PAGE_HEIGHT, None);
+    }
+    pdf.finish()
+}
+
+/// Lays out the title and table of contents of the cookbook.
+///
+/// # Parameters
+///
+/// * `title` - the title of the cookbook
+/// * `recipes` - the recipes of the cookbook
+/// * `recipe_starts` - the indices of the first pages of the recipes after the contents
+/// * `contents_length` - the number of pages of the table of contents
+fn layout_contents(
+    title: &str,
+    recipes: &[(Recipe, Option<ExportImage>)],
+    recipe_starts: &[usize],
+    contents_length: usize,
+) -> Layout {
+    let mut layout = Layout::new();
+    layout.start_page();
+    layout.text(title, Font::Bold, FONT_SIZE_TITLE);
+    layout.space(FONT_SIZE_TITLE);
+    layout.text("Contents", Font::Bold, FONT_SIZE_HEADING);
+    layout.space(FONT_SIZE_TEXT / 2.0);
+    if recipes.is_empty() {
+        layout.text("No recipes match the filter.", Font::Regular, FONT_SIZE_TEXT);
+    }
+    for ((recipe, _), start) in recipes.iter().zip(recipe_starts) {
+        let target = start + contents_length;
+        let areas = layout.lines(
+            recipe.title(),
+            Font::Regular,
+            FONT_SIZE_TEXT,
+            0.0,
+            CONTENT_WIDTH - CONTENTS_PAGE_NUMBER_WIDTH,
+            None,
+        );
+        // Draw the page number right-aligned next to the last line of the title.
+        if let Some((area, _)) = areas.last() {
+            let number = encode_win_ansi(&(target + 1).to_string());
+            let number_width = Font::Regular.text_width(&number, FONT_SIZE_TEXT);
+            layout.show(
+                &number,
+                Font::Regular,
+                FONT_SIZE_TEXT,
+                PAGE_MARGIN + CONTENT_WIDTH - number_width,
+                baseline(area.y1, FONT_SIZE_TEXT),
+            );
+        }
+        for (area, page) in areas {
+            layout.pages[page].links.push((area, target));
+        }
+    }
+    layout
+}
+
+/// Lays out the recipe starting at the current position.
+///
+/// # Parameters
+///
+/// * `layout` - the layout of the cookbook
+/// * `recipe` - the recipe
+/// * `thumbnail` - the index and image of the thumbnail if it can be exported
+fn layout_recipe(layout: &mut Layout, recipe: &Recipe, thumbnail: Option<(usize, &ExportImage)>) {
+    layout.text(recipe.title(), Font::Bold, FONT_SIZE_RECIPE_TITLE);
+    layout.space(FONT_SIZE_TEXT / 2.0);
+    for (label, value) in recipe_details(recipe) {
+        layout.text(&format!("{}: {}", label, value), Font::Regular, FONT_SIZE_TEXT);
+    }
+    if let Some((index, image)) = thumbnail {
+        layout.space(FONT_SIZE_TEXT);
+        layout.image(index, image, THUMBNAIL_MAXIMUM_WIDTH, THUMBNAIL_MAXIMUM_HEIGHT);
+    }
+    layout.space(FONT_SIZE_HEADING);
+    layout.text("Ingredients", Font::Bold, FONT_SIZE_HEADING);
+    layout.space(FONT_SIZE_TEXT / 4.0);
+    for ingredient in ordered_ingredients(recipe) {
+        layout.list_item(&ingredient_line(ingredient), FONT_SIZE_TEXT);
+    }
+    layout.space(FONT_SIZE_HEADING);
+    layout.text("Instructions", Font::Bold, FONT_SIZE_HEADING);
+    layout.space(FONT_SIZE_TEXT / 4.0);
+    for paragraph in instruction_paragraphs(recipe.instructions(), TextStyle::Plain) {
+        layout.text(&paragraph, Font::Regular, FONT_SIZE_TEXT);
+        layout.space(FONT_SIZE_TEXT / 2.0);
+    }
+}
+
+/// Returns the baseline of a line of text, which lies above the bottom of the line
+/// by the descent of the font.
+fn baseline(line_bottom: f32, size: f32) -> f32 {
+    line_bottom + (size * LINE_HEIGHT - size) / 2.0 + 0.2 * size
+}
+
+/// Returns the name of the image with the index within the page resources.
+fn image_resource_name(index: usize) -> String {
+    format!("Im{}", index)
+}
+
+/// Encodes the text in `WinAnsiEncoding`, which the standard fonts support.
+/// Characters that are not available are replaced.
+fn encode_win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|character| match character {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => character as u8,
+            '\t' | '\n' | '\r' => b' ',
+            '€' => 0x80,
+            '‚' => 0x82,
+            'ƒ' => 0x83,
+            '„' => 0x84,
+            '…' => 0x85,
+            '†' => 0x86,
+            '‡' => 0x87,
+            'ˆ' => 0x88,
+            '‰' => 0x89,
+            'Š' => 0x8a,
+            '‹' => 0x8b,
+            'Œ' => 0x8c,
+            'Ž' => 0x8e,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '˜' => 0x98,
+            '™' => 0x99,
+            'š' => 0x9a,
+            '›' => 0x9b,
+            'œ' => 0x9c,
+            'ž' => 0x9e,
+            'Ÿ' => 0x9f,
+            _ => UNKNOWN_CHARACTER,
+        })
+        .collect()
+}
+
+/// Wraps the encoded text into lines not wider than the width.
+/// Words that are wider than a line are broken between characters.
+fn wrap(text: &[u8], font: Font, size: f32, width: f32) -> Vec<Vec<u8>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<u8> = Vec::new();
+    for word in text
+        .split(|byte| *byte == b' ')
+        .filter(|word| !word.is_empty())
+    {
+        let mut extended = line.clone();
+        if !extended.is_empty() {
+            extended.push(b' ');
+        }
+        extended.extend_from_slice(word);
+        if font.text_width(&extended, size) <= width {
+            line = extended;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for byte in word {
+            line.push(*byte);
+            if line.len() > 1 && font.text_width(&line, size) > width {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, vec![*byte]));
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test;
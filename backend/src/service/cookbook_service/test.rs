@@ -0,0 +1,107 @@
+use serde_json::json;
+
+use crate::application::config::Configuration;
+
+use super::*;
+
+/// Creates a recipe with the number of identical ingredients.
+fn recipe(title: &str, ingredient_count: usize) -> Recipe {
+    let recipe_id = Configuration::generate_uuid();
+    let ingredients: Vec<serde_json::Value> = (0..ingredient_count)
+        .map(|ordering| {
+            json!({
+                "id": Configuration::generate_uuid(),
+                "amount": "1",
+                "unit": "tbsp",
+                "text": "sugar",
+                "creationTime": "2023-01-01T00:00:00Z",
+                "recipeReference": null,
+                "recipeId": recipe_id,
+                "ordering": ordering,
+                "filterText": null,
+            })
+        })
+        .collect();
+    serde_json::from_value(json!({
+        "id": recipe_id,
+        "title": title,
+        "instructions": "<p>Mix everything.</p>",
+        "reference": "",
+        "rating": 0,
+        "servings": null,
+        "thumbnail": null,
+        "tags": [],
+        "attachments": [],
+        "ingredients": ingredients,
+        "creationTime": "2023-01-01T00:00:00Z",
+    }))
+    .unwrap()
+}
+
+/// Returns the number of occurrences of the pattern in the PDF.
+fn count(pdf: &[u8], pattern: &str) -> usize {
+    pdf.windows(pattern.len())
+        .filter(|window| *window == pattern.as_bytes())
+        .count()
+}
+
+#[test]
+/// Tests if the `cookbook_pdf` function renders a table of contents with the page numbers of all recipes.
+fn test_cookbook_pdf() {
+    let path = std::env::temp_dir().join(format!("{}.png", Configuration::generate_uuid()));
+    image::RgbImage::new(4, 3).save(&path).unwrap();
+    let thumbnail = ExportImage::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    let thumbnail = thumbnail.unwrap();
+    let recipes = vec![
+        (recipe("Long cake", 100), Some(thumbnail)),
+        (recipe("Crème brûlée", 3), None),
+    ];
+    let pdf = cookbook_pdf("Desserts", &recipes);
+    assert!(pdf.starts_with(b"%PDF-"));
+    // The contents, three pages of the long recipe and one of the short recipe.
+    assert_eq!(count(&pdf, "/Type /Page\n"), 5);
+    assert_eq!(count(&pdf, "/Subtype /Link"), 2);
+    assert_eq!(count(&pdf, "/Type /Outlines"), 1);
+    assert_eq!(count(&pdf, "/Subtype /Image"), 1);
+    assert_eq!(count(&pdf, "/Im0 Do"), 1);
+    assert_eq!(count(&pdf, "(Desserts) Tj"), 1);
+    // The recipes start on the second and fifth page.
+    assert_eq!(count(&pdf, "(2) Tj"), 2);
+    assert_eq!(count(&pdf, "(5) Tj"), 2);
+    // Non-ASCII text is written as hex string of the encoded characters.
+    assert_eq!(count(&pdf, "<4372E86D65206272FB6CE965> Tj"), 2);
+}
+
+#[test]
+/// Tests if the `cookbook_pdf` function renders an empty cookbook.
+fn test_cookbook_pdf_empty() {
+    let pdf = cookbook_pdf("Nothing", &[]);
+    assert_eq!(count(&pdf, "/Type /Page\n"), 1);
+    assert_eq!(count(&pdf, "/Type /Outlines"), 0);
+    assert_eq!(count(&pdf, "(No recipes match the filter.) Tj"), 1);
+}
+
+#[test]
+/// Tests if the `wrap` function breaks lines between words and within overlong words.
+fn test_wrap() {
+    let width = Font::Regular.text_width(b"aaaa aaaa", 10.0);
+    assert_eq!(
+        wrap(b"aaaa aaaa aaaa", Font::Regular, 10.0, width),
+        vec![b"aaaa aaaa".to_vec(), b"aaaa".to_vec()]
+    );
+    assert_eq!(
+        wrap(b"aaaaaaaaaaaaaaaaaaaa", Font::Regular, 10.0, width),
+        vec![b"aaaaaaaa".to_vec(), b"aaaaaaaa".to_vec(), b"aaaa".to_vec()]
+    );
+    assert_eq!(wrap(b"", Font::Regular, 10.0, width), vec![Vec::<u8>::new()]);
+}
+
+#[test]
+/// Tests if the `encode_win_ansi` function encodes Latin characters and replaces all others.
+fn test_encode_win_ansi() {
+    assert_eq!(
+        encode_win_ansi("Café – 5 €, 寿司"),
+        vec![b'C', b'a', b'f', 0xe9, b' ', 0x96, b' ', b'5', b' ', 0x80, b',', b' ', b'?', b'?']
+    );
+}
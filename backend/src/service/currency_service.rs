@@ -0,0 +1,195 @@
+//! The `currency_service` module converts amounts between currencies based on
+//! the locally stored [`ExchangeRate`]s.
+
+use std::collections::HashMap;
+
+use bigdecimal::{BigDecimal, Signed, Zero};
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    entity::{exchange_rate::ExchangeRate, payment::SplitMode},
+};
+
+use super::split_service::{split, total};
+
+/// The number of decimal places converted amounts are rounded to.
+const CONVERSION_SCALE: i64 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A converter of amounts into a base currency.
+pub struct CurrencyConverter {
+    base_currency: String,
+    rates: Vec<ExchangeRate>,
+}
+
+impl CurrencyConverter {
+    /// Creates a new `CurrencyConverter`.
+    ///
+    /// # Parameters
+    ///
+    /// * `base_currency` - the currency all amounts are converted to
+    /// * `rates` - the available exchange rates
+    pub fn new<T: ToString>(base_currency: T, mut rates: Vec<ExchangeRate>) -> Self {
+        rates.sort_by_key(|rate| rate.valid_from());
+        CurrencyConverter {
+            base_currency: base_currency.to_string(),
+            rates,
+        }
+    }
+
+    /// Creates a new `CurrencyConverter` using all exchange rates stored in the database.
+    ///
+    /// # Parameters
+    ///
+    /// * `base_currency` - the currency all amounts are converted to
+    /// * `connection` - the database connection
+    pub fn from_database<T: ToString>(
+        base_currency: T,
+        connection: &Connection,
+    ) -> Result<Self, HomeworkError> {
+        Ok(Self::new(base_currency, ExchangeRate::select_all_from_database(connection)?))
+    }
+
+    /// Returns the currency all amounts are converted to.
+    pub fn base_currency(&self) -> &str {
+        &self.base_currency
+    }
+
+    /// Returns the units of the base currency that one unit of the specified currency
+    /// was worth at the specified date.
+    /// The most recent rate valid at the date is used. If no rate was valid yet,
+    /// the earliest rate is used instead. Rates stored in the inverse direction
+    /// are inverted.
+    ///
+    /// # Parameters
+    ///
+    /// * `currency` - the currency to convert from
+    /// * `date` - the date of the conversion
+    pub fn rate(&self, currency: &str, date: DateTime<Utc>) -> Result<BigDecimal, HomeworkError> {
+        if currency == self.base_currency() {
+            return Ok(BigDecimal::from(1));
+        }
+        self.rates
+            .iter()
+            .filter(|rate| rate.valid_from() <= date)
+            .rev()
+            .chain(self.rates.iter().filter(|rate| rate.valid_from() > date))
+            .find_map(|rate| {
+                if rate.source_currency() == currency
+                    && rate.target_currency() == self.base_currency()
+                {
+                    Some(rate.rate().clone())
+                } else if rate.source_currency() == self.base_currency()
+                    && rate.target_currency() == currency
+                {
+                    Some(BigDecimal::from(1) / rate.rate())
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| {
+                HomeworkError::BadRequestError(InternalError::new(
+                    "Missing exchange rate",
+                    format!(
+                        "There is no exchange rate between {} and {}.",
+                        currency,
+                        self.base_currency()
+                    ),
+                    format!(
+                        "An exchange rate between {} and {} is required.",
+                        currency,
+                        self.base_currency()
+                    ),
+                ))
+            })
+    }
+
+    /// Converts the amount into the base currency and rounds it to cents.
+    ///
+    /// # Parameters
+    ///
+    /// * `amount` - the amount to convert
+    /// * `currency` - the currency of the amount
+    /// * `date` - the date of the conversion
+    pub fn convert(
+        &self,
+        amount: &BigDecimal,
+        currency: &str,
+        date: DateTime<Utc>,
+    ) -> Result<BigDecimal, HomeworkError> {
+        if currency == self.base_currency() {
+            return Ok(amount.clone());
+        }
+        Ok(round_half_up(&(amount * self.rate(currency, date)?), CONVERSION_SCALE))
+    }
+
+    /// Converts all amounts into the base currency. The total of the amounts is converted
+    /// once and split proportionally to the original amounts, so the converted amounts
+    /// still add up to the converted total instead of drifting apart by rounding each amount.
+    ///
+    /// # Parameters
+    ///
+    /// * `amounts` - the amounts to convert
+    /// * `currency` - the currency of the amounts
+    /// * `date` - the date of the conversion
+    pub fn convert_amounts(
+        &self,
+        amounts: &HashMap<String, BigDecimal>,
+        currency: &str,
+        date: DateTime<Utc>,
+    ) -> Result<HashMap<String, BigDecimal>, HomeworkError> {
+        let amounts_total = total(amounts);
+        if currency == self.base_currency() || amounts_total.is_zero() {
+            return amounts
+                .iter()
+                .map(|(person, amount)| Ok((person.clone(), self.convert(amount, currency, date)?)))
+                .collect();
+        }
+        split(
+            &SplitMode::Shares {
+                shares: amounts.clone(),
+            },
+            &self.convert(&amounts_total, currency, date)?,
+        )
+    }
+}
+
+/// Validates an ISO 4217 like currency code consisting of three letters and
+/// returns it in upper case.
+///
+/// # Parameters
+///
+/// * `code` - the currency code to validate
+pub fn validate_currency_code(code: &str) -> Result<String, HomeworkError> {
+    let code = code.trim().to_uppercase();
+    if code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase()) {
+        Ok(code)
+    } else {
+        Err(HomeworkError::BadRequestError(InternalError::new(
+            "Invalid currency",
+            format!("\"{}\" is not a valid currency code.", code),
+            "A currency code must consist of three letters.",
+        )))
+    }
+}
+
+/// Rounds the value to the specified number of decimal places.
+/// Halves are rounded away from zero.
+///
+/// # Parameters
+///
+/// * `value` - the value to round
+/// * `scale` - the number of decimal places
+pub fn round_half_up(value: &BigDecimal, scale: i64) -> BigDecimal {
+    let half = BigDecimal::new(5.into(), scale + 1);
+    if value.is_negative() {
+        (value - half).with_scale(scale)
+    } else {
+        (value + half).with_scale(scale)
+    }
+}
+
+#[cfg(test)]
+mod test;
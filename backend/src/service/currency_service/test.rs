@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+use super::*;
+
+/// Creates an exchange rate valid from the specified date.
+fn rate(
+    source_currency: &str,
+    target_currency: &str,
+    rate: &str,
+    valid_from: &str,
+) -> ExchangeRate {
+    ExchangeRate::new(
+        Uuid::nil(),
+        source_currency,
+        target_currency,
+        BigDecimal::from_str(rate).unwrap(),
+        valid_from.parse().unwrap(),
+    )
+}
+
+#[test]
+/// Tests if the `rate` function picks the most recent rate valid at the date.
+fn test_rate_by_date() {
+    let converter = CurrencyConverter::new(
+        "EUR",
+        vec![
+            rate("CHF", "EUR", "1.02", "2022-06-01T00:00:00Z"),
+            rate("CHF", "EUR", "0.95", "2022-01-01T00:00:00Z"),
+            rate("GBP", "EUR", "1.15", "2022-03-01T00:00:00Z"),
+        ],
+    );
+    let date = |date: &str| DateTime::<Utc>::from_str(date).unwrap();
+    assert_eq!(
+        converter.rate("CHF", date("2022-05-31T00:00:00Z")).unwrap(),
+        BigDecimal::from_str("0.95").unwrap()
+    );
+    assert_eq!(
+        converter.rate("CHF", date("2022-06-01T00:00:00Z")).unwrap(),
+        BigDecimal::from_str("1.02").unwrap()
+    );
+    // Dates before the first rate use the earliest rate.
+    assert_eq!(
+        converter.rate("CHF", date("2021-01-01T00:00:00Z")).unwrap(),
+        BigDecimal::from_str("0.95").unwrap()
+    );
+    assert_eq!(converter.rate("EUR", date("2021-01-01T00:00:00Z")).unwrap(), BigDecimal::from(1));
+    assert!(converter.rate("USD", date("2022-01-01T00:00:00Z")).is_err());
+}
+
+#[test]
+/// Tests if the `convert` function inverts rates and rounds to cents.
+fn test_convert_inverse() {
+    let converter =
+        CurrencyConverter::new("GBP", vec![rate("GBP", "EUR", "1.15", "2022-01-01T00:00:00Z")]);
+    let date = DateTime::<Utc>::from_str("2022-02-01T00:00:00Z").unwrap();
+    assert_eq!(
+        converter
+            .convert(&BigDecimal::from_str("10.00").unwrap(), "EUR", date)
+            .unwrap(),
+        BigDecimal::from_str("8.70").unwrap()
+    );
+}
+
+#[test]
+/// Tests if the `convert_amounts` function converts the total once and distributes
+/// the cents, so paid and involved amounts of a payment still balance.
+fn test_convert_amounts() {
+    let converter =
+        CurrencyConverter::new("EUR", vec![rate("GBP", "EUR", "1.07", "2022-01-01T00:00:00Z")]);
+    let date = DateTime::<Utc>::from_str("2022-02-01T00:00:00Z").unwrap();
+    let amounts = |entries: &[(&str, &str)]| -> HashMap<String, BigDecimal> {
+        entries
+            .iter()
+            .map(|(person, amount)| (person.to_string(), BigDecimal::from_str(amount).unwrap()))
+            .collect()
+    };
+    let paid = converter
+        .convert_amounts(&amounts(&[("Anna", "10.00")]), "GBP", date)
+        .unwrap();
+    let involved = converter
+        .convert_amounts(
+            &amounts(&[("Anna", "3.34"), ("Ben", "3.33"), ("Carl", "3.33")]),
+            "GBP",
+            date,
+        )
+        .unwrap();
+    assert_eq!(paid, amounts(&[("Anna", "10.70")]));
+    assert_eq!(involved, amounts(&[("Anna", "3.58"), ("Ben", "3.56"), ("Carl", "3.56")]));
+    assert_eq!(
+        converter
+            .convert_amounts(&amounts(&[("Anna", "0.00")]), "GBP", date)
+            .unwrap(),
+        amounts(&[("Anna", "0.00")])
+    );
+}
+
+#[test]
+/// Tests if the `validate_currency_code` function normalises and rejects codes.
+fn test_validate_currency_code() {
+    assert_eq!(validate_currency_code(" chf ").unwrap(), "CHF");
+    assert!(validate_currency_code("EURO").is_err());
+    assert!(validate_currency_code("E1R").is_err());
+    assert!(validate_currency_code("").is_err());
+}
+
+#[test]
+/// Tests if the `round_half_up` function rounds halves away from zero.
+fn test_round_half_up() {
+    let round = |value: &str| round_half_up(&BigDecimal::from_str(value).unwrap(), 2);
+    assert_eq!(round("1.005"), BigDecimal::from_str("1.01").unwrap());
+    assert_eq!(round("1.0049"), BigDecimal::from_str("1.00").unwrap());
+    assert_eq!(round("-1.005"), BigDecimal::from_str("-1.01").unwrap());
+    assert_eq!(round("2"), BigDecimal::from_str("2.00").unwrap());
+}
@@ -0,0 +1,279 @@
+//! The `encryption_service` module encrypts files with authenticated encryption
+//! using a key derived from a passphrase or key file.
+
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    ops::RangeInclusive,
+};
+
+use openssl::{
+    hash::MessageDigest,
+    pkcs5::pbkdf2_hmac,
+    pkey::PKey,
+    rand::rand_bytes,
+    sign::Signer,
+    symm::{Cipher, Crypter, Mode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::application::error::{HomeworkError, InternalError};
+
+/// The name of the cipher.
+const CIPHER: &str = "aes-256-gcm";
+/// The name of the key derivation function.
+const KDF: &str = "pbkdf2-hmac-sha256";
+/// The number of iterations of the key derivation function for new keys.
+const KDF_ITERATIONS: u32 = 600_000;
+/// The numbers of iterations of the key derivation function accepted from stored parameters,
+/// so a manipulated archive cannot make the derivation take arbitrarily long.
+const KDF_ITERATIONS_SUPPORTED: RangeInclusive<u32> = 1_000..=10_000_000;
+/// The length of the random salt of the key derivation function in bytes.
+const SALT_LENGTH: usize = 16;
+/// The length of the cipher key and of the key used to identify content in bytes.
+const KEY_LENGTH: usize = 32;
+/// The length of the nonce stored before the encrypted content in bytes.
+const NONCE_LENGTH: usize = 12;
+/// The length of the authentication tag stored after the encrypted content in bytes.
+const TAG_LENGTH: usize = 16;
+/// The number of bytes encrypted at once.
+const CHUNK_LENGTH: usize = 64 * 1024;
+/// The message authenticated to identify a key without revealing it.
+const KEY_ID_MESSAGE: &[u8] = b"homework backup key";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// The parameters needed to derive a key from the secret again, which are stored
+/// unencrypted next to the encrypted content.
+pub struct EncryptionParameters {
+    /// The cipher the content is encrypted with.
+    cipher: String,
+    /// The key derivation function.
+    kdf: String,
+    /// The number of iterations of the key derivation function.
+    iterations: u32,
+    /// The hexadecimal salt of the key derivation function.
+    salt: String,
+    /// An identifier of the derived key to detect a wrong secret before decrypting.
+    key_id: String,
+}
+
+/// A key derived from a secret to encrypt and identify content.
+pub struct EncryptionKey {
+    /// The key of the cipher.
+    cipher_key: Vec<u8>,
+    /// The key used to identify content without revealing its hash.
+    content_key: Vec<u8>,
+    /// The parameters the key was derived with.
+    parameters: EncryptionParameters,
+}
+
+impl EncryptionKey {
+    /// Derives a new key with a random salt from the secret.
+    ///
+    /// # Parameters
+    ///
+    /// * `secret` - the passphrase or key file content
+    pub fn generate(secret: &[u8]) -> Result<Self, HomeworkError> {
+        let mut salt = [0; SALT_LENGTH];
+        rand_bytes(&mut salt)?;
+        EncryptionKey::derive(secret, KDF_ITERATIONS, &salt)
+    }
+
+    /// Derives the key described by the parameters from the secret.
+    /// Fails if the parameters are not supported or the secret does not match.
+    ///
+    /// # Parameters
+    ///
+    /// * `secret` - the passphrase or key file content
+    /// * `parameters` - the parameters stored with the encrypted content
+    pub fn from_parameters(
+        secret: &[u8],
+        parameters: &EncryptionParameters,
+    ) -> Result<Self, HomeworkError> {
+        let supported = parameters.cipher == CIPHER
+            && parameters.kdf == KDF
+            && KDF_ITERATIONS_SUPPORTED.contains(&parameters.iterations);
+        let salt = supported
+            .then(|| decode_hex(&parameters.salt))
+            .flatten()
+            .ok_or_else(|| {
+                HomeworkError::BadRequestError(InternalError::new(
+                    "Unsupported encryption",
+                    format!("The encryption parameters {:?} are not supported.", parameters),
+                    "The encryption of the backup is not supported.",
+                ))
+            })?;
+        let key = EncryptionKey::derive(secret, parameters.iterations, &salt)?;
+        if key.parameters.key_id != parameters.key_id {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Wrong backup key",
+                format!(
+                    "The key {} does not match the key {} of the backup.",
+                    key.parameters.key_id, parameters.key_id
+                ),
+                "The backup is encrypted with a different passphrase or key file.",
+            )));
+        }
+        Ok(key)
+    }
+
+    /// Derives the cipher and content key from the secret.
+    ///
+    /// # Parameters
+    ///
+    /// * `secret` - the passphrase or key file content
+    /// * `iterations` - the number of iterations of the key derivation function
+    /// * `salt` - the salt of the key derivation function
+    fn derive(secret: &[u8], iterations: u32, salt: &[u8]) -> Result<Self, HomeworkError> {
+        let mut derived_key = [0; 2 * KEY_LENGTH];
+        pbkdf2_hmac(secret, salt, iterations as usize, MessageDigest::sha256(), &mut derived_key)?;
+        let (cipher_key, content_key) = derived_key.split_at(KEY_LENGTH);
+        let content_pkey = PKey::hmac(content_key)?;
+        let mut key_id_signer = Signer::new(MessageDigest::sha256(), &content_pkey)?;
+        key_id_signer.update(KEY_ID_MESSAGE)?;
+        Ok(EncryptionKey {
+            cipher_key: cipher_key.to_vec(),
+            content_key: content_key.to_vec(),
+            parameters: EncryptionParameters {
+                cipher: CIPHER.to_string(),
+                kdf: KDF.to_string(),
+                iterations,
+                salt: encode_hex(salt),
+                key_id: encode_hex(&key_id_signer.sign_to_vec()?[..8]),
+            },
+        })
+    }
+
+    /// Returns the parameters to derive this `EncryptionKey` again.
+    pub fn parameters(&self) -> &EncryptionParameters {
+        &self.parameters
+    }
+
+    /// Returns a hexadecimal identifier of the content, which is equal for equal content,
+    /// but cannot be computed without the key.
+    ///
+    /// # Parameters
+    ///
+    /// * `input` - the content to identify
+    pub fn content_id<R: Read>(&self, input: &mut R) -> Result<String, HomeworkError> {
+        let content_pkey = PKey::hmac(&self.content_key)?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &content_pkey)?;
+        std::io::copy(input, &mut signer)?;
+        Ok(encode_hex(&signer.sign_to_vec()?))
+    }
+
+    /// Encrypts the content and writes the nonce, the encrypted content and the
+    /// authentication tag.
+    ///
+    /// # Parameters
+    ///
+    /// * `input` - the content to encrypt
+    /// * `output` - the destination of the encrypted content
+    pub fn encrypt<R: Read, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+    ) -> Result<(), HomeworkError> {
+        let mut nonce = [0; NONCE_LENGTH];
+        rand_bytes(&mut nonce)?;
+        let mut crypter =
+            Crypter::new(Cipher::aes_256_gcm(), Mode::Encrypt, &self.cipher_key, Some(&nonce))?;
+        output.write_all(&nonce)?;
+        let mut buffer = vec![0; CHUNK_LENGTH];
+        let mut encrypted = vec![0; CHUNK_LENGTH + Cipher::aes_256_gcm().block_size()];
+        loop {
+            let read = input.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            let length = crypter.update(&buffer[..read], &mut encrypted)?;
+            output.write_all(&encrypted[..length])?;
+        }
+        let length = crypter.finalize(&mut encrypted)?;
+        output.write_all(&encrypted[..length])?;
+        let mut tag = [0; TAG_LENGTH];
+        crypter.get_tag(&mut tag)?;
+        output.write_all(&tag)?;
+        Ok(())
+    }
+
+    /// Decrypts content written by [`EncryptionKey::encrypt`]. Fails if the content
+    /// was modified or encrypted with another key. The output must be discarded then,
+    /// as the content is only authenticated after it has been written completely.
+    ///
+    /// # Parameters
+    ///
+    /// * `input` - the encrypted content
+    /// * `output` - the destination of the decrypted content
+    pub fn decrypt<R: Read + Seek, W: Write>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+    ) -> Result<(), HomeworkError> {
+        let length = input.seek(SeekFrom::End(0))?;
+        let mut remaining = length
+            .checked_sub((NONCE_LENGTH + TAG_LENGTH) as u64)
+            .ok_or_else(|| damaged_content("The content is too short."))?;
+        let mut tag = [0; TAG_LENGTH];
+        input.seek(SeekFrom::End(-(TAG_LENGTH as i64)))?;
+        input.read_exact(&mut tag)?;
+        let mut nonce = [0; NONCE_LENGTH];
+        input.seek(SeekFrom::Start(0))?;
+        input.read_exact(&mut nonce)?;
+        let mut crypter =
+            Crypter::new(Cipher::aes_256_gcm(), Mode::Decrypt, &self.cipher_key, Some(&nonce))?;
+        crypter.set_tag(&tag)?;
+        let mut buffer = vec![0; CHUNK_LENGTH];
+        let mut decrypted = vec![0; CHUNK_LENGTH + Cipher::aes_256_gcm().block_size()];
+        while remaining > 0 {
+            let chunk_length = remaining.min(CHUNK_LENGTH as u64) as usize;
+            input.read_exact(&mut buffer[..chunk_length])?;
+            let length = crypter.update(&buffer[..chunk_length], &mut decrypted)?;
+            output.write_all(&decrypted[..length])?;
+            remaining -= chunk_length as u64;
+        }
+        let length = crypter.finalize(&mut decrypted).map_err(damaged_content)?;
+        output.write_all(&decrypted[..length])?;
+        Ok(())
+    }
+}
+
+/// Returns the bytes as lowercase hexadecimal string.
+///
+/// # Parameters
+///
+/// * `bytes` - the bytes to encode
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Returns the bytes of a hexadecimal string or `None` if the string is not hexadecimal.
+///
+/// # Parameters
+///
+/// * `hex` - the string to decode
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+        .collect()
+}
+
+/// Creates the error for encrypted content that cannot be decrypted.
+///
+/// # Parameters
+///
+/// * `reason` - the reason the content cannot be decrypted
+fn damaged_content<T: std::fmt::Display>(reason: T) -> HomeworkError {
+    HomeworkError::BadRequestError(InternalError::new(
+        "Damaged encrypted content",
+        format!("The encrypted content cannot be decrypted: {}", reason),
+        "The encrypted content is damaged or was encrypted with a different key.",
+    ))
+}
+
+#[cfg(test)]
+mod test;
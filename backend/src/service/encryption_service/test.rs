@@ -0,0 +1,121 @@
+use std::io::Cursor;
+
+use super::*;
+
+/// Derives a key quickly for tests.
+fn derive_key(secret: &[u8]) -> EncryptionKey {
+    EncryptionKey::derive(secret, 1000, b"0123456789abcdef").unwrap()
+}
+
+#[test]
+/// Tests if encrypted content can be decrypted again, also if it spans multiple chunks.
+fn test_encrypt_decrypt() {
+    let key = derive_key(b"passphrase");
+    for content in [
+        Vec::new(),
+        b"receipt".to_vec(),
+        vec![7; 3 * CHUNK_LENGTH + 5],
+    ] {
+        let mut encrypted = Vec::new();
+        key.encrypt(&mut content.as_slice(), &mut encrypted)
+            .unwrap();
+        assert_eq!(encrypted.len(), content.len() + NONCE_LENGTH + TAG_LENGTH);
+        if !content.is_empty() {
+            assert!(!encrypted
+                .windows(content.len())
+                .any(|window| window == content));
+        }
+
+        let mut decrypted = Vec::new();
+        key.decrypt(&mut Cursor::new(encrypted), &mut decrypted)
+            .unwrap();
+        assert_eq!(decrypted, content);
+    }
+}
+
+#[test]
+/// Tests if modified content or content encrypted with another key is rejected.
+fn test_decrypt_damaged() {
+    let key = derive_key(b"passphrase");
+    let mut encrypted = Vec::new();
+    key.encrypt(&mut b"receipt".as_slice(), &mut encrypted)
+        .unwrap();
+
+    let mut modified = encrypted.clone();
+    modified[NONCE_LENGTH] ^= 1;
+    assert!(matches!(
+        key.decrypt(&mut Cursor::new(modified), &mut Vec::new()),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    assert!(matches!(
+        derive_key(b"other").decrypt(&mut Cursor::new(encrypted), &mut Vec::new()),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    assert!(matches!(
+        key.decrypt(&mut Cursor::new(vec![0; 10]), &mut Vec::new()),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+}
+
+#[test]
+/// Tests if keys are derived again from their parameters and a wrong secret is detected.
+fn test_from_parameters() {
+    let key = derive_key(b"passphrase");
+    let parameters = key.parameters().clone();
+    assert_eq!(parameters.cipher, "aes-256-gcm");
+    assert_eq!(parameters.kdf, "pbkdf2-hmac-sha256");
+    assert_eq!(parameters.iterations, 1000);
+    assert_eq!(parameters.salt, "30313233343536373839616263646566");
+
+    let derived = EncryptionKey::from_parameters(b"passphrase", &parameters).unwrap();
+    assert_eq!(derived.cipher_key, key.cipher_key);
+    assert!(matches!(
+        EncryptionKey::from_parameters(b"other", &parameters),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    let unsupported = [
+        EncryptionParameters {
+            cipher: "aes-128-cbc".to_string(),
+            ..parameters.clone()
+        },
+        EncryptionParameters {
+            iterations: 0,
+            ..parameters.clone()
+        },
+        EncryptionParameters {
+            iterations: u32::MAX,
+            ..parameters
+        },
+    ];
+    for parameters in unsupported {
+        assert!(matches!(
+            EncryptionKey::from_parameters(b"passphrase", &parameters),
+            Err(HomeworkError::BadRequestError(_))
+        ));
+    }
+}
+
+#[test]
+/// Tests if content IDs are equal for equal content and depend on the key.
+fn test_content_id() {
+    let key = derive_key(b"passphrase");
+    let id = key.content_id(&mut b"receipt".as_slice()).unwrap();
+    assert_eq!(id.len(), 64);
+    assert_eq!(key.content_id(&mut b"receipt".as_slice()).unwrap(), id);
+    assert_ne!(key.content_id(&mut b"other".as_slice()).unwrap(), id);
+    assert_ne!(
+        derive_key(b"other")
+            .content_id(&mut b"receipt".as_slice())
+            .unwrap(),
+        id
+    );
+}
+
+#[test]
+/// Tests if the hexadecimal encoding can be decoded again.
+fn test_hex() {
+    assert_eq!(encode_hex(&[0, 15, 255]), "000fff");
+    assert_eq!(decode_hex("000fff"), Some(vec![0, 15, 255]));
+    assert_eq!(decode_hex("0g"), None);
+    assert_eq!(decode_hex("abc"), None);
+}
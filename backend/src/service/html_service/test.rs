@@ -0,0 +1,19 @@
+use super::*;
+
+#[test]
+/// Tests if the `escape_html` function escapes all characters with a special meaning.
+fn test_escape_html() {
+    assert_eq!(
+        escape_html("<a href=\"x\">Salt & 'pepper'</a>"),
+        "&lt;a href=&quot;x&quot;&gt;Salt &amp; &#39;pepper&#39;&lt;/a&gt;"
+    );
+}
+
+#[test]
+/// Tests if the `decode_html_entities` function decodes named and numeric entities.
+fn test_decode_html_entities() {
+    assert_eq!(
+        decode_html_entities("a &lt;b&gt; &#233;&#xE9; &unknown; & c&amp;"),
+        "a <b> éé &unknown; & c&"
+    );
+}
@@ -0,0 +1,420 @@
+//! The `migration_service` module versions the database schema with `PRAGMA user_version`
+//! and migrates databases created by earlier versions of the application step by step.
+
+use log::info;
+use rusqlite::Connection;
+
+use crate::{
+    application::{
+        config::Configuration,
+        error::{HomeworkError, InternalError},
+    },
+    service::{
+        person_service::migrate_person_references, search_service::synchronise_recipe_index,
+    },
+};
+
+/// The schema version of databases created before the schema was versioned.
+const UNVERSIONED: u32 = 0;
+
+/// A single step migrating the database schema to the next version.
+struct Migration {
+    /// The schema version after the migration.
+    version: u32,
+    /// A short description of the changes.
+    description: &'static str,
+    /// Applies the changes to the database.
+    apply: fn(&Connection, &Configuration) -> Result<(), HomeworkError>,
+}
+
+/// All migrations ordered by their version. New migrations must only be appended,
+/// as migrations that have already been applied are never executed again.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create the schema and complete the tables of unversioned databases",
+        apply: create_schema,
+    },
+    Migration {
+        version: 2,
+        description: "Map the free-text person names of payments and budgets onto persons",
+        apply: |connection, _| migrate_person_references(connection),
+    },
+    Migration {
+        version: 3,
+        description: "Index the recipes stored before the search index was added",
+        apply: |connection, _| Ok(synchronise_recipe_index(connection)?),
+    },
+];
+
+/// Returns the schema version of the database.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+pub fn schema_version(connection: &Connection) -> Result<u32, HomeworkError> {
+    Ok(connection.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+/// Returns the schema version all migrations lead to.
+pub fn latest_schema_version() -> u32 {
+    latest_version(MIGRATIONS)
+}
+
+/// Checks if the database contains data that will be migrated, so it should be
+/// backed up before. Fails if the database has a newer schema than known.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+pub fn requires_backup(connection: &Connection) -> Result<bool, HomeworkError> {
+    let version = validate_schema_version(connection, MIGRATIONS)?;
+    let contains_tables: bool = connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table')",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(version < latest_schema_version() && contains_tables)
+}
+
+/// Applies all migrations the database has not been migrated with yet. Each migration
+/// runs in its own transaction together with the update of the schema version,
+/// so a failing migration leaves the database at the previous version.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+/// * `configuration` - the [`Configuration`] providing default values
+pub fn migrate_database(
+    connection: &Connection,
+    configuration: &Configuration,
+) -> Result<(), HomeworkError> {
+    apply_migrations(connection, configuration, MIGRATIONS)
+}
+
+/// Applies all pending migrations of the specified list.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+/// * `configuration` - the [`Configuration`] providing default values
+/// * `migrations` - the migrations ordered by their version
+fn apply_migrations(
+    connection: &Connection,
+    configuration: &Configuration,
+    migrations: &[Migration],
+) -> Result<(), HomeworkError> {
+    let version = validate_schema_version(connection, migrations)?;
+    for migration in migrations
+        .iter()
+        .filter(|migration| migration.version > version)
+    {
+        info!(
+            "Migrating the database schema to version {}: {}",
+            migration.version, migration.description
+        );
+        let transaction = connection.unchecked_transaction()?;
+        (migration.apply)(&transaction, configuration)?;
+        transaction.pragma_update(None, "user_version", migration.version)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+/// Returns the schema version of the database if it is known to the migrations.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+/// * `migrations` - the migrations ordered by their version
+fn validate_schema_version(
+    connection: &Connection,
+    migrations: &[Migration],
+) -> Result<u32, HomeworkError> {
+    let version = schema_version(connection)?;
+    let latest_version = latest_version(migrations);
+    if version > latest_version {
+        return Err(HomeworkError::InternalServerError(InternalError::new(
+            "Unsupported database schema",
+            format!(
+                "The database schema version {} is newer than the latest known version {}.",
+                version, latest_version
+            ),
+            "The database was created by a newer version of the application.",
+        )));
+    }
+    Ok(version)
+}
+
+/// Returns the version of the last migration.
+///
+/// # Parameters
+///
+/// * `migrations` - the migrations ordered by their version
+fn latest_version(migrations: &[Migration]) -> u32 {
+    migrations
+        .last()
+        .map(|migration| migration.version)
+        .unwrap_or(UNVERSIONED)
+}
+
+/// Creates all tables and indices. Unversioned databases created by earlier versions
+/// of the application may already contain the tables, but lack columns added later.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+/// * `configuration` - the [`Configuration`] providing the base currency of existing payments
+fn create_schema(
+    connection: &Connection,
+    configuration: &Configuration,
+) -> Result<(), HomeworkError> {
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS attachment (
+            id              TEXT PRIMARY KEY,
+            name            TEXT NOT NULL,
+            creation_time   TEXT NOT NULL
+                )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS recipe (
+            id              BLOB PRIMARY KEY,
+            title           TEXT NOT NULL,
+            instructions    TEXT NOT NULL,                    
+            reference       TEXT NOT NULL,
+            rating          RATING NOT NULL,
+            creation_time   TEXT NOT NULL,
+            thumbnail       BLOB,
+            servings        INTEGER,
+            FOREIGN KEY (thumbnail) REFERENCES attachment (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE SET NULL
+        )",
+        [],
+    )?;
+    add_column_if_missing(connection, "recipe", "servings", "INTEGER")?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS ingredient (
+                id                              BLOB PRIMARY KEY,
+                amount                          TEXT NOT NULL,
+                unit                            TEXT NOT NULL,                    
+                text                            TEXT NOT NULL,
+                creation_time                   TEXT NOT NULL,
+                recipe_reference                BLOB,
+                recipe_id                       BLOB NOT NULL,
+                ordering                        INTEGER NOT NULL,
+                filter_text                     TEXT,
+                density                         TEXT,
+                FOREIGN KEY (recipe_id) REFERENCES recipe (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE,
+                FOREIGN KEY (recipe_reference) REFERENCES recipe (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE SET NULL
+                  )",
+        [],
+    )?;
+    add_column_if_missing(connection, "ingredient", "filter_text", "TEXT")?;
+    add_column_if_missing(connection, "ingredient", "density", "TEXT")?;
+    connection.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS recipe_search USING fts5 (
+                recipe_id UNINDEXED,
+                title,
+                instructions,
+                reference,
+                ingredients,
+                tags,
+                tokenize = 'unicode61 remove_diacritics 2'
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS meal (
+                id                              BLOB PRIMARY KEY,
+                date                            TEXT NOT NULL,
+                slot                            TEXT NOT NULL,
+                recipe_id                       BLOB NOT NULL,
+                servings                        INTEGER,
+                creation_time                   TEXT NOT NULL,
+                FOREIGN KEY (recipe_id) REFERENCES recipe (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS shopping_list (
+                id                              BLOB PRIMARY KEY,
+                title                           TEXT NOT NULL,
+                creation_time                   TEXT NOT NULL
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS shopping_list_item (
+                id                              BLOB PRIMARY KEY,
+                shopping_list_id                BLOB NOT NULL,
+                name                            TEXT NOT NULL,
+                amount                          TEXT NOT NULL,
+                unit                            TEXT NOT NULL,
+                checked                         INTEGER NOT NULL DEFAULT 0,
+                ordering                        INTEGER NOT NULL,
+                FOREIGN KEY (shopping_list_id) REFERENCES shopping_list (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS payment (
+                id                              BLOB PRIMARY KEY,
+                target                          TEXT NOT NULL,
+                note                            TEXT NOT NULL,
+                paid                            TEXT NOT NULL,                    
+                involved                        TEXT NOT NULL,
+                payment_type                    TEXT NOT NULL,
+                creation_time                   TEXT NOT NULL,
+                split                           TEXT,
+                currency                        TEXT NOT NULL,
+                transfer                        INTEGER NOT NULL DEFAULT 0
+                  )",
+        [],
+    )?;
+    add_column_if_missing(connection, "payment", "split", "TEXT")?;
+    add_column_if_missing(
+        connection,
+        "payment",
+        "currency",
+        &format!("TEXT NOT NULL DEFAULT '{}'", configuration.base_currency()),
+    )?;
+    add_column_if_missing(connection, "payment", "transfer", "INTEGER NOT NULL DEFAULT 0")?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS exchange_rate (
+                id                              BLOB PRIMARY KEY,
+                source_currency                 TEXT NOT NULL,
+                target_currency                 TEXT NOT NULL,
+                rate                            TEXT NOT NULL,
+                valid_from                      TEXT NOT NULL,
+                creation_time                   TEXT NOT NULL
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS person (
+                id                              BLOB PRIMARY KEY,
+                display_name                    TEXT NOT NULL,
+                aliases                         TEXT NOT NULL,
+                active                          INTEGER NOT NULL DEFAULT 1,
+                creation_time                   TEXT NOT NULL
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS budget (
+                id                              BLOB PRIMARY KEY,
+                tag                             TEXT NOT NULL,
+                period                          TEXT NOT NULL,
+                spending_limit                  TEXT NOT NULL,
+                person                          TEXT,
+                creation_time                   TEXT NOT NULL
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS attachment_recipe_mapping (
+                id                          INTEGER PRIMARY KEY,
+                recipe_id                   BLOB NOT NULL,
+                attachment_id               BLOB NOT NULL,
+                FOREIGN KEY (recipe_id)     REFERENCES recipe (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE,
+                FOREIGN KEY (attachment_id) REFERENCES attachment (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS tag_recipe_mapping (
+                id                      INTEGER PRIMARY KEY,
+                tag                     TEXT NOT NULL,
+                recipe_id               BLOB NOT NULL,
+                FOREIGN KEY (recipe_id) REFERENCES recipe (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS attachment_payment_mapping (
+                id                          INTEGER PRIMARY KEY,
+                payment_id                  BLOB NOT NULL,
+                attachment_id               BLOB NOT NULL,
+                FOREIGN KEY (payment_id)       REFERENCES payment (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE,
+                FOREIGN KEY (attachment_id) REFERENCES attachment (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE
+                  )",
+        [],
+    )?;
+    connection.execute(
+        "CREATE TABLE IF NOT EXISTS tag_payment_mapping (
+                id                          INTEGER PRIMARY KEY,
+                tag                         TEXT NOT NULL,
+                payment_id                  BLOB NOT NULL,
+                FOREIGN KEY (payment_id)    REFERENCES payment (id) 
+                    ON UPDATE CASCADE
+                    ON DELETE CASCADE
+                  )",
+        [],
+    )?;
+    // Index the references used to load the relations of recipes and payments
+    // and to resolve sub-recipes.
+    for (table, column) in [
+        ("ingredient", "recipe_id"),
+        ("ingredient", "recipe_reference"),
+        ("attachment_recipe_mapping", "recipe_id"),
+        ("tag_recipe_mapping", "recipe_id"),
+        ("attachment_payment_mapping", "payment_id"),
+        ("tag_payment_mapping", "payment_id"),
+    ] {
+        connection.execute(
+            &format!(
+                "CREATE INDEX IF NOT EXISTS {table}_{column} ON {table} ({column})",
+                table = table,
+                column = column
+            ),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// Adds a column to an existing table if the column is not yet present.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+/// * `table` - the table to add the column to
+/// * `column` - the name of the column
+/// * `definition` - the type and constraints of the column
+fn add_column_if_missing(
+    connection: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), HomeworkError> {
+    let mut stmt = connection
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))?;
+    if !stmt.exists([column])? {
+        connection
+            .execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test;
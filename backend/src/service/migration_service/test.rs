@@ -0,0 +1,173 @@
+use uuid::Uuid;
+
+use super::*;
+
+/// Returns if the table contains the column.
+fn has_column(connection: &Connection, table: &str, column: &str) -> bool {
+    connection
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))
+        .unwrap()
+        .exists([column])
+        .unwrap()
+}
+
+/// Returns if the database contains the table.
+fn has_table(connection: &Connection, table: &str) -> bool {
+    connection
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")
+        .unwrap()
+        .exists([table])
+        .unwrap()
+}
+
+#[test]
+/// Tests if the `migrate_database` function creates the latest schema on an empty database.
+fn test_migrate_database_empty() {
+    let connection = Connection::open_in_memory().unwrap();
+    assert!(!requires_backup(&connection).unwrap());
+
+    migrate_database(&connection, &Configuration::default()).unwrap();
+    assert_eq!(schema_version(&connection).unwrap(), latest_schema_version());
+    assert!(has_table(&connection, "recipe"));
+    assert!(has_column(&connection, "ingredient", "filter_text"));
+    assert!(!requires_backup(&connection).unwrap());
+
+    // Migrating again does not change anything.
+    migrate_database(&connection, &Configuration::default()).unwrap();
+    assert_eq!(schema_version(&connection).unwrap(), latest_schema_version());
+}
+
+#[test]
+/// Tests if the `migrate_database` function completes the tables of unversioned databases
+/// and migrates their person names and recipes.
+fn test_migrate_database_unversioned() {
+    let connection = Connection::open_in_memory().unwrap();
+    connection
+        .execute_batch(
+            r#"CREATE TABLE ingredient (
+                id BLOB PRIMARY KEY,
+                amount TEXT NOT NULL,
+                unit TEXT NOT NULL,
+                text TEXT NOT NULL,
+                creation_time TEXT NOT NULL,
+                recipe_reference BLOB,
+                recipe_id BLOB NOT NULL,
+                ordering INTEGER NOT NULL
+            );
+            CREATE TABLE recipe (
+                id BLOB PRIMARY KEY,
+                title TEXT NOT NULL,
+                instructions TEXT NOT NULL,
+                reference TEXT NOT NULL,
+                rating RATING NOT NULL,
+                creation_time TEXT NOT NULL,
+                thumbnail BLOB
+            );
+            INSERT INTO recipe VALUES (
+                x'00000000000000000000000000000002', 'Soup', '', '', 0, '2022-01-01T00:00:00Z', NULL
+            );
+            CREATE TABLE payment (
+                id BLOB PRIMARY KEY,
+                target TEXT NOT NULL,
+                note TEXT NOT NULL,
+                paid TEXT NOT NULL,
+                involved TEXT NOT NULL,
+                payment_type TEXT NOT NULL,
+                creation_time TEXT NOT NULL
+            );
+            INSERT INTO payment VALUES (
+                x'00000000000000000000000000000001', 'Shop', '', '{"Anna": "10"}', '{"Anna": "10"}',
+                '{"OneOff": {"start": "2022-01-01T00:00:00Z"}}', '2022-01-01T00:00:00Z'
+            );"#,
+        )
+        .unwrap();
+    assert!(requires_backup(&connection).unwrap());
+
+    migrate_database(&connection, &Configuration::default()).unwrap();
+    assert_eq!(schema_version(&connection).unwrap(), latest_schema_version());
+    for column in ["filter_text", "density"] {
+        assert!(has_column(&connection, "ingredient", column));
+    }
+    let currency: String = connection
+        .query_row("SELECT currency FROM payment", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(currency, "EUR");
+    let (person_id, display_name): (String, String) = connection
+        .query_row("SELECT id, display_name FROM person", [], |row| {
+            Ok((row.get::<_, Uuid>(0)?.to_string(), row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(display_name, "Anna");
+    let paid: String = connection
+        .query_row("SELECT paid FROM payment", [], |row| row.get(0))
+        .unwrap();
+    assert!(paid.contains(&person_id));
+    let indexed_title: String = connection
+        .query_row("SELECT title FROM recipe_search", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(indexed_title, "Soup");
+    assert!(!requires_backup(&connection).unwrap());
+}
+
+#[test]
+/// Tests if databases of a newer schema version are rejected.
+fn test_migrate_database_newer() {
+    let connection = Connection::open_in_memory().unwrap();
+    connection
+        .pragma_update(None, "user_version", latest_schema_version() + 1)
+        .unwrap();
+
+    assert!(requires_backup(&connection).is_err());
+    assert!(migrate_database(&connection, &Configuration::default()).is_err());
+    assert_eq!(schema_version(&connection).unwrap(), latest_schema_version() + 1);
+}
+
+#[test]
+/// Tests if the `apply_migrations` function keeps all successful migrations and
+/// rolls back a failing migration completely.
+fn test_apply_migrations_failure() {
+    let connection = Connection::open_in_memory().unwrap();
+    let migrations = [
+        Migration {
+            version: 1,
+            description: "First",
+            apply: |connection, _| {
+                connection.execute("CREATE TABLE first (id INTEGER)", [])?;
+                Ok(())
+            },
+        },
+        Migration {
+            version: 2,
+            description: "Failing",
+            apply: |connection, _| {
+                connection.execute("CREATE TABLE second (id INTEGER)", [])?;
+                connection.execute("INSERT INTO missing VALUES (1)", [])?;
+                Ok(())
+            },
+        },
+    ];
+
+    assert!(apply_migrations(&connection, &Configuration::default(), &migrations).is_err());
+    assert_eq!(schema_version(&connection).unwrap(), 1);
+    assert!(has_table(&connection, "first"));
+    assert!(!has_table(&connection, "second"));
+
+    let migrations = [
+        Migration {
+            version: 1,
+            description: "First",
+            apply: |_, _| panic!("The first migration must not be applied again."),
+        },
+        Migration {
+            version: 2,
+            description: "Fixed",
+            apply: |connection, _| {
+                connection.execute("CREATE TABLE second (id INTEGER)", [])?;
+                Ok(())
+            },
+        },
+    ];
+    apply_migrations(&connection, &Configuration::default(), &migrations).unwrap();
+    assert_eq!(schema_version(&connection).unwrap(), 2);
+    assert!(has_table(&connection, "second"));
+}
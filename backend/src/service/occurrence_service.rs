@@ -0,0 +1,244 @@
+//! The `occurrence_service` module expands recurring [`PaymentType`]s into the
+//! dated instances at which a [`Payment`] is due.
+
+use std::collections::HashMap;
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Datelike, Duration, Months, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entity::payment::{Payment, PaymentType};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A single dated instance of a [`Payment`].
+pub struct Occurrence {
+    payment_id: Uuid,
+    target: String,
+    date: DateTime<Utc>,
+    paid: HashMap<String, BigDecimal>,
+    involved: HashMap<String, BigDecimal>,
+    currency: String,
+    tags: Vec<String>,
+}
+
+impl Occurrence {
+    /// Expands the specified [`Payment`] into all its occurrences within the
+    /// specified time window.
+    ///
+    /// # Parameters
+    ///
+    /// * `payment` - the payment to expand
+    /// * `from` - the inclusive start of the time window
+    /// * `to` - the inclusive end of the time window
+    pub fn from_payment(payment: &Payment, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<Self> {
+        occurrences(payment.payment_type(), from, to)
+            .into_iter()
+            .map(|date| Occurrence {
+                payment_id: payment.id(),
+                target: payment.target().to_string(),
+                date,
+                paid: payment.paid().clone(),
+                involved: payment.involved().clone(),
+                currency: payment.currency().to_string(),
+                tags: payment.tags().clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the date of this `Occurrence`.
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+}
+
+/// Returns all dates of the specified [`PaymentType`] within the specified time window
+/// in ascending order.
+/// Monthly and annual payments keep the day of their start date. If a month is too short,
+/// the last day of the month is used instead, so a payment starting on the 31st of January
+/// falls on the last day of February and on the 31st of March again.
+///
+/// # Parameters
+///
+/// * `payment_type` - the payment type to expand
+/// * `from` - the inclusive start of the time window
+/// * `to` - the inclusive end of the time window
+pub fn occurrences(
+    payment_type: &PaymentType,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let (distance, start, end) = match payment_type {
+        PaymentType::OneOff { start } => {
+            return if *start >= from && *start <= to {
+                vec![*start]
+            } else {
+                Vec::new()
+            };
+        },
+        PaymentType::Daily {
+            distance,
+            start,
+            end,
+        }
+        | PaymentType::Weekly {
+            distance,
+            start,
+            end,
+        }
+        | PaymentType::Monthly {
+            distance,
+            start,
+            end,
+        }
+        | PaymentType::Annualy {
+            distance,
+            start,
+            end,
+        } => (*distance, *start, *end),
+    };
+    let last = std::cmp::min(end, to);
+    let mut dates = Vec::new();
+    if distance == 0 {
+        // A payment without a distance between its occurrences only occurs once.
+        if start >= from && start <= last {
+            dates.push(start);
+        }
+        return dates;
+    }
+    let mut index = first_index_candidate(payment_type, distance, start, from);
+    while let Some(date) = nth_occurrence(payment_type, distance, start, index) {
+        if date > last {
+            break;
+        }
+        if date >= from {
+            dates.push(date);
+        }
+        index += 1;
+    }
+    dates
+}
+
+/// Returns the number of dates of the specified [`PaymentType`] within the specified
+/// time window like [`occurrences`] without expanding them, so the work does not grow
+/// with the length of the time window.
+///
+/// # Parameters
+///
+/// * `payment_type` - the payment type to count
+/// * `from` - the inclusive start of the time window
+/// * `to` - the inclusive end of the time window
+pub fn occurrence_count(payment_type: &PaymentType, from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+    let (distance, start, end) = match payment_type {
+        PaymentType::Daily {
+            distance,
+            start,
+            end,
+        }
+        | PaymentType::Weekly {
+            distance,
+            start,
+            end,
+        }
+        | PaymentType::Monthly {
+            distance,
+            start,
+            end,
+        }
+        | PaymentType::Annualy {
+            distance,
+            start,
+            end,
+        } if *distance > 0 => (*distance, *start, *end),
+        // One-off payments and payments without a distance occur at most once.
+        _ => return occurrences(payment_type, from, to).len() as u64,
+    };
+    let last = std::cmp::min(end, to);
+    if last < from {
+        return 0;
+    }
+    let first_index = index_at_or_after(payment_type, distance, start, from);
+    let end_index = index_at_or_after(
+        payment_type,
+        distance,
+        start,
+        last.checked_add_signed(Duration::nanoseconds(1))
+            .unwrap_or(last),
+    );
+    end_index.saturating_sub(first_index)
+}
+
+/// Returns the index of the first occurrence at or after the specified date or
+/// the index of the first occurrence out of the representable range.
+fn index_at_or_after(
+    payment_type: &PaymentType,
+    distance: u64,
+    start: DateTime<Utc>,
+    date: DateTime<Utc>,
+) -> u64 {
+    let mut index = first_index_candidate(payment_type, distance, start, date);
+    while nth_occurrence(payment_type, distance, start, index).is_some_and(|nth| nth < date) {
+        index += 1;
+    }
+    index
+}
+
+/// Returns the date of the occurrence with the specified index or `None` if the date
+/// is out of the representable range.
+fn nth_occurrence(
+    payment_type: &PaymentType,
+    distance: u64,
+    start: DateTime<Utc>,
+    index: u64,
+) -> Option<DateTime<Utc>> {
+    let steps = distance.checked_mul(index)?;
+    match payment_type {
+        PaymentType::OneOff { .. } => (index == 0).then_some(start),
+        PaymentType::Daily { .. } => {
+            start.checked_add_signed(Duration::days(i64::try_from(steps).ok()?))
+        },
+        PaymentType::Weekly { .. } => {
+            start.checked_add_signed(Duration::weeks(i64::try_from(steps).ok()?))
+        },
+        PaymentType::Monthly { .. } => {
+            start.checked_add_months(Months::new(u32::try_from(steps).ok()?))
+        },
+        PaymentType::Annualy { .. } => {
+            start.checked_add_months(Months::new(u32::try_from(steps.checked_mul(12)?).ok()?))
+        },
+    }
+}
+
+/// Returns an occurrence index that is guaranteed to not lie after the first occurrence
+/// within the time window starting at `from`.
+/// This allows to skip occurrences long before the time window without iterating them.
+fn first_index_candidate(
+    payment_type: &PaymentType,
+    distance: u64,
+    start: DateTime<Utc>,
+    from: DateTime<Utc>,
+) -> u64 {
+    if from <= start {
+        return 0;
+    }
+    let elapsed_units = match payment_type {
+        PaymentType::OneOff { .. } => 0,
+        PaymentType::Daily { .. } => (from - start).num_days(),
+        PaymentType::Weekly { .. } => (from - start).num_weeks(),
+        PaymentType::Monthly { .. } => months_between(start, from),
+        PaymentType::Annualy { .. } => i64::from(from.year() - start.year()),
+    };
+    // Step back one occurrence to account for partial periods.
+    u64::try_from(elapsed_units)
+        .map(|units| (units / distance).saturating_sub(1))
+        .unwrap_or(0)
+}
+
+/// Returns the number of calendar months between the two dates ignoring the day of the month.
+fn months_between(start: DateTime<Utc>, end: DateTime<Utc>) -> i64 {
+    i64::from(end.year() - start.year()) * 12 + i64::from(end.month()) - i64::from(start.month())
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,160 @@
+use chrono::TimeZone;
+
+use super::*;
+
+/// Creates a UTC date at midnight.
+fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+}
+
+#[test]
+/// Tests if one-off payments only occur within the time window.
+fn test_occurrences_one_off() {
+    let payment_type = PaymentType::OneOff {
+        start: date(2022, 3, 15),
+    };
+    assert_eq!(
+        occurrences(&payment_type, date(2022, 3, 1), date(2022, 3, 31)),
+        vec![date(2022, 3, 15)]
+    );
+    assert!(occurrences(&payment_type, date(2022, 4, 1), date(2022, 4, 30)).is_empty());
+}
+
+#[test]
+/// Tests if daily and weekly payments respect their distance, window and end.
+fn test_occurrences_daily_weekly() {
+    let daily = PaymentType::Daily {
+        distance: 10,
+        start: date(2022, 1, 1),
+        end: date(2022, 3, 1),
+    };
+    assert_eq!(
+        occurrences(&daily, date(2022, 2, 1), date(2022, 12, 31)),
+        vec![date(2022, 2, 10), date(2022, 2, 20)]
+    );
+    let weekly = PaymentType::Weekly {
+        distance: 2,
+        start: date(2022, 1, 3),
+        end: date(2023, 1, 1),
+    };
+    assert_eq!(
+        occurrences(&weekly, date(2022, 1, 1), date(2022, 2, 1)),
+        vec![date(2022, 1, 3), date(2022, 1, 17), date(2022, 1, 31)]
+    );
+}
+
+#[test]
+/// Tests if monthly payments are clamped to the end of shorter months.
+fn test_occurrences_monthly_month_end() {
+    let payment_type = PaymentType::Monthly {
+        distance: 1,
+        start: date(2024, 1, 31),
+        end: date(2024, 12, 31),
+    };
+    assert_eq!(
+        occurrences(&payment_type, date(2024, 1, 1), date(2024, 4, 30)),
+        vec![
+            date(2024, 1, 31),
+            date(2024, 2, 29),
+            date(2024, 3, 31),
+            date(2024, 4, 30)
+        ]
+    );
+    assert_eq!(
+        occurrences(&payment_type, date(2024, 6, 1), date(2024, 6, 30)),
+        vec![date(2024, 6, 30)]
+    );
+}
+
+#[test]
+/// Tests if annual payments on leap days fall on the last day of February in other years.
+fn test_occurrences_annualy_leap_year() {
+    let payment_type = PaymentType::Annualy {
+        distance: 1,
+        start: date(2020, 2, 29),
+        end: date(2030, 1, 1),
+    };
+    assert_eq!(
+        occurrences(&payment_type, date(2023, 1, 1), date(2024, 12, 31)),
+        vec![date(2023, 2, 28), date(2024, 2, 29)]
+    );
+}
+
+#[test]
+/// Tests if a distance of zero results in a single occurrence.
+fn test_occurrences_zero_distance() {
+    let payment_type = PaymentType::Monthly {
+        distance: 0,
+        start: date(2022, 5, 5),
+        end: date(2023, 5, 5),
+    };
+    assert_eq!(
+        occurrences(&payment_type, date(2022, 1, 1), date(2023, 12, 31)),
+        vec![date(2022, 5, 5)]
+    );
+}
+
+#[test]
+/// Tests if the `occurrence_count` function counts the same occurrences as the
+/// `occurrences` function and handles long time windows.
+fn test_occurrence_count() {
+    let window_starts = [
+        date(2021, 12, 31),
+        date(2022, 1, 31),
+        date(2022, 2, 28),
+        date(2023, 6, 1),
+    ];
+    let window_ends = [
+        date(2021, 12, 1),
+        date(2022, 2, 28),
+        date(2022, 3, 31),
+        date(2030, 1, 1),
+    ];
+    let start = date(2022, 1, 31);
+    let end = date(2026, 1, 31);
+    for distance in [0, 1, 2, 3] {
+        for payment_type in [
+            PaymentType::OneOff { start },
+            PaymentType::Daily {
+                distance,
+                start,
+                end,
+            },
+            PaymentType::Weekly {
+                distance,
+                start,
+                end,
+            },
+            PaymentType::Monthly {
+                distance,
+                start,
+                end,
+            },
+            PaymentType::Annualy {
+                distance,
+                start,
+                end,
+            },
+        ] {
+            for from in window_starts {
+                for to in window_ends {
+                    assert_eq!(
+                        occurrence_count(&payment_type, from, to),
+                        occurrences(&payment_type, from, to).len() as u64,
+                        "{:?} from {} to {}",
+                        payment_type,
+                        from,
+                        to
+                    );
+                }
+            }
+        }
+    }
+    let daily = PaymentType::Daily {
+        distance: 1,
+        start: date(1000, 1, 1),
+        end: date(9999, 12, 31),
+    };
+    assert_eq!(occurrence_count(&daily, date(1000, 1, 1), date(1000, 12, 31)), 365);
+    assert!(occurrence_count(&daily, date(1000, 1, 1), date(2022, 1, 1)) > 370_000);
+}
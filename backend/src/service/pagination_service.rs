@@ -0,0 +1,159 @@
+//! The `pagination_service` module selects a single page of filtered and sorted
+//! entities from the database instead of whole tables.
+
+use rusqlite::{params_from_iter, types::ToSql, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::application::error::HomeworkError;
+
+/// The SQL limit selecting all rows.
+const LIMIT_ALL: i64 = -1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+/// The query parameters selecting a page of a list.
+pub struct Pagination {
+    /// The maximum number of entries of the page. All entries are returned if not specified.
+    limit: Option<u32>,
+    /// The number of entries to skip. Defaults to `0`.
+    offset: Option<u32>,
+}
+
+impl Pagination {
+    /// Returns the number of entries to skip.
+    pub fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+/// The direction entities are sorted in.
+pub enum SortDirection {
+    #[default]
+    #[serde(rename = "asc")]
+    Ascending,
+    #[serde(rename = "desc")]
+    Descending,
+}
+
+impl std::fmt::Display for SortDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SortDirection::Ascending => "ASC",
+                SortDirection::Descending => "DESC",
+            }
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A single page of a filtered list.
+pub struct Page<T> {
+    /// The entries of the page.
+    items: Vec<T>,
+    /// The number of entries matching the filters on all pages.
+    total: u64,
+    /// The number of entries skipped before the page.
+    offset: u32,
+    /// The maximum number of entries of the page if limited.
+    limit: Option<u32>,
+}
+
+impl<T> Page<T> {
+    /// Returns the entries of this `Page`.
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// Returns the entries of this `Page` for modification.
+    pub fn items_mut(&mut self) -> &mut Vec<T> {
+        &mut self.items
+    }
+}
+
+#[derive(Default)]
+/// The filter conditions of a query, which are combined with `AND`.
+pub struct Conditions {
+    clauses: Vec<String>,
+    parameters: Vec<Box<dyn ToSql>>,
+}
+
+impl Conditions {
+    /// Adds a condition with a single anonymous parameter `?`.
+    ///
+    /// # Parameters
+    ///
+    /// * `clause` - the SQL condition
+    /// * `parameter` - the value of the parameter
+    pub fn push<T: ToSql + 'static>(&mut self, clause: &str, parameter: T) {
+        self.clauses.push(clause.to_string());
+        self.parameters.push(Box::new(parameter));
+    }
+
+    /// Returns the `WHERE` clause of all conditions or an empty string if there are none.
+    fn where_clause(&self) -> String {
+        if self.clauses.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", self.clauses.join(" AND "))
+        }
+    }
+}
+
+/// Selects a page of the table.
+///
+/// # Parameters
+///
+/// * `select` - the `SELECT ... FROM ...` part of the query
+/// * `table` - the table to count the matching rows of
+/// * `conditions` - the filter conditions
+/// * `order` - the `ORDER BY` expression, which must be unique to page consistently
+/// * `pagination` - the page to select
+/// * `connection` - the database connection
+/// * `map` - the conversion of a row into an entity
+pub fn select_page<T, E, F>(
+    select: &str,
+    table: &str,
+    conditions: &Conditions,
+    order: &str,
+    pagination: &Pagination,
+    connection: &Connection,
+    map: F,
+) -> Result<Page<T>, HomeworkError>
+where
+    F: FnMut(&Row<'_>) -> Result<T, E>,
+    E: From<rusqlite::Error>,
+    HomeworkError: From<E>,
+{
+    let where_clause = conditions.where_clause();
+    let total: u64 = connection.query_row(
+        &format!("SELECT COUNT(*) FROM {} {}", table, where_clause),
+        params_from_iter(conditions.parameters.iter()),
+        |row| row.get(0),
+    )?;
+    let mut stmt = connection.prepare(&format!(
+        "{} {} ORDER BY {} LIMIT {} OFFSET {}",
+        select,
+        where_clause,
+        order,
+        pagination.limit.map(i64::from).unwrap_or(LIMIT_ALL),
+        pagination.offset()
+    ))?;
+    let rows = stmt.query_and_then(params_from_iter(conditions.parameters.iter()), map)?;
+    let mut items = Vec::new();
+    for item in rows {
+        items.push(item?);
+    }
+    Ok(Page {
+        items,
+        total,
+        offset: pagination.offset(),
+        limit: pagination.limit,
+    })
+}
+
+#[cfg(test)]
+mod test;
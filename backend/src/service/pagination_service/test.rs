@@ -0,0 +1,160 @@
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    application::config::Configuration,
+    entity::{
+        attachment::Attachment,
+        payment::{test_builder::date, Payment, PaymentType},
+        recipe::Recipe,
+    },
+};
+
+use super::*;
+
+/// Deserializes query parameters from their JSON representation.
+fn query<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+    serde_json::from_value(value).unwrap()
+}
+
+/// Returns the IDs of all recipes of the page.
+fn recipe_ids(page: &Page<Recipe>) -> Vec<Uuid> {
+    page.items
+        .iter()
+        .map(|recipe| {
+            serde_json::to_value(recipe).unwrap()["id"]
+                .as_str()
+                .unwrap()
+                .parse()
+                .unwrap()
+        })
+        .collect()
+}
+
+/// Returns the targets of all payments of the page.
+fn payment_targets(page: &Page<Payment>) -> Vec<&str> {
+    page.items.iter().map(Payment::target).collect()
+}
+
+#[test]
+/// Tests if recipes are filtered, sorted and paged with the total count of all pages.
+fn test_select_page_recipes() {
+    let connection = Configuration::in_memory_database_connection();
+    let (apple, banana, cherry) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+    for (id, title, rating) in [
+        (banana, "banana", 4),
+        (cherry, "Cherry", 2),
+        (apple, "Apple", 4),
+    ] {
+        Recipe::insert_into_database_new_entry(id, title, &connection).unwrap();
+        Recipe::update_in_database_rating(id, rating, &connection).unwrap();
+    }
+    Recipe::update_in_database_insert_tag(cherry, "Fruit", &connection).unwrap();
+    Recipe::update_in_database_insert_tag(apple, "Fruit", &connection).unwrap();
+
+    let page =
+        Recipe::select_page_from_database(&query(json!({})), &Pagination::default(), &connection)
+            .unwrap();
+    assert_eq!(recipe_ids(&page), vec![apple, banana, cherry]);
+    assert_eq!(page.total, 3);
+
+    let page = Recipe::select_page_from_database(
+        &query(json!({"sort": "rating", "direction": "desc"})),
+        &query(json!({"limit": 2, "offset": 1})),
+        &connection,
+    )
+    .unwrap();
+    assert_eq!(recipe_ids(&page), vec![banana, cherry]);
+    assert_eq!((page.total, page.offset, page.limit), (3, 1, Some(2)));
+
+    let page = Recipe::select_page_from_database(
+        &query(json!({"tag": "Fruit", "minimumRating": 3})),
+        &Pagination::default(),
+        &connection,
+    )
+    .unwrap();
+    assert_eq!(recipe_ids(&page), vec![apple]);
+    assert_eq!(page.total, 1);
+}
+
+#[test]
+/// Tests if attachments are filtered by time window.
+fn test_select_page_attachments() {
+    let connection = Configuration::in_memory_database_connection();
+    for (day, name) in [(1, "first"), (2, "second"), (3, "third")] {
+        connection
+            .execute(
+                "INSERT INTO attachment (id, name, creation_time) VALUES (?1, ?2, ?3)",
+                rusqlite::params![
+                    Uuid::from_u128(day),
+                    name,
+                    Utc.with_ymd_and_hms(2022, 1, day as u32, 12, 0, 0).unwrap()
+                ],
+            )
+            .unwrap();
+    }
+
+    let page = Attachment::select_page_from_database(
+        &query(json!({
+            "from": "2022-01-02T00:00:00Z",
+            "to": "2022-01-03T12:00:00Z",
+            "sort": "creationTime",
+            "direction": "desc",
+        })),
+        &Pagination::default(),
+        &connection,
+    )
+    .unwrap();
+    let names: Vec<&str> = page
+        .items
+        .iter()
+        .map(|attachment| attachment.name())
+        .collect();
+    assert_eq!(names, vec!["third", "second"]);
+    assert_eq!(page.total, 2);
+}
+
+#[test]
+/// Tests if payments are filtered and sorted by the date of their first occurrence
+/// instead of the time they were created at.
+fn test_select_page_payments() {
+    let connection = Configuration::in_memory_database_connection();
+    for (index, target, start) in [
+        (1, "march", "2022-03-01T00:00:00Z"),
+        (2, "january", "2022-01-15T00:00:00.500Z"),
+        (3, "february", "2022-02-01T00:00:00Z"),
+        (4, "later january", "2022-01-15T00:00:00.750Z"),
+    ] {
+        let id = Uuid::from_u128(index);
+        Payment::insert_into_database_new_entry(id, target, "EUR", &connection).unwrap();
+        let payment_type = if index == 3 {
+            PaymentType::Monthly {
+                distance: 1,
+                start: date(start),
+                end: date("2022-12-31T00:00:00Z"),
+            }
+        } else {
+            PaymentType::OneOff { start: date(start) }
+        };
+        Payment::update_in_database_payment_type(id, &payment_type, &connection).unwrap();
+    }
+
+    let page = Payment::select_page_from_database(
+        &query(json!({"from": "2022-01-15T00:00:00.600Z", "to": "2022-02-01T00:00:00Z"})),
+        &Pagination::default(),
+        &connection,
+    )
+    .unwrap();
+    assert_eq!(payment_targets(&page), vec!["later january", "february"]);
+    assert_eq!(page.total, 2);
+
+    let page = Payment::select_page_from_database(
+        &query(json!({"direction": "desc"})),
+        &query(json!({"limit": 3, "offset": 0})),
+        &connection,
+    )
+    .unwrap();
+    assert_eq!(payment_targets(&page), vec!["march", "february", "later january"]);
+    assert_eq!(page.total, 4);
+}
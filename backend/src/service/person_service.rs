@@ -0,0 +1,129 @@
+//! The `person_service` module maintains the references from [`Payment`]s and
+//! [`Budget`]s to [`Person`]s.
+
+use std::collections::HashMap;
+
+use log::info;
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::{
+    application::{
+        config::Configuration,
+        error::{HomeworkError, InternalError},
+    },
+    entity::{budget::Budget, payment::Payment, person::Person},
+};
+
+/// Replaces all person references of payments and budgets that are not yet the ID of
+/// an existing person with the ID of the person with the respective name.
+/// Persons that do not exist yet are created, so free-text names of databases created
+/// by earlier versions of the application are mapped onto the person registry.
+/// Names are matched ignoring case and surrounding whitespace.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+pub fn migrate_person_references(connection: &Connection) -> Result<(), HomeworkError> {
+    let mut persons = Person::select_all_from_database(connection)?;
+    let mut mapping: HashMap<String, String> = HashMap::new();
+    let mut resolve = |reference: &str| -> Result<String, HomeworkError> {
+        if let Some(id) = mapping.get(reference) {
+            return Ok(id.clone());
+        }
+        let id = match Uuid::parse_str(reference)
+            .ok()
+            .filter(|id| persons.iter().any(|person| person.id() == *id))
+        {
+            Some(id) => id,
+            None => match persons.iter().find(|person| person.is_named(reference)) {
+                Some(person) => person.id(),
+                None => {
+                    let person = Person::new(Configuration::generate_uuid(), reference.trim());
+                    person.insert_into_database(connection)?;
+                    info!("Created person {} for \"{}\".", person.id(), reference);
+                    let id = person.id();
+                    persons.push(person);
+                    id
+                },
+            },
+        };
+        mapping.insert(reference.to_string(), id.to_string());
+        Ok(id.to_string())
+    };
+    for payment in Payment::select_all_from_database(connection)? {
+        // Resolve the references in a fixed order, so the same persons are created
+        // independent of the order of the amounts.
+        let mut references: Vec<String> = payment.persons().into_iter().collect();
+        references.sort();
+        let mut changed = false;
+        for reference in &references {
+            changed |= resolve(reference)? != *reference;
+        }
+        if changed {
+            payment.update_in_database_person_references(&mut resolve, connection)?;
+        }
+    }
+    for budget in Budget::select_all_from_database(connection)? {
+        if let Some(reference) = budget.person() {
+            let id = resolve(reference)?;
+            if id != reference {
+                Budget::update_in_database_person_references(reference, &id, connection)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Merges a person into another person. All references to the merged person are replaced
+/// with references to the remaining person, whose aliases are extended by the names of the
+/// merged person. The merged person is deleted afterwards.
+///
+/// # Parameters
+///
+/// * `source` - the ID of the person to merge
+/// * `target` - the ID of the person to merge into
+/// * `connection` - the database connection
+pub fn merge_persons(
+    source: Uuid,
+    target: Uuid,
+    connection: &Connection,
+) -> Result<(), HomeworkError> {
+    if source == target {
+        return Err(HomeworkError::BadRequestError(InternalError::new(
+            "Invalid merge",
+            format!("The person {} cannot be merged into itself.", source),
+            "A person cannot be merged into itself.",
+        )));
+    }
+    let merged = Person::select_from_database_by_id(source, connection)?;
+    let remaining = Person::select_from_database_by_id(target, connection)?;
+    let (source, target) = (source.to_string(), target.to_string());
+    for payment in Payment::select_all_from_database(connection)?
+        .iter()
+        .filter(|payment| payment.persons().contains(&source))
+    {
+        payment.update_in_database_person_references(
+            |reference| {
+                Ok(if reference == source {
+                    target.clone()
+                } else {
+                    reference.to_string()
+                })
+            },
+            connection,
+        )?;
+    }
+    Budget::update_in_database_person_references(&source, &target, connection)?;
+    Person::delete_from_database_by_id(merged.id(), connection)?;
+    remaining
+        .with_aliases(
+            std::iter::once(merged.display_name())
+                .chain(merged.aliases().iter().map(String::as_str)),
+        )
+        .update_in_database(connection)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test;
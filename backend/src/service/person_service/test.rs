@@ -0,0 +1,91 @@
+use rusqlite::params;
+
+use crate::entity::payment::SplitMode;
+
+use super::*;
+
+/// Inserts a payment with free-text person names as created by earlier versions.
+fn insert_legacy_payment(
+    id: Uuid,
+    paid: serde_json::Value,
+    involved: serde_json::Value,
+    split: Option<SplitMode>,
+    connection: &Connection,
+) {
+    Payment::insert_into_database_new_entry(id, "Target", "EUR", connection).unwrap();
+    connection
+        .execute(
+            "UPDATE payment SET paid = ?1, involved = ?2, split = ?3 WHERE id = ?4",
+            params![
+                paid,
+                involved,
+                split.map(|split| serde_json::to_value(split).unwrap()),
+                id
+            ],
+        )
+        .unwrap();
+}
+
+/// Returns the ID of the person with the specified name.
+fn person_id(name: &str, connection: &Connection) -> String {
+    Person::resolve_id(name, connection).unwrap().to_string()
+}
+
+#[test]
+/// Tests if the `migrate_person_references` function maps names onto persons
+/// ignoring case and whitespace and can be run repeatedly.
+fn test_migrate_person_references() {
+    let connection = Configuration::in_memory_database_connection();
+    let payment_id = Uuid::from_u128(1);
+    insert_legacy_payment(
+        payment_id,
+        serde_json::json!({"Anna": "30.00"}),
+        serde_json::json!({"anna ": "15.00", "Ben": "15.00"}),
+        Some(SplitMode::Equal {
+            persons: vec!["Anna".to_string(), "Ben".to_string()],
+        }),
+        &connection,
+    );
+    migrate_person_references(&connection).unwrap();
+    migrate_person_references(&connection).unwrap();
+
+    let persons = Person::select_all_from_database(&connection).unwrap();
+    assert_eq!(persons.iter().map(Person::display_name).collect::<Vec<_>>(), vec!["Anna", "Ben"]);
+    let (anna, ben) = (person_id("Anna", &connection), person_id("Ben", &connection));
+    let payment = Payment::select_from_database_by_id(payment_id, &connection).unwrap();
+    assert_eq!(payment.paid().keys().collect::<Vec<_>>(), vec![&anna]);
+    assert_eq!(payment.involved().len(), 2);
+    assert!(payment.involved().contains_key(&anna) && payment.involved().contains_key(&ben));
+    assert_eq!(
+        payment.split(),
+        Some(&SplitMode::Equal {
+            persons: vec![anna, ben]
+        })
+    );
+}
+
+#[test]
+/// Tests if the `merge_persons` function folds all amounts into the remaining person
+/// and keeps the merged name as alias.
+fn test_merge_persons() {
+    let connection = Configuration::in_memory_database_connection();
+    let payment_id = Uuid::from_u128(1);
+    insert_legacy_payment(
+        payment_id,
+        serde_json::json!({"Anna": "30.00"}),
+        serde_json::json!({"Anna": "10.00", "Anna M.": "20.00"}),
+        None,
+        &connection,
+    );
+    migrate_person_references(&connection).unwrap();
+    let anna = Person::resolve_id("Anna", &connection).unwrap();
+    let anna_m = Person::resolve_id("Anna M.", &connection).unwrap();
+
+    assert!(merge_persons(anna, anna, &connection).is_err());
+    merge_persons(anna_m, anna, &connection).unwrap();
+
+    assert!(!Person::exists_in_database_by_id(anna_m, &connection).unwrap());
+    assert_eq!(Person::resolve_id("anna m.", &connection).unwrap(), anna);
+    let payment = Payment::select_from_database_by_id(payment_id, &connection).unwrap();
+    assert_eq!(payment.involved(), &HashMap::from([(anna.to_string(), "30.00".parse().unwrap())]));
+}
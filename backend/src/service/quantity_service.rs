@@ -0,0 +1,306 @@
+//! The `quantity_service` module parses the free-text amounts of
+//! [`Ingredient`](crate::entity::ingredient::Ingredient)s into exact quantities,
+//! so they can be used in calculations like scaling a recipe.
+
+use serde::{Serialize, Serializer};
+
+/// The largest denominator that is displayed as a fraction instead of a decimal number.
+const DISPLAY_MAXIMUM_DENOMINATOR: i64 = 10;
+/// The number of decimal places of quantities that cannot be displayed exactly.
+const DISPLAY_DECIMAL_PLACES: u32 = 2;
+/// The characters separating the bounds of a range.
+const RANGE_SEPARATORS: [char; 3] = ['-', '–', '—'];
+/// The unicode characters representing common fractions.
+const UNICODE_FRACTIONS: [(char, &str); 15] = [
+    ('½', "1/2"),
+    ('⅓', "1/3"),
+    ('⅔', "2/3"),
+    ('¼', "1/4"),
+    ('¾', "3/4"),
+    ('⅕', "1/5"),
+    ('⅖', "2/5"),
+    ('⅗', "3/5"),
+    ('⅘', "4/5"),
+    ('⅙', "1/6"),
+    ('⅚', "5/6"),
+    ('⅛', "1/8"),
+    ('⅜', "3/8"),
+    ('⅝', "5/8"),
+    ('⅞', "7/8"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// An exact, non-negative rational number in lowest terms.
+pub struct Fraction {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Fraction {
+    /// Creates a new `Fraction` in lowest terms.
+    /// Returns `None` if the denominator is zero or the fraction is negative.
+    ///
+    /// # Parameters
+    ///
+    /// * `numerator` - the numerator
+    /// * `denominator` - the denominator
+    pub fn new(numerator: i64, denominator: i64) -> Option<Self> {
+        Self::reduced(numerator.into(), denominator.into())
+    }
+
+    /// Creates a new `Fraction` representing a whole number.
+    pub fn from_integer(value: u32) -> Self {
+        Fraction {
+            numerator: value.into(),
+            denominator: 1,
+        }
+    }
+
+    /// Returns `true` if this `Fraction` is zero.
+    pub fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    /// Returns the sum of both fractions or `None` if the result is too large to be represented.
+    pub fn checked_add(&self, other: &Fraction) -> Option<Fraction> {
+        Self::reduced(
+            i128::from(self.numerator) * i128::from(other.denominator)
+                + i128::from(other.numerator) * i128::from(self.denominator),
+            i128::from(self.denominator) * i128::from(other.denominator),
+        )
+    }
+
+    /// Returns the product of both fractions or `None` if the result is too large to be represented.
+    pub fn checked_mul(&self, other: &Fraction) -> Option<Fraction> {
+        Self::reduced(
+            i128::from(self.numerator) * i128::from(other.numerator),
+            i128::from(self.denominator) * i128::from(other.denominator),
+        )
+    }
+
+    /// Returns the quotient of both fractions or `None` if the divisor is zero
+    /// or the result is too large to be represented.
+    pub fn checked_div(&self, other: &Fraction) -> Option<Fraction> {
+        Self::reduced(
+            i128::from(self.numerator) * i128::from(other.denominator),
+            i128::from(self.denominator) * i128::from(other.numerator),
+        )
+    }
+
+    /// Reduces the fraction to lowest terms if it can be represented.
+    fn reduced(numerator: i128, denominator: i128) -> Option<Fraction> {
+        if denominator == 0 || numerator.signum() * denominator.signum() < 0 {
+            return None;
+        }
+        let (numerator, denominator) = (numerator.abs(), denominator.abs());
+        let divisor = greatest_common_divisor(numerator, denominator);
+        Some(Fraction {
+            numerator: (numerator / divisor).try_into().ok()?,
+            denominator: (denominator / divisor).try_into().ok()?,
+        })
+    }
+
+    /// Parses a single number, which is either a whole number, a decimal number with a point or a comma,
+    /// a fraction like `1/2`, a mixed number like `1 1/2` or a unicode fraction like `1½`.
+    /// Returns `None` if the text is not a single number.
+    ///
+    /// # Parameters
+    ///
+    /// * `value` - the text to parse
+    pub fn parse(value: &str) -> Option<Fraction> {
+        let mut value = value.trim().to_string();
+        for (character, fraction) in UNICODE_FRACTIONS {
+            value = value.replace(character, &format!(" {}", fraction));
+        }
+        let parts: Vec<&str> = value.split_whitespace().collect();
+        match parts.as_slice() {
+            [number] => Self::parse_simple(number),
+            [whole, fraction] if fraction.contains('/') && !whole.contains('/') => {
+                Self::parse_simple(whole)?.checked_add(&Self::parse_simple(fraction)?)
+            },
+            _ => None,
+        }
+    }
+
+    /// Parses a whole number, a decimal number or a fraction without whitespace.
+    fn parse_simple(value: &str) -> Option<Fraction> {
+        if let Some((numerator, denominator)) = value.split_once('/') {
+            return Self::new(parse_digits(numerator)?, parse_digits(denominator)?);
+        }
+        match value.split_once(['.', ',']) {
+            Some((whole, decimals)) => {
+                let whole = if whole.is_empty() {
+                    Some(0)
+                } else {
+                    parse_digits(whole)
+                }?;
+                let scale = 10_i64.checked_pow(decimals.len().try_into().ok()?)?;
+                Self::new(whole, 1)?.checked_add(&Self::new(parse_digits(decimals)?, scale)?)
+            },
+            None => Self::new(parse_digits(value)?, 1),
+        }
+    }
+}
+
+impl std::fmt::Display for Fraction {
+    /// Formats the fraction as a mixed number like `1 1/2` if the denominator is small
+    /// and as a decimal number otherwise.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let whole = self.numerator / self.denominator;
+        let remainder = self.numerator % self.denominator;
+        if remainder == 0 {
+            write!(f, "{}", whole)
+        } else if self.denominator <= DISPLAY_MAXIMUM_DENOMINATOR {
+            if whole == 0 {
+                write!(f, "{}/{}", remainder, self.denominator)
+            } else {
+                write!(f, "{} {}/{}", whole, remainder, self.denominator)
+            }
+        } else {
+            let scale = 10_i128.pow(DISPLAY_DECIMAL_PLACES);
+            // Rounds half up to the displayed number of decimal places.
+            let scaled = (i128::from(self.numerator) * scale * 2 + i128::from(self.denominator))
+                / (i128::from(self.denominator) * 2);
+            let decimals =
+                format!("{:0width$}", scaled % scale, width = DISPLAY_DECIMAL_PLACES as usize);
+            let decimals = decimals.trim_end_matches('0');
+            if decimals.is_empty() {
+                write!(f, "{}", scaled / scale)
+            } else {
+                write!(f, "{}.{}", scaled / scale, decimals)
+            }
+        }
+    }
+}
+
+impl Ord for Fraction {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (i128::from(self.numerator) * i128::from(other.denominator))
+            .cmp(&(i128::from(other.numerator) * i128::from(self.denominator)))
+    }
+}
+
+impl PartialOrd for Fraction {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serialize for Fraction {
+    /// Serializes the fraction exactly as `numerator/denominator`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}/{}", self.numerator, self.denominator))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The exact quantity of an ingredient, which is either a single number or a range like `2-3`.
+pub struct Quantity {
+    /// The quantity or the lower bound of the range.
+    minimum: Fraction,
+    /// The upper bound of the range if the quantity is a range.
+    maximum: Option<Fraction>,
+}
+
+impl Quantity {
+    /// Parses the free-text amount of an ingredient.
+    /// Returns `None` if the text does not describe a quantity, e.g. `a pinch`.
+    ///
+    /// # Parameters
+    ///
+    /// * `text` - the text to parse
+    pub fn parse(text: &str) -> Option<Quantity> {
+        let (minimum, maximum) = match text.split_once(RANGE_SEPARATORS) {
+            Some((minimum, maximum)) => {
+                (Fraction::parse(minimum)?, Some(Fraction::parse(maximum)?))
+            },
+            None => (Fraction::parse(text)?, None),
+        };
+        match maximum {
+            Some(maximum) if maximum < minimum => None,
+            // A range with equal bounds is a single quantity.
+            Some(maximum) if maximum == minimum => Some(Quantity {
+                minimum,
+                maximum: None,
+            }),
+            _ => Some(Quantity { minimum, maximum }),
+        }
+    }
+
+    /// Returns the quantity or the lower bound of the range.
+    pub fn minimum(&self) -> Fraction {
+        self.minimum
+    }
+
+    /// Returns the upper bound if this `Quantity` is a range.
+    pub fn maximum(&self) -> Option<Fraction> {
+        self.maximum
+    }
+
+    /// Returns the sum of both quantities or `None` if the result is too large to be represented.
+    /// Adding a range yields a range.
+    ///
+    /// # Parameters
+    ///
+    /// * `other` - the quantity to add
+    pub fn checked_add(&self, other: &Quantity) -> Option<Quantity> {
+        Some(Quantity {
+            minimum: self.minimum.checked_add(&other.minimum)?,
+            maximum: if self.maximum.is_some() || other.maximum.is_some() {
+                Some(
+                    self.maximum
+                        .unwrap_or(self.minimum)
+                        .checked_add(&other.maximum.unwrap_or(other.minimum))?,
+                )
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Returns this `Quantity` multiplied by the factor or `None` if the result
+    /// is too large to be represented.
+    ///
+    /// # Parameters
+    ///
+    /// * `factor` - the factor to multiply with
+    pub fn checked_scale(&self, factor: &Fraction) -> Option<Quantity> {
+        Some(Quantity {
+            minimum: self.minimum.checked_mul(factor)?,
+            maximum: match self.maximum {
+                Some(maximum) => Some(maximum.checked_mul(factor)?),
+                None => None,
+            },
+        })
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.maximum {
+            Some(maximum) => write!(f, "{}-{}", self.minimum, maximum),
+            None => write!(f, "{}", self.minimum),
+        }
+    }
+}
+
+/// Parses a non-empty sequence of ASCII digits.
+fn parse_digits(value: &str) -> Option<i64> {
+    if value.is_empty() || !value.chars().all(|character| character.is_ascii_digit()) {
+        None
+    } else {
+        value.parse().ok()
+    }
+}
+
+/// Returns the greatest common divisor of two non-negative numbers, which is at least one.
+fn greatest_common_divisor(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,110 @@
+use super::*;
+
+/// Creates a fraction that is known to be valid.
+fn fraction(numerator: i64, denominator: i64) -> Fraction {
+    Fraction::new(numerator, denominator).unwrap()
+}
+
+#[test]
+/// Tests if the `parse` function parses whole, decimal, fractional and mixed numbers.
+fn test_parse_number() {
+    let expected = |numerator, denominator| {
+        Some(Quantity {
+            minimum: fraction(numerator, denominator),
+            maximum: None,
+        })
+    };
+    assert_eq!(Quantity::parse("2"), expected(2, 1));
+    assert_eq!(Quantity::parse(" 0,5 "), expected(1, 2));
+    assert_eq!(Quantity::parse("1.25"), expected(5, 4));
+    assert_eq!(Quantity::parse(".5"), expected(1, 2));
+    assert_eq!(Quantity::parse("3/4"), expected(3, 4));
+    assert_eq!(Quantity::parse("1 1/2"), expected(3, 2));
+    assert_eq!(Quantity::parse("1½"), expected(3, 2));
+    assert_eq!(Quantity::parse("⅓"), expected(1, 3));
+}
+
+#[test]
+/// Tests if the `parse` function parses ranges.
+fn test_parse_range() {
+    assert_eq!(
+        Quantity::parse("2-3"),
+        Some(Quantity {
+            minimum: fraction(2, 1),
+            maximum: Some(fraction(3, 1)),
+        })
+    );
+    assert_eq!(
+        Quantity::parse("1/2 – 1"),
+        Some(Quantity {
+            minimum: fraction(1, 2),
+            maximum: Some(fraction(1, 1)),
+        })
+    );
+    assert_eq!(
+        Quantity::parse("2-2"),
+        Some(Quantity {
+            minimum: fraction(2, 1),
+            maximum: None,
+        })
+    );
+    assert_eq!(Quantity::parse("3-2"), None);
+}
+
+#[test]
+/// Tests if the `parse` function rejects amounts that do not describe a quantity.
+fn test_parse_invalid() {
+    for text in [
+        "",
+        "a pinch",
+        "1/0",
+        "1 2",
+        "1/2 1",
+        "2x",
+        "1,2,3",
+        "-1",
+        "99999999999999999999",
+    ] {
+        assert_eq!(Quantity::parse(text), None, "{}", text);
+    }
+}
+
+#[test]
+/// Tests if fractions are displayed as mixed numbers or rounded decimal numbers.
+fn test_display() {
+    assert_eq!(fraction(3, 1).to_string(), "3");
+    assert_eq!(fraction(1, 2).to_string(), "1/2");
+    assert_eq!(fraction(7, 3).to_string(), "2 1/3");
+    assert_eq!(fraction(3, 16).to_string(), "0.19");
+    assert_eq!(fraction(1, 20).to_string(), "0.05");
+    assert_eq!(fraction(1999, 1000).to_string(), "2");
+    assert_eq!(Quantity::parse("1 - 1½").unwrap().to_string(), "1-1 1/2");
+}
+
+#[test]
+/// Tests if quantities are scaled exactly.
+fn test_checked_scale() {
+    let factor = fraction(3, 2);
+    assert_eq!(
+        Quantity::parse("1/3-2/3")
+            .unwrap()
+            .checked_scale(&factor)
+            .unwrap()
+            .to_string(),
+        "1/2-1"
+    );
+    assert_eq!(
+        Quantity::parse("0,5")
+            .unwrap()
+            .checked_scale(&fraction(4, 1))
+            .unwrap()
+            .to_string(),
+        "2"
+    );
+    assert_eq!(
+        Quantity::parse("9223372036854775807")
+            .unwrap()
+            .checked_scale(&fraction(2, 1)),
+        None
+    );
+}
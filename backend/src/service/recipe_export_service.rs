@@ -0,0 +1,414 @@
+//! The `recipe_export_service` module renders recipes as Markdown and as
+//! self-contained, print-friendly HTML pages, so they can be shared outside of the app.
+
+use std::{io::Cursor, path::Path};
+
+use image::{codecs::jpeg::JpegEncoder, ColorType, GenericImageView, RgbImage};
+use serde::Deserialize;
+
+use crate::{
+    application::error::HomeworkError,
+    entity::{ingredient::Ingredient, recipe::Recipe},
+};
+
+use super::html_service::{decode_html_entities, escape_html};
+
+/// The highest rating of a recipe.
+const RATING_MAXIMUM: u8 = 10;
+/// The maximum width and height in pixels of exported images.
+const IMAGE_MAXIMUM_SIZE: u32 = 600;
+/// The quality of exported JPEG images.
+const IMAGE_JPEG_QUALITY: u8 = 85;
+/// The label of the Markdown image reference of the thumbnail.
+const MARKDOWN_THUMBNAIL_LABEL: &str = "thumbnail";
+/// The style sheet of exported HTML pages.
+const HTML_STYLE: &str = "
+    body { font-family: Helvetica, Arial, sans-serif; line-height: 1.5; color: #222; margin: 0; }
+    article { max-width: 42em; margin: 2em auto; padding: 0 1em; }
+    h1 { margin-bottom: 0.25em; }
+    h2 { border-bottom: 1px solid #ccc; margin-top: 1.5em; }
+    img.thumbnail { display: block; max-width: 100%; max-height: 20em; margin: 1em 0; }
+    dl.details { display: grid; grid-template-columns: max-content auto; gap: 0.25em 1em; }
+    dl.details dt { font-weight: bold; }
+    dl.details dd { margin: 0; }
+    ul.ingredients { padding-left: 1.25em; }
+    @media print {
+        article { max-width: none; margin: 0; }
+        a { color: inherit; text-decoration: none; }
+        h2 { break-after: avoid; }
+        li, img.thumbnail { break-inside: avoid; }
+    }
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The formats a single recipe can be exported in.
+pub enum RecipeExportFormat {
+    Markdown,
+    Html,
+}
+
+impl RecipeExportFormat {
+    /// Returns the MIME type of this `RecipeExportFormat`.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            RecipeExportFormat::Markdown => "text/markdown; charset=utf-8",
+            RecipeExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    /// Returns the file extension of this `RecipeExportFormat`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecipeExportFormat::Markdown => "md",
+            RecipeExportFormat::Html => "html",
+        }
+    }
+
+    /// Renders the recipe in this `RecipeExportFormat`.
+    ///
+    /// # Parameters
+    ///
+    /// * `recipe` - the recipe to render
+    /// * `thumbnail` - the thumbnail of the recipe if it can be exported
+    pub fn render(&self, recipe: &Recipe, thumbnail: Option<&ExportImage>) -> String {
+        match self {
+            RecipeExportFormat::Markdown => recipe_markdown(recipe, thumbnail),
+            RecipeExportFormat::Html => recipe_html(recipe, thumbnail),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An image prepared for embedding into exported documents.
+pub struct ExportImage {
+    /// The image encoded as JPEG.
+    jpeg: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl ExportImage {
+    /// Decodes the image file and scales it down to the maximum export size.
+    /// The image is encoded as JPEG, as it can be embedded into HTML and PDF alike.
+    /// Transparent areas are filled with white.
+    ///
+    /// # Parameters
+    ///
+    /// * `path` - the path to the image file
+    pub fn from_file(path: &Path) -> Result<Self, HomeworkError> {
+        let image = image::io::Reader::open(path)?
+            .with_guessed_format()?
+            .decode()?;
+        let image = if image.width() > IMAGE_MAXIMUM_SIZE || image.height() > IMAGE_MAXIMUM_SIZE {
+            image.thumbnail(IMAGE_MAXIMUM_SIZE, IMAGE_MAXIMUM_SIZE)
+        } else {
+            image
+        };
+        let (width, height) = image.dimensions();
+        let rgba = image.into_rgba8();
+        let rgb = RgbImage::from_fn(width, height, |x, y| {
+            let [red, green, blue, alpha] = rgba.get_pixel(x, y).0;
+            let blend = |channel: u8| {
+                ((u16::from(channel) * u16::from(alpha) + 255 * u16::from(255 - alpha)) / 255) as u8
+            };
+            image::Rgb([blend(red), blend(green), blend(blue)])
+        });
+        let mut jpeg = Cursor::new(Vec::new());
+        JpegEncoder::new_with_quality(&mut jpeg, IMAGE_JPEG_QUALITY).encode(
+            rgb.as_raw(),
+            width,
+            height,
+            ColorType::Rgb8,
+        )?;
+        Ok(ExportImage {
+            jpeg: jpeg.into_inner(),
+            width,
+            height,
+        })
+    }
+
+    /// Returns the JPEG encoded content of this `ExportImage`.
+    pub fn jpeg(&self) -> &[u8] {
+        &self.jpeg
+    }
+
+    /// Returns the width in pixels of this `ExportImage`.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height in pixels of this `ExportImage`.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns this `ExportImage` as data URL.
+    fn data_url(&self) -> String {
+        format!("data:image/jpeg;base64,{}", base64::encode(&self.jpeg))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The markup of text converted from HTML.
+pub enum TextStyle {
+    /// Text without any markup.
+    Plain,
+    /// Markdown with escaped text and emphasis.
+    Markdown,
+}
+
+/// Returns the ingredients of the recipe in the order defined by the user.
+///
+/// # Parameters
+///
+/// * `recipe` - the recipe
+pub fn ordered_ingredients(recipe: &Recipe) -> Vec<&Ingredient> {
+    let mut ingredients: Vec<&Ingredient> = recipe.ingredients().iter().collect();
+    ingredients.sort_by_key(|ingredient| ingredient.ordering());
+    ingredients
+}
+
+/// Returns the amount, unit and text of the ingredient as single line.
+///
+/// # Parameters
+///
+/// * `ingredient` - the ingredient
+pub fn ingredient_line(ingredient: &Ingredient) -> String {
+    [ingredient.amount(), ingredient.unit(), ingredient.text()]
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Returns the labelled details of the recipe, i.e. its rating, servings, tags and source.
+/// Details that are not specified are left out.
+///
+/// # Parameters
+///
+/// * `recipe` - the recipe
+pub fn recipe_details(recipe: &Recipe) -> Vec<(&'static str, String)> {
+    let mut details = Vec::new();
+    if recipe.rating() > 0 {
+        details.push(("Rating", format!("{}/{}", recipe.rating(), RATING_MAXIMUM)));
+    }
+    if let Some(servings) = recipe.servings() {
+        details.push(("Servings", servings.to_string()));
+    }
+    if !recipe.tags().is_empty() {
+        details.push(("Tags", recipe.tags().join(", ")));
+    }
+    if !recipe.reference().trim().is_empty() {
+        details.push(("Source", recipe.reference().trim().to_string()));
+    }
+    details
+}
+
+/// Converts the HTML of the instructions written in the recipe editor into paragraphs.
+/// Block elements and line breaks start a new paragraph, list items are prefixed with
+/// a bullet or their number. All other markup is removed, apart from emphasis in Markdown.
+///
+/// # Parameters
+///
+/// * `html` - the instructions as HTML
+/// * `style` - the markup of the paragraphs
+pub fn instruction_paragraphs(html: &str, style: TextStyle) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut paragraph = String::new();
+    // The numbers of the current item of all open lists, `None` for unordered lists.
+    let mut lists: Vec<Option<u32>> = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(tag_start) = rest.find('<') else {
+            push_text(&mut paragraph, rest, style);
+            break;
+        };
+        push_text(&mut paragraph, &rest[..tag_start], style);
+        let Some(tag_end) = rest[tag_start..].find('>').map(|end| tag_start + end) else {
+            push_text(&mut paragraph, &rest[tag_start..], style);
+            break;
+        };
+        let tag = &rest[tag_start + 1..tag_end];
+        rest = &rest[tag_end + 1..];
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|character: char| character.is_whitespace() || character == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "script" | "style" if !closing => {
+                // Skip the content, which is not displayed.
+                let end = rest.to_ascii_lowercase().find(&format!("</{}", name));
+                rest = end.map(|end| &rest[end..]).unwrap_or_default();
+            },
+            "ul" | "ol" => {
+                end_paragraph(&mut paragraphs, &mut paragraph);
+                if closing {
+                    lists.pop();
+                } else {
+                    lists.push((name == "ol").then_some(0));
+                }
+            },
+            "li" => {
+                end_paragraph(&mut paragraphs, &mut paragraph);
+                if !closing {
+                    let prefix = match lists.last_mut() {
+                        Some(Some(number)) => {
+                            *number += 1;
+                            format!("{}. ", number)
+                        },
+                        _ => match style {
+                            TextStyle::Plain => "• ".to_string(),
+                            TextStyle::Markdown => "- ".to_string(),
+                        },
+                    };
+                    paragraph.push_str(&prefix);
+                }
+            },
+            "br" | "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote" | "pre"
+            | "tr" | "hr" => end_paragraph(&mut paragraphs, &mut paragraph),
+            "b" | "strong" if style == TextStyle::Markdown => paragraph.push_str("**"),
+            "i" | "em" if style == TextStyle::Markdown => paragraph.push('*'),
+            "s" | "strike" | "del" if style == TextStyle::Markdown => paragraph.push_str("~~"),
+            _ => {},
+        }
+    }
+    end_paragraph(&mut paragraphs, &mut paragraph);
+    paragraphs
+}
+
+/// Renders the recipe as Markdown document. The thumbnail is embedded as data URL,
+/// which is defined at the end of the document to keep the text readable.
+///
+/// # Parameters
+///
+/// * `recipe` - the recipe to render
+/// * `thumbnail` - the thumbnail of the recipe if it can be exported
+pub fn recipe_markdown(recipe: &Recipe, thumbnail: Option<&ExportImage>) -> String {
+    let mut markdown = format!("# {}\n\n", escape_markdown(recipe.title()));
+    if thumbnail.is_some() {
+        markdown.push_str(&format!(
+            "![{}][{}]\n\n",
+            escape_markdown(recipe.title()),
+            MARKDOWN_THUMBNAIL_LABEL
+        ));
+    }
+    let details = recipe_details(recipe);
+    for (label, value) in &details {
+        let value = if *label == "Source" && is_link(value) {
+            format!("<{}>", value)
+        } else {
+            escape_markdown(value)
+        };
+        markdown.push_str(&format!("- **{}:** {}\n", label, value));
+    }
+    if !details.is_empty() {
+        markdown.push('\n');
+    }
+    markdown.push_str("## Ingredients\n\n");
+    for ingredient in ordered_ingredients(recipe) {
+        markdown.push_str(&format!("- {}\n", escape_markdown(&ingredient_line(ingredient))));
+    }
+    markdown.push_str("\n## Instructions\n\n");
+    for paragraph in instruction_paragraphs(recipe.instructions(), TextStyle::Markdown) {
+        markdown.push_str(&paragraph);
+        markdown.push_str("\n\n");
+    }
+    if let Some(thumbnail) = thumbnail {
+        markdown.push_str(&format!("[{}]: {}\n", MARKDOWN_THUMBNAIL_LABEL, thumbnail.data_url()));
+    }
+    markdown
+}
+
+/// Renders the recipe as HTML page that does not depend on any other resources,
+/// so it can be saved, shared and printed as it is. The instructions are included
+/// as HTML like they are displayed in the app.
+///
+/// # Parameters
+///
+/// * `recipe` - the recipe to render
+/// * `thumbnail` - the thumbnail of the recipe if it can be exported
+pub fn recipe_html(recipe: &Recipe, thumbnail: Option<&ExportImage>) -> String {
+    let title = escape_html(recipe.title());
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+        <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<article>\n<h1>{}</h1>\n",
+        title, HTML_STYLE, title
+    );
+    if let Some(thumbnail) = thumbnail {
+        html.push_str(&format!(
+            "<img class=\"thumbnail\" src=\"{}\" alt=\"{}\">\n",
+            thumbnail.data_url(),
+            title
+        ));
+    }
+    let details = recipe_details(recipe);
+    if !details.is_empty() {
+        html.push_str("<dl class=\"details\">\n");
+        for (label, value) in details {
+            let value = if label == "Source" && is_link(&value) {
+                format!("<a href=\"{0}\">{0}</a>", escape_html(&value))
+            } else {
+                escape_html(&value)
+            };
+            html.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", label, value));
+        }
+        html.push_str("</dl>\n");
+    }
+    html.push_str("<h2>Ingredients</h2>\n<ul class=\"ingredients\">\n");
+    for ingredient in ordered_ingredients(recipe) {
+        html.push_str(&format!("<li>{}</li>\n", escape_html(&ingredient_line(ingredient))));
+    }
+    html.push_str("</ul>\n<h2>Instructions</h2>\n<section class=\"instructions\">\n");
+    html.push_str(recipe.instructions());
+    html.push_str("\n</section>\n</article>\n</body>\n</html>\n");
+    html
+}
+
+/// Appends the text of an HTML document to the paragraph collapsing whitespace.
+fn push_text(paragraph: &mut String, html_text: &str, style: TextStyle) {
+    let text = decode_html_entities(html_text);
+    for (index, word) in text.split(char::is_whitespace).enumerate() {
+        if index > 0 && !paragraph.is_empty() && !paragraph.ends_with(' ') {
+            paragraph.push(' ');
+        }
+        match style {
+            TextStyle::Plain => paragraph.push_str(word),
+            TextStyle::Markdown => paragraph.push_str(&escape_markdown(word)),
+        }
+    }
+}
+
+/// Adds the paragraph to the paragraphs unless it does not contain any text.
+fn end_paragraph(paragraphs: &mut Vec<String>, paragraph: &mut String) {
+    let text = paragraph.trim();
+    if text.chars().any(char::is_alphanumeric) {
+        paragraphs.push(text.to_string());
+    }
+    paragraph.clear();
+}
+
+/// Returns if the reference is a web link.
+fn is_link(reference: &str) -> bool {
+    reference.starts_with("https://") || reference.starts_with("http://")
+}
+
+/// Escapes all characters with a special meaning in Markdown.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        if matches!(character, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '~' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test;
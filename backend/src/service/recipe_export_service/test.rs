@@ -0,0 +1,158 @@
+use serde_json::json;
+
+use crate::application::config::Configuration;
+
+use super::*;
+
+/// Creates a recipe with unordered ingredients, tags and all details.
+fn recipe() -> Recipe {
+    let recipe_id = Configuration::generate_uuid();
+    let ingredient = |amount: &str, unit: &str, text: &str, ordering: i32| {
+        json!({
+            "id": Configuration::generate_uuid(),
+            "amount": amount,
+            "unit": unit,
+            "text": text,
+            "creationTime": "2023-01-01T00:00:00Z",
+            "recipeReference": null,
+            "recipeId": recipe_id,
+            "ordering": ordering,
+            "filterText": null,
+        })
+    };
+    serde_json::from_value(json!({
+        "id": recipe_id,
+        "title": "Fish & <Chips>",
+        "instructions": "<div>Cut the <b>potatoes</b>.</div><ol><li>Fry&nbsp;them</li><li>Salt them</li></ol><p><br></p><script>alert(1)</script>",
+        "reference": "https://example.com/fish",
+        "rating": 7,
+        "servings": 2,
+        "thumbnail": null,
+        "tags": ["dinner", "fried"],
+        "attachments": [],
+        "ingredients": [
+            ingredient("", "", "Salt", 2),
+            ingredient("500", "g", "potatoes", 0),
+            ingredient("2", "", "fish_fillets", 1),
+        ],
+        "creationTime": "2023-01-01T00:00:00Z",
+    }))
+    .unwrap()
+}
+
+/// Creates a small image.
+fn image() -> ExportImage {
+    ExportImage {
+        jpeg: vec![0xff, 0xd8, 0xff, 0xd9],
+        width: 1,
+        height: 1,
+    }
+}
+
+#[test]
+/// Tests if the `ingredient_line` function joins the specified parts in the defined order.
+fn test_ordered_ingredients() {
+    let recipe = recipe();
+    let lines: Vec<String> = ordered_ingredients(&recipe)
+        .into_iter()
+        .map(ingredient_line)
+        .collect();
+    assert_eq!(lines, vec!["500 g potatoes", "2 fish_fillets", "Salt"]);
+}
+
+#[test]
+/// Tests if the `instruction_paragraphs` function converts editor HTML into paragraphs.
+fn test_instruction_paragraphs() {
+    let html = "<div>Cut the <b>potatoes</b>.</div><ul><li>Fry&nbsp;them</li><li><i>Salt</i> *them*</li></ul><ol><li>Serve</li><li>Eat</li></ol><p><br></p><script>alert(1)</script>Enjoy";
+    assert_eq!(
+        instruction_paragraphs(html, TextStyle::Plain),
+        vec![
+            "Cut the potatoes.",
+            "• Fry them",
+            "• Salt *them*",
+            "1. Serve",
+            "2. Eat",
+            "Enjoy"
+        ]
+    );
+    assert_eq!(
+        instruction_paragraphs(html, TextStyle::Markdown),
+        vec![
+            "Cut the **potatoes**.",
+            "- Fry them",
+            "- *Salt* \\*them\\*",
+            "1. Serve",
+            "2. Eat",
+            "Enjoy"
+        ]
+    );
+    assert_eq!(
+        instruction_paragraphs("Plain text\nwithout   markup", TextStyle::Plain),
+        vec!["Plain text without markup"]
+    );
+}
+
+#[test]
+/// Tests if the `recipe_markdown` function renders all parts of the recipe.
+fn test_recipe_markdown() {
+    let markdown = recipe_markdown(&recipe(), Some(&image()));
+    assert_eq!(
+        markdown,
+        "# Fish & \\<Chips\\>\n\n\
+        ![Fish & \\<Chips\\>][thumbnail]\n\n\
+        - **Rating:** 7/10\n\
+        - **Servings:** 2\n\
+        - **Tags:** dinner, fried\n\
+        - **Source:** <https://example.com/fish>\n\n\
+        ## Ingredients\n\n\
+        - 500 g potatoes\n\
+        - 2 fish\\_fillets\n\
+        - Salt\n\n\
+        ## Instructions\n\n\
+        Cut the **potatoes**.\n\n\
+        1. Fry them\n\n\
+        2. Salt them\n\n\
+        [thumbnail]: data:image/jpeg;base64,/9j/2Q==\n"
+    );
+}
+
+#[test]
+/// Tests if the `recipe_html` function renders a self-contained page with escaped text.
+fn test_recipe_html() {
+    let html = recipe_html(&recipe(), Some(&image()));
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Fish &amp; &lt;Chips&gt;</title>"));
+    assert!(html.contains("<h1>Fish &amp; &lt;Chips&gt;</h1>"));
+    assert!(html.contains("src=\"data:image/jpeg;base64,/9j/2Q==\""));
+    assert!(html.contains("<dt>Rating</dt><dd>7/10</dd>"));
+    assert!(html.contains(
+        "<dt>Source</dt><dd><a href=\"https://example.com/fish\">https://example.com/fish</a></dd>"
+    ));
+    assert!(html.contains("<li>500 g potatoes</li>\n<li>2 fish_fillets</li>\n<li>Salt</li>"));
+    assert!(html.contains("<div>Cut the <b>potatoes</b>.</div>"));
+    assert!(!html.contains("<link") && !html.contains("<script src"));
+
+    let without_thumbnail = recipe_html(&recipe(), None);
+    assert!(!without_thumbnail.contains("<img"));
+}
+
+#[test]
+/// Tests if the `ExportImage::from_file` function scales images and fills transparent areas.
+fn test_export_image_from_file() {
+    let path = std::env::temp_dir().join(format!("{}.png", Configuration::generate_uuid()));
+    image::RgbaImage::from_pixel(800, 400, image::Rgba([0, 0, 0, 0]))
+        .save(&path)
+        .unwrap();
+    let export_image = ExportImage::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    let export_image = export_image.unwrap();
+    assert_eq!((export_image.width(), export_image.height()), (600, 300));
+    let decoded = image::load_from_memory(export_image.jpeg())
+        .unwrap()
+        .into_rgb8();
+    assert!(decoded
+        .pixels()
+        .all(|pixel| pixel.0.iter().all(|channel| *channel > 250)));
+
+    assert!(ExportImage::from_file(&std::env::temp_dir().join("missing.png")).is_err());
+}
@@ -0,0 +1,383 @@
+//! The `recipe_import_service` module extracts recipes from saved HTML pages
+//! that describe the recipe as schema.org `Recipe` in JSON-LD.
+
+use serde_json::Value;
+
+use crate::application::error::{HomeworkError, InternalError};
+
+use super::{
+    html_service::{decode_html_entities, escape_html},
+    quantity_service::Quantity,
+    unit_service::Unit,
+};
+
+/// The type of script elements containing JSON-LD.
+const SCRIPT_TYPE_JSON_LD: &str = "application/ld+json";
+/// The schema.org type of recipes.
+const SCHEMA_TYPE_RECIPE: &str = "Recipe";
+/// The maximum number of words an amount can consist of, e.g. `1 - 1 1/2`.
+const AMOUNT_MAXIMUM_WORDS: usize = 5;
+/// The prefix of data URLs.
+const DATA_URL_PREFIX: &str = "data:";
+/// The marker of base64 encoded data URLs.
+const DATA_URL_BASE64: &str = ";base64";
+/// The file name of imported images if the recipe does not have a usable title.
+const IMAGE_DEFAULT_FILE_NAME: &str = "recipe";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A recipe extracted from an HTML page.
+pub struct ImportedRecipe {
+    title: String,
+    instructions: String,
+    reference: String,
+    servings: Option<u32>,
+    ingredients: Vec<ImportedIngredient>,
+    image: Option<ImportedImage>,
+}
+
+impl ImportedRecipe {
+    /// Returns the title of this `ImportedRecipe`.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the instructions of this `ImportedRecipe` as HTML with one step per paragraph,
+    /// as the instructions of recipes are edited as HTML.
+    pub fn instructions(&self) -> &str {
+        &self.instructions
+    }
+
+    /// Returns the URL of the original recipe or an empty string if unknown.
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    /// Returns the number of servings this `ImportedRecipe` yields if specified.
+    pub fn servings(&self) -> Option<u32> {
+        self.servings
+    }
+
+    /// Returns the ingredients of this `ImportedRecipe` in their original order.
+    pub fn ingredients(&self) -> &Vec<ImportedIngredient> {
+        &self.ingredients
+    }
+
+    /// Returns the image embedded into the page if any.
+    pub fn image(&self) -> Option<&ImportedImage> {
+        self.image.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An ingredient line split into amount, unit and text.
+pub struct ImportedIngredient {
+    amount: String,
+    unit: String,
+    text: String,
+}
+
+impl ImportedIngredient {
+    /// Splits the ingredient line into amount, unit and text. Lines that do not start
+    /// with a quantity are kept as text without amount and unit. Units are only split off
+    /// if they are known, so e.g. `2 cloves garlic` keeps `cloves` as part of the text.
+    ///
+    /// # Parameters
+    ///
+    /// * `line` - the ingredient line, e.g. `1 1/2 cups flour, sifted`
+    pub fn parse(line: &str) -> Self {
+        let line = clean_text(line);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        for amount_words in (1..=AMOUNT_MAXIMUM_WORDS.min(words.len())).rev() {
+            let amount = words[..amount_words].join(" ");
+            let rest = &words[amount_words..];
+            if Quantity::parse(&amount).is_some() {
+                return match rest.split_first() {
+                    Some((unit, text)) if Unit::parse(unit).is_some() && !text.is_empty() => {
+                        ImportedIngredient::new(amount, *unit, text.join(" "))
+                    },
+                    _ if !rest.is_empty() => ImportedIngredient::new(amount, "", rest.join(" ")),
+                    _ => ImportedIngredient::new("", "", &line),
+                };
+            }
+        }
+        // Handle amounts written together with their unit, e.g. `200g flour`.
+        if let Some((first, text)) = words.split_first() {
+            if let Some(unit_start) = first.find(|character: char| character.is_alphabetic()) {
+                let (amount, unit) = first.split_at(unit_start);
+                if Quantity::parse(amount).is_some()
+                    && Unit::parse(unit).is_some()
+                    && !text.is_empty()
+                {
+                    return ImportedIngredient::new(amount, unit, text.join(" "));
+                }
+            }
+        }
+        ImportedIngredient::new("", "", &line)
+    }
+
+    /// Creates a new `ImportedIngredient`.
+    fn new<T: ToString, U: ToString, V: ToString>(amount: T, unit: U, text: V) -> Self {
+        ImportedIngredient {
+            amount: amount.to_string(),
+            unit: unit.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    /// Returns the amount of this `ImportedIngredient`.
+    pub fn amount(&self) -> &str {
+        &self.amount
+    }
+
+    /// Returns the unit of this `ImportedIngredient`.
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    /// Returns the text of this `ImportedIngredient`.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An image embedded into an HTML page as data URL.
+pub struct ImportedImage {
+    file_name: String,
+    content: Vec<u8>,
+}
+
+impl ImportedImage {
+    /// Returns the file name of this `ImportedImage` including an extension matching its type.
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Returns the decoded content of this `ImportedImage`.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+}
+
+/// Extracts the first schema.org `Recipe` described as JSON-LD from the HTML page.
+/// Images are only imported if they are embedded into the page as data URL,
+/// as the referenced images are not downloaded.
+///
+/// # Parameters
+///
+/// * `html` - the content of the HTML page
+pub fn parse_recipe_html(html: &str) -> Result<ImportedRecipe, HomeworkError> {
+    let recipe = json_ld_scripts(html)
+        .iter()
+        .filter_map(|script| serde_json::from_str::<Value>(script).ok())
+        .find_map(|json| find_recipe(&json).cloned())
+        .ok_or_else(|| {
+            HomeworkError::BadRequestError(InternalError::new(
+                "Missing recipe",
+                "The HTML page does not contain a schema.org recipe as JSON-LD.",
+                "The page does not contain a recipe that can be imported.",
+            ))
+        })?;
+    let title = recipe
+        .get("name")
+        .and_then(Value::as_str)
+        .map(clean_text)
+        .filter(|title| !title.is_empty())
+        .ok_or_else(|| {
+            HomeworkError::BadRequestError(InternalError::new(
+                "Missing recipe title",
+                format!("The recipe {} does not have a name.", recipe),
+                "The recipe does not have a title.",
+            ))
+        })?;
+    let mut instructions = Vec::new();
+    if let Some(value) = recipe.get("recipeInstructions") {
+        collect_instructions(value, &mut instructions);
+    }
+    let image = recipe
+        .get("image")
+        .and_then(|image| decode_image(image, &title));
+    Ok(ImportedRecipe {
+        instructions: instructions
+            .iter()
+            .map(|step| format!("<p>{}</p>", escape_html(step)))
+            .collect(),
+        reference: reference(&recipe).unwrap_or_default(),
+        servings: recipe.get("recipeYield").and_then(servings),
+        ingredients: strings(recipe.get("recipeIngredient"))
+            .iter()
+            .map(|line| ImportedIngredient::parse(line))
+            .filter(|ingredient| !ingredient.text().is_empty())
+            .collect(),
+        image,
+        title,
+    })
+}
+
+/// Returns the content of all script elements of the HTML page containing JSON-LD.
+fn json_ld_scripts(html: &str) -> Vec<&str> {
+    // ASCII lowercasing keeps all byte offsets, so they can be used on the original.
+    let lowercase = html.to_ascii_lowercase();
+    let mut scripts = Vec::new();
+    let mut position = 0;
+    while let Some(start) = lowercase[position..].find("<script") {
+        let tag_start = position + start;
+        let Some(tag_end) = lowercase[tag_start..].find('>').map(|end| tag_start + end) else {
+            break;
+        };
+        let Some(content_end) = lowercase[tag_end..]
+            .find("</script")
+            .map(|end| tag_end + end)
+        else {
+            break;
+        };
+        if lowercase[tag_start..tag_end].contains(SCRIPT_TYPE_JSON_LD) {
+            scripts.push(&html[tag_end + 1..content_end]);
+        }
+        position = content_end;
+    }
+    scripts
+}
+
+/// Searches the JSON-LD document for a node of type `Recipe`,
+/// including arrays of nodes, `@graph` and `mainEntity`.
+fn find_recipe(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Array(nodes) => nodes.iter().find_map(find_recipe),
+        Value::Object(node) => {
+            let is_recipe = match node.get("@type") {
+                Some(Value::String(node_type)) => node_type == SCHEMA_TYPE_RECIPE,
+                Some(Value::Array(node_types)) => node_types
+                    .iter()
+                    .any(|node_type| node_type.as_str() == Some(SCHEMA_TYPE_RECIPE)),
+                _ => false,
+            };
+            if is_recipe {
+                Some(value)
+            } else {
+                ["@graph", "mainEntity"]
+                    .iter()
+                    .filter_map(|key| node.get(*key))
+                    .find_map(find_recipe)
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Collects the steps of the instructions, which can be text, a list of texts,
+/// `HowToStep`s or `HowToSection`s containing steps.
+fn collect_instructions(value: &Value, instructions: &mut Vec<String>) {
+    match value {
+        Value::String(text) => {
+            instructions.extend(text.lines().map(clean_text).filter(|line| !line.is_empty()))
+        },
+        Value::Array(steps) => {
+            for step in steps {
+                collect_instructions(step, instructions);
+            }
+        },
+        Value::Object(step) => {
+            if let Some(elements) = step.get("itemListElement") {
+                if let Some(name) = step.get("name").and_then(Value::as_str).map(clean_text) {
+                    instructions.push(name);
+                }
+                collect_instructions(elements, instructions);
+            } else if let Some(text) = step.get("text").or_else(|| step.get("name")) {
+                collect_instructions(text, instructions);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Returns the number of servings of the recipe yield, which can be a number,
+/// a text like `4 servings` or a list of both.
+fn servings(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .and_then(|servings| u32::try_from(servings).ok()),
+        Value::String(text) => text
+            .split(|character: char| !character.is_ascii_digit())
+            .find(|number| !number.is_empty())
+            .and_then(|number| number.parse().ok()),
+        Value::Array(values) => values.iter().find_map(servings),
+        _ => None,
+    }
+    .filter(|servings| *servings > 0)
+}
+
+/// Returns the URL of the recipe, which is either its `url` or the page it is the main entity of.
+fn reference(recipe: &Value) -> Option<String> {
+    recipe
+        .get("url")
+        .and_then(Value::as_str)
+        .or_else(|| match recipe.get("mainEntityOfPage") {
+            Some(Value::String(url)) => Some(url.as_str()),
+            Some(page) => page.get("@id").and_then(Value::as_str),
+            None => None,
+        })
+        .map(|url| url.trim().to_string())
+}
+
+/// Returns the texts of a text or list of texts.
+fn strings(value: Option<&Value>) -> Vec<&str> {
+    match value {
+        Some(Value::String(text)) => vec![text.as_str()],
+        Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Decodes the first image embedded as base64 data URL. The image can be a URL,
+/// an `ImageObject` or a list of both.
+fn decode_image(value: &Value, title: &str) -> Option<ImportedImage> {
+    let url = match value {
+        Value::String(url) => url.as_str(),
+        Value::Object(image) => image.get("url").and_then(Value::as_str)?,
+        Value::Array(images) => return images.iter().find_map(|image| decode_image(image, title)),
+        _ => return None,
+    };
+    let (media_type, data) = url.trim().strip_prefix(DATA_URL_PREFIX)?.split_once(',')?;
+    let media_type = media_type.strip_suffix(DATA_URL_BASE64)?;
+    let extension = match media_type.strip_prefix("image/")? {
+        "jpeg" => "jpg",
+        "svg+xml" => "svg",
+        subtype => subtype,
+    };
+    let content = base64::decode(data.trim()).ok()?;
+    let mut file_name = sanitize_filename::sanitize(title);
+    if file_name.is_empty() {
+        file_name = IMAGE_DEFAULT_FILE_NAME.to_string();
+    }
+    Some(ImportedImage {
+        file_name: format!("{}.{}", file_name, extension),
+        content,
+    })
+}
+
+/// Removes HTML tags, decodes HTML entities and collapses whitespace,
+/// as many pages include markup in their JSON-LD texts.
+fn clean_text(text: &str) -> String {
+    let mut without_tags = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for character in text.chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                without_tags.push(' ');
+            },
+            _ if !in_tag => without_tags.push(character),
+            _ => {},
+        }
+    }
+    decode_html_entities(&without_tags)
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,132 @@
+use serde_json::json;
+
+use super::*;
+
+/// Wraps the JSON-LD into an HTML page.
+fn html_page(json_ld: &Value) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+        <html>
+        <head>
+            <script src="analytics.js"></script>
+            <SCRIPT type="application/ld+json">{{"@type": "WebSite", "name": "Cooking"}}</SCRIPT>
+            <script type='application/ld+json'>{}</script>
+        </head>
+        <body><h1>Recipe</h1></body>
+        </html>"#,
+        json_ld
+    )
+}
+
+/// Returns the amount, unit and text of the ingredient line.
+fn ingredient(line: &str) -> (String, String, String) {
+    let ingredient = ImportedIngredient::parse(line);
+    (ingredient.amount, ingredient.unit, ingredient.text)
+}
+
+#[test]
+/// Tests if the `parse_recipe_html` function extracts all fields of a recipe within a graph.
+fn test_parse_recipe_html() {
+    let html = html_page(&json!({
+        "@context": "https://schema.org",
+        "@graph": [
+            {"@type": "WebPage", "@id": "https://example.com/pancakes"},
+            {
+                "@type": ["Recipe", "NewsArticle"],
+                "name": "Fluffy Pancakes &amp; Syrup",
+                "mainEntityOfPage": {"@id": "https://example.com/pancakes"},
+                "recipeYield": ["4", "4 pancakes"],
+                "image": [
+                    "https://example.com/pancakes.jpg",
+                    {"@type": "ImageObject", "url": "data:image/jpeg;base64,/9j/4AAQ"}
+                ],
+                "recipeIngredient": [
+                    "1 1/2 cups flour, sifted",
+                    "200g sugar",
+                    "2 eggs",
+                    "a pinch of salt",
+                    "  "
+                ],
+                "recipeInstructions": [
+                    {
+                        "@type": "HowToSection",
+                        "name": "Batter",
+                        "itemListElement": [
+                            {"@type": "HowToStep", "text": "<p>Mix everything.</p>"}
+                        ]
+                    },
+                    {"@type": "HowToStep", "text": "Fry in a pan."}
+                ]
+            }
+        ]
+    }));
+
+    let recipe = parse_recipe_html(&html).unwrap();
+    assert_eq!(recipe.title(), "Fluffy Pancakes & Syrup");
+    assert_eq!(recipe.reference(), "https://example.com/pancakes");
+    assert_eq!(recipe.servings(), Some(4));
+    assert_eq!(recipe.instructions(), "<p>Batter</p><p>Mix everything.</p><p>Fry in a pan.</p>");
+    let ingredients: Vec<(&str, &str, &str)> = recipe
+        .ingredients()
+        .iter()
+        .map(|ingredient| (ingredient.amount(), ingredient.unit(), ingredient.text()))
+        .collect();
+    assert_eq!(
+        ingredients,
+        vec![
+            ("1 1/2", "cups", "flour, sifted"),
+            ("200", "g", "sugar"),
+            ("2", "", "eggs"),
+            ("", "", "a pinch of salt"),
+        ]
+    );
+    let image = recipe.image().unwrap();
+    assert_eq!(image.file_name(), "Fluffy Pancakes & Syrup.jpg");
+    assert_eq!(image.content(), &[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10]);
+}
+
+#[test]
+/// Tests if the `parse_recipe_html` function accepts instructions as text and images as URL.
+fn test_parse_recipe_html_minimal() {
+    let html = html_page(&json!({
+        "@type": "Recipe",
+        "name": "Tea",
+        "url": "https://example.com/tea",
+        "recipeYield": "Serves 2-3",
+        "image": "https://example.com/tea.jpg",
+        "recipeIngredient": "1 tea bag",
+        "recipeInstructions": "Boil water.\nSteep for 3&#160;minutes &amp; serve."
+    }));
+
+    let recipe = parse_recipe_html(&html).unwrap();
+    assert_eq!(recipe.reference(), "https://example.com/tea");
+    assert_eq!(recipe.servings(), Some(2));
+    assert_eq!(recipe.instructions(), "<p>Boil water.</p><p>Steep for 3 minutes &amp; serve.</p>");
+    assert_eq!(recipe.ingredients().len(), 1);
+    assert_eq!(recipe.image(), None);
+}
+
+#[test]
+/// Tests if the `parse_recipe_html` function rejects pages without recipe.
+fn test_parse_recipe_html_missing() {
+    assert!(parse_recipe_html("<html><body>No recipe</body></html>").is_err());
+    assert!(parse_recipe_html(&html_page(&json!({"@type": "Recipe"}))).is_err());
+    assert!(parse_recipe_html(
+        "<script type=\"application/ld+json\">{\"@type\": \"Recipe\", invalid</script>"
+    )
+    .is_err());
+}
+
+#[test]
+/// Tests if the `ImportedIngredient::parse` function splits amounts and known units only.
+fn test_imported_ingredient_parse() {
+    let expected = |amount: &str, unit: &str, text: &str| {
+        (amount.to_string(), unit.to_string(), text.to_string())
+    };
+    assert_eq!(ingredient("1-2 tbsp. oil"), expected("1-2", "tbsp.", "oil"));
+    assert_eq!(ingredient("½ tsp salt"), expected("½", "tsp", "salt"));
+    assert_eq!(ingredient("2 cloves garlic"), expected("2", "", "cloves garlic"));
+    assert_eq!(ingredient("1.5kg potatoes"), expected("1.5", "kg", "potatoes"));
+    assert_eq!(ingredient("3"), expected("", "", "3"));
+    assert_eq!(ingredient("Salt &amp; pepper"), expected("", "", "Salt & pepper"));
+}
@@ -0,0 +1,213 @@
+//! The `report_service` module aggregates the spending of [`Payment`]s per month,
+//! per tag and per person.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Datelike, Utc};
+use serde::Serialize;
+
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    entity::payment::Payment,
+};
+
+use super::{currency_service::CurrencyConverter, occurrence_service::occurrences};
+
+/// The header of the CSV rendering of a report.
+const REPORT_CSV_HEADER: [&str; 4] = ["month", "category", "name", "amount"];
+/// The CSV category of the monthly total.
+const REPORT_CSV_CATEGORY_TOTAL: &str = "total";
+/// The CSV category of tag totals.
+const REPORT_CSV_CATEGORY_TAG: &str = "tag";
+/// The CSV category of person totals.
+const REPORT_CSV_CATEGORY_PERSON: &str = "person";
+/// The month value of the CSV rows summarising the whole report.
+const REPORT_CSV_MONTH_ALL: &str = "all";
+/// The maximum number of months a report can span.
+const REPORT_MAXIMUM_MONTHS: i64 = 120;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The spending totals of a time period.
+pub struct SpendingTotals {
+    /// The total amount paid.
+    total: BigDecimal,
+    /// The total amount paid per tag. Payments with multiple tags count towards every tag.
+    tags: BTreeMap<String, BigDecimal>,
+    /// The share of the total every person is involved with.
+    persons: BTreeMap<String, BigDecimal>,
+}
+
+impl SpendingTotals {
+    /// Adds a single payment occurrence to the totals.
+    fn add(
+        &mut self,
+        paid: &HashMap<String, BigDecimal>,
+        involved: &HashMap<String, BigDecimal>,
+        tags: &[String],
+    ) {
+        let paid_total: BigDecimal = paid.values().sum();
+        self.total += &paid_total;
+        for tag in tags {
+            *self
+                .tags
+                .entry(tag.clone())
+                .or_insert_with(BigDecimal::zero) += &paid_total;
+        }
+        for (person, amount) in involved {
+            *self
+                .persons
+                .entry(person.clone())
+                .or_insert_with(BigDecimal::zero) += amount;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A report of the spending within a time window.
+pub struct SpendingReport {
+    /// The currency of all amounts.
+    currency: String,
+    /// The inclusive start of the time window.
+    from: DateTime<Utc>,
+    /// The inclusive end of the time window.
+    to: DateTime<Utc>,
+    /// The totals of every month in the format `YYYY-MM`.
+    months: BTreeMap<String, SpendingTotals>,
+    /// The totals of the whole time window.
+    totals: SpendingTotals,
+}
+
+impl SpendingReport {
+    /// Creates a report of the spending of the specified [`Payment`]s within the time window.
+    /// Recurring payments are expanded into their single occurrences, which are converted into
+    /// the base currency at the date of the respective occurrence.
+    /// Transfers settling balances are not spending and therefore excluded.
+    /// Time windows spanning more than [`REPORT_MAXIMUM_MONTHS`] months are rejected.
+    ///
+    /// # Parameters
+    ///
+    /// * `payments` - the payments to report
+    /// * `from` - the inclusive start of the time window
+    /// * `to` - the inclusive end of the time window
+    /// * `converter` - the converter used to convert all amounts into the base currency
+    pub fn from_payments<'a, T: IntoIterator<Item = &'a Payment>>(
+        payments: T,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        converter: &CurrencyConverter,
+    ) -> Result<Self, HomeworkError> {
+        if month_count(from, to) > REPORT_MAXIMUM_MONTHS {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Report window too long",
+                format!(
+                    "The time window from {} to {} spans more than {} months.",
+                    from, to, REPORT_MAXIMUM_MONTHS
+                ),
+                format!("A report can span at most {} months.", REPORT_MAXIMUM_MONTHS),
+            )));
+        }
+        let mut months: BTreeMap<String, SpendingTotals> = month_keys(from, to)
+            .into_iter()
+            .map(|month| (month, SpendingTotals::default()))
+            .collect();
+        let mut totals = SpendingTotals::default();
+        for payment in payments
+            .into_iter()
+            .filter(|payment| !payment.is_transfer())
+        {
+            for date in occurrences(payment.payment_type(), from, to) {
+                let paid = converter.convert_amounts(payment.paid(), payment.currency(), date)?;
+                let involved =
+                    converter.convert_amounts(payment.involved(), payment.currency(), date)?;
+                months
+                    .entry(month_key(date))
+                    .or_default()
+                    .add(&paid, &involved, payment.tags());
+                totals.add(&paid, &involved, payment.tags());
+            }
+        }
+        Ok(SpendingReport {
+            currency: converter.base_currency().to_string(),
+            from,
+            to,
+            months,
+            totals,
+        })
+    }
+
+    /// Renders the report as CSV with the columns `month`, `category`, `name` and `amount`.
+    /// The category is either the `total`, a `tag` or a `person`. The totals of the whole
+    /// time window are listed with the month `all`.
+    pub fn to_csv(&self) -> Result<String, HomeworkError> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .write_record(REPORT_CSV_HEADER)
+            .map_err(std::io::Error::from)?;
+        for (month, totals) in self
+            .months
+            .iter()
+            .map(|(month, totals)| (month.as_str(), totals))
+            .chain(std::iter::once((REPORT_CSV_MONTH_ALL, &self.totals)))
+        {
+            writer
+                .write_record([
+                    month,
+                    REPORT_CSV_CATEGORY_TOTAL,
+                    "",
+                    &totals.total.to_string(),
+                ])
+                .map_err(std::io::Error::from)?;
+            for (tag, amount) in &totals.tags {
+                writer
+                    .write_record([month, REPORT_CSV_CATEGORY_TAG, tag, &amount.to_string()])
+                    .map_err(std::io::Error::from)?;
+            }
+            for (person, amount) in &totals.persons {
+                writer
+                    .write_record([
+                        month,
+                        REPORT_CSV_CATEGORY_PERSON,
+                        person,
+                        &amount.to_string(),
+                    ])
+                    .map_err(std::io::Error::from)?;
+            }
+        }
+        let content = writer.into_inner().map_err(|err| err.into_error())?;
+        Ok(String::from_utf8_lossy(&content).to_string())
+    }
+}
+
+/// Returns the month key in the format `YYYY-MM` of the specified date.
+fn month_key(date: DateTime<Utc>) -> String {
+    format!("{:04}-{:02}", date.year(), date.month())
+}
+
+/// Returns the number of months the time window touches.
+fn month_count(from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    (i64::from(to.year()) - i64::from(from.year())) * 12 + i64::from(to.month())
+        - i64::from(from.month())
+        + 1
+}
+
+/// Returns the keys of all months within the time window.
+fn month_keys(from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<String> {
+    let mut keys = Vec::new();
+    let (mut year, mut month) = (from.year(), from.month());
+    while (year, month) <= (to.year(), to.month()) {
+        keys.push(format!("{:04}-{:02}", year, month));
+        if month == 12 {
+            year += 1;
+            month = 1;
+        } else {
+            month += 1;
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,160 @@
+use uuid::Uuid;
+
+use crate::entity::{
+    exchange_rate::ExchangeRate,
+    payment::test_builder::{date, decimal, PaymentBuilder},
+};
+
+use super::*;
+
+#[test]
+/// Tests if the `from_payments` function expands recurring payments and aggregates
+/// the amounts per month, tag and person.
+fn test_from_payments() {
+    let payments = vec![
+        PaymentBuilder::new(
+            serde_json::json!({"Anna": "30.00"}),
+            serde_json::json!({"Anna": "15.00", "Ben": "15.00"}),
+        )
+        .payment_type(serde_json::json!({"Monthly": {
+            "distance": 1,
+            "start": "2022-01-15T00:00:00Z",
+            "end": "2022-12-15T00:00:00Z"
+        }}))
+        .tags(&["Rent", "Home"])
+        .build(),
+        PaymentBuilder::new(
+            serde_json::json!({"Ben": "10.00"}),
+            serde_json::json!({"Anna": "10.00"}),
+        )
+        .one_off("2022-02-20T00:00:00Z")
+        .currency("GBP")
+        .build(),
+    ];
+    let converter = CurrencyConverter::new(
+        "EUR",
+        vec![ExchangeRate::new(
+            Uuid::nil(),
+            "GBP",
+            "EUR",
+            decimal("1.15"),
+            date("2022-01-01T00:00:00Z"),
+        )],
+    );
+    let report = SpendingReport::from_payments(
+        &payments,
+        date("2022-01-01T00:00:00Z"),
+        date("2022-03-31T00:00:00Z"),
+        &converter,
+    )
+    .unwrap();
+    assert_eq!(report.currency, "EUR");
+    assert_eq!(report.months.keys().collect::<Vec<_>>(), vec!["2022-01", "2022-02", "2022-03"]);
+    let february = &report.months["2022-02"];
+    assert_eq!(february.total, decimal("41.50"));
+    assert_eq!(february.tags["Rent"], decimal("30.00"));
+    assert_eq!(february.tags["Home"], decimal("30.00"));
+    assert_eq!(february.persons["Anna"], decimal("26.50"));
+    assert_eq!(february.persons["Ben"], decimal("15.00"));
+    assert_eq!(report.months["2022-03"].total, decimal("30.00"));
+    assert_eq!(report.totals.total, decimal("101.50"));
+    assert_eq!(report.totals.tags["Rent"], decimal("90.00"));
+    assert_eq!(report.totals.persons["Anna"], decimal("56.50"));
+    assert_eq!(report.totals.persons["Ben"], decimal("45.00"));
+}
+
+#[test]
+/// Tests if the `from_payments` function lists months without spending.
+fn test_from_payments_empty_months() {
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    let report = SpendingReport::from_payments(
+        &Vec::new(),
+        date("2022-11-30T00:00:00Z"),
+        date("2023-02-01T00:00:00Z"),
+        &converter,
+    )
+    .unwrap();
+    assert_eq!(
+        report.months.keys().collect::<Vec<_>>(),
+        vec!["2022-11", "2022-12", "2023-01", "2023-02"]
+    );
+    assert!(report
+        .months
+        .values()
+        .all(|totals| *totals == SpendingTotals::default()));
+}
+
+#[test]
+/// Tests if the `from_payments` function excludes transfers settling balances.
+fn test_from_payments_transfer() {
+    let transfer = PaymentBuilder::new(
+        serde_json::json!({"Ben": "15.00"}),
+        serde_json::json!({"Anna": "15.00"}),
+    )
+    .target("Anna")
+    .one_off("2022-01-10T00:00:00Z")
+    .transfer()
+    .build();
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    let report = SpendingReport::from_payments(
+        &vec![transfer],
+        date("2022-01-01T00:00:00Z"),
+        date("2022-01-31T00:00:00Z"),
+        &converter,
+    )
+    .unwrap();
+    assert_eq!(report.totals, SpendingTotals::default());
+}
+
+#[test]
+/// Tests if the `to_csv` function renders monthly and overall totals.
+fn test_to_csv() {
+    let payments = vec![PaymentBuilder::new(
+        serde_json::json!({"Anna": "12.50"}),
+        serde_json::json!({"Ben": "12.50"}),
+    )
+    .one_off("2022-01-10T00:00:00Z")
+    .tags(&["Food"])
+    .build()];
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    let report = SpendingReport::from_payments(
+        &payments,
+        date("2022-01-01T00:00:00Z"),
+        date("2022-01-31T00:00:00Z"),
+        &converter,
+    )
+    .unwrap();
+    assert_eq!(
+        report.to_csv().unwrap(),
+        "month,category,name,amount\n\
+         2022-01,total,,12.50\n\
+         2022-01,tag,Food,12.50\n\
+         2022-01,person,Ben,12.50\n\
+         all,total,,12.50\n\
+         all,tag,Food,12.50\n\
+         all,person,Ben,12.50\n"
+    );
+}
+
+#[test]
+/// Tests if time windows spanning too many months are rejected.
+fn test_from_payments_window_too_long() {
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    assert!(matches!(
+        SpendingReport::from_payments(
+            &Vec::new(),
+            date("0001-01-01T00:00:00Z"),
+            date("2022-12-31T00:00:00Z"),
+            &converter,
+        ),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    let report = SpendingReport::from_payments(
+        &Vec::new(),
+        date("2013-01-01T00:00:00Z"),
+        date("2022-12-31T00:00:00Z"),
+        &converter,
+    )
+    .unwrap();
+    assert_eq!(report.months.len(), 120);
+}
@@ -0,0 +1,187 @@
+//! The `search_service` module maintains a full-text index over all recipes,
+//! so they can be searched without loading every recipe.
+
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::application::error::{HomeworkError, InternalError};
+
+/// The query selecting the searchable content of recipes in the order of the index columns.
+const INDEX_CONTENT_QUERY: &str = "
+    SELECT recipe.id, recipe.title, recipe.instructions, recipe.reference,
+        (SELECT group_concat(ingredient.text || ' ' || IFNULL(ingredient.filter_text, ''), ' ')
+            FROM ingredient WHERE ingredient.recipe_id = recipe.id),
+        (SELECT group_concat(tag, ' ')
+            FROM tag_recipe_mapping WHERE tag_recipe_mapping.recipe_id = recipe.id)
+    FROM recipe";
+/// The weights of the indexed columns for ranking, in the order of the columns:
+/// recipe ID, title, instructions, reference, ingredients and tags.
+const RANK_COLUMN_WEIGHTS: &str = "0.0, 10.0, 1.0, 2.0, 5.0, 5.0";
+/// The maximum number of tokens of a snippet.
+const SNIPPET_MAXIMUM_TOKENS: usize = 16;
+/// The text marking omitted text at the start or end of a snippet.
+const SNIPPET_ELLIPSIS: &str = "…";
+/// The marker inserted by SQLite before a match. It is a private use character,
+/// so it cannot be confused with the indexed text when escaping the snippet.
+const MARKER_MATCH_START: char = '\u{E000}';
+/// The marker inserted by SQLite after a match.
+const MARKER_MATCH_END: char = '\u{E001}';
+/// The HTML inserted before a match.
+const HIGHLIGHT_START: &str = "<mark>";
+/// The HTML inserted after a match.
+const HIGHLIGHT_END: &str = "</mark>";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// A recipe matching a full-text search.
+pub struct RecipeSearchResult {
+    /// The ID of the recipe.
+    id: Uuid,
+    /// The title of the recipe as HTML with all matches highlighted.
+    title: String,
+    /// An excerpt of the best matching text of the recipe as HTML with all matches highlighted.
+    snippet: String,
+    /// The relevance of the recipe. Higher values are more relevant.
+    score: f64,
+}
+
+impl TryFrom<&Row<'_>> for RecipeSearchResult {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+        let title: String = row.get(1)?;
+        let snippet: String = row.get(2)?;
+        let rank: f64 = row.get(3)?;
+        Ok(RecipeSearchResult {
+            id: row.get(0)?,
+            title: highlight_html(&title),
+            snippet: highlight_html(&snippet),
+            // BM25 ranks are negative with more relevant results being lower.
+            score: -rank,
+        })
+    }
+}
+
+/// Searches all recipes for the query ordered by relevance. Every word of the query
+/// has to occur in the title, instructions, reference, ingredients or tags of a recipe.
+/// Words are matched as prefixes ignoring case and diacritics.
+///
+/// # Parameters
+///
+/// * `query` - the words to search for
+/// * `connection` - the database connection
+pub fn search_recipe_index(
+    query: &str,
+    connection: &Connection,
+) -> Result<Vec<RecipeSearchResult>, HomeworkError> {
+    let match_expression = match_expression(query).ok_or_else(|| {
+        HomeworkError::BadRequestError(InternalError::new(
+            "Invalid search",
+            format!("The search query \"{}\" does not contain any words.", query),
+            "The search query must not be empty.",
+        ))
+    })?;
+    let mut stmt = connection.prepare(&format!(
+        "SELECT recipe_id,
+                highlight(recipe_search, 1, ?2, ?3),
+                snippet(recipe_search, -1, ?2, ?3, ?4, ?5),
+                bm25(recipe_search, {}) AS rank
+            FROM recipe_search
+            WHERE recipe_search MATCH ?1
+            ORDER BY rank",
+        RANK_COLUMN_WEIGHTS
+    ))?;
+    let result_rows = stmt.query_map(
+        params![
+            match_expression,
+            MARKER_MATCH_START.to_string(),
+            MARKER_MATCH_END.to_string(),
+            SNIPPET_ELLIPSIS,
+            SNIPPET_MAXIMUM_TOKENS
+        ],
+        |row| RecipeSearchResult::try_from(row),
+    )?;
+    let mut results = Vec::new();
+    for result in result_rows {
+        results.push(result?);
+    }
+    Ok(results)
+}
+
+/// Replaces the indexed content of the recipe with its current content.
+/// This needs to be called after every change of the searchable content of a recipe
+/// including its ingredients and tags. Recipes that do not exist are removed from the index.
+///
+/// # Parameters
+///
+/// * `recipe_id` - the ID of the recipe
+/// * `connection` - the database connection
+pub fn index_recipe(recipe_id: Uuid, connection: &Connection) -> Result<(), rusqlite::Error> {
+    connection.execute("DELETE FROM recipe_search WHERE recipe_id = ?1", [recipe_id])?;
+    connection.execute(
+        &format!("INSERT INTO recipe_search {} WHERE recipe.id = ?1", INDEX_CONTENT_QUERY),
+        [recipe_id],
+    )?;
+    Ok(())
+}
+
+/// Rebuilds the index if it does not contain exactly the stored recipes,
+/// e.g. when the index has been added to a database created by an earlier version
+/// of the application.
+///
+/// # Parameters
+///
+/// * `connection` - the database connection
+pub fn synchronise_recipe_index(connection: &Connection) -> Result<(), rusqlite::Error> {
+    let in_sync: bool = connection.query_row(
+        "SELECT (SELECT COUNT(*) FROM recipe) = (SELECT COUNT(*) FROM recipe_search)
+            AND NOT EXISTS (
+                SELECT 1 FROM recipe
+                WHERE NOT EXISTS (SELECT 1 FROM recipe_search WHERE recipe_id = recipe.id)
+            )",
+        [],
+        |row| row.get(0),
+    )?;
+    if !in_sync {
+        connection.execute("DELETE FROM recipe_search", [])?;
+        connection.execute(&format!("INSERT INTO recipe_search {}", INDEX_CONTENT_QUERY), [])?;
+    }
+    Ok(())
+}
+
+/// Converts the free-text query into an FTS5 expression matching all words as prefixes.
+/// Every word is quoted, so characters with a special meaning in FTS5 are searched literally.
+/// Returns `None` if the query does not contain any words.
+fn match_expression(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+/// Escapes the text as HTML and replaces the match markers with highlighting tags.
+fn highlight_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            MARKER_MATCH_START => html.push_str(HIGHLIGHT_START),
+            MARKER_MATCH_END => html.push_str(HIGHLIGHT_END),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(character),
+        }
+    }
+    html
+}
+
+#[cfg(test)]
+mod test;
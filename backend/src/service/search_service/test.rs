@@ -0,0 +1,107 @@
+use serde_json::json;
+
+use crate::{
+    application::config::Configuration,
+    entity::{ingredient::Ingredient, recipe::Recipe},
+};
+
+use super::*;
+
+/// Creates an ingredient of the recipe.
+fn ingredient(recipe_id: Uuid, text: &str) -> Ingredient {
+    serde_json::from_value(json!({
+        "id": Configuration::generate_uuid(),
+        "amount": "1",
+        "unit": "",
+        "text": text,
+        "creationTime": "2022-01-01T00:00:00Z",
+        "recipeReference": null,
+        "recipeId": recipe_id,
+        "ordering": 0,
+        "filterText": null,
+    }))
+    .unwrap()
+}
+
+/// Returns the IDs of all recipes matching the query in the order of relevance.
+fn search(query: &str, connection: &Connection) -> Vec<Uuid> {
+    search_recipe_index(query, connection)
+        .unwrap()
+        .iter()
+        .map(|result| result.id)
+        .collect()
+}
+
+#[test]
+/// Tests if the `search_recipe_index` function ranks title matches first and highlights matches.
+fn test_search_recipe_index() {
+    let connection = Configuration::in_memory_database_connection();
+    let (soup, salad) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    Recipe::insert_into_database_new_entry(soup, "Tomato soup", &connection).unwrap();
+    Recipe::insert_into_database_new_entry(salad, "Crème brûlée", &connection).unwrap();
+    Recipe::update_in_database_string_column(
+        salad,
+        "instructions",
+        "Serve with a <b>fresh</b> tomato & basil salad on the side.",
+        &connection,
+    )
+    .unwrap();
+    ingredient(soup, "Basil leaves")
+        .insert_into_database(&connection)
+        .unwrap();
+    Recipe::update_in_database_insert_tag(salad, "Dessert", &connection).unwrap();
+
+    assert_eq!(search("tomato", &connection), vec![soup, salad]);
+    assert_eq!(search("TOM basil", &connection), vec![soup, salad]);
+    assert_eq!(search("creme", &connection), vec![salad]);
+    assert_eq!(search("dessert", &connection), vec![salad]);
+    assert_eq!(search("\"soup OR", &connection), Vec::<Uuid>::new());
+    assert!(search_recipe_index("  ", &connection).is_err());
+
+    let results = search_recipe_index("tomato", &connection).unwrap();
+    assert_eq!(results[0].title, "<mark>Tomato</mark> soup");
+    assert!(results[0].score > results[1].score);
+    assert_eq!(results[1].title, "Crème brûlée");
+    assert!(results[1]
+        .snippet
+        .contains("&lt;b&gt;fresh&lt;/b&gt; <mark>tomato</mark> &amp; basil"));
+}
+
+#[test]
+/// Tests if the index follows changes and deletions of recipes and ingredients.
+fn test_index_recipe() {
+    let connection = Configuration::in_memory_database_connection();
+    let recipe = Uuid::from_u128(1);
+    Recipe::insert_into_database_new_entry(recipe, "Pancakes", &connection).unwrap();
+    let eggs = ingredient(recipe, "Eggs");
+    eggs.insert_into_database(&connection).unwrap();
+    assert_eq!(search("eggs", &connection), vec![recipe]);
+
+    Ingredient::delete_from_database_by_id(eggs.id(), &connection).unwrap();
+    assert!(search("eggs", &connection).is_empty());
+
+    Recipe::update_in_database_string_column(recipe, "title", "Waffles", &connection).unwrap();
+    assert!(search("pancakes", &connection).is_empty());
+    assert_eq!(search("waffles", &connection), vec![recipe]);
+
+    Recipe::update_in_database_insert_tag(recipe, "Breakfast", &connection).unwrap();
+    assert_eq!(search("breakfast", &connection), vec![recipe]);
+    Recipe::update_in_database_delete_tag(recipe, "Breakfast", &connection).unwrap();
+    assert!(search("breakfast", &connection).is_empty());
+
+    Recipe::delete_from_database_by_id(recipe, &connection).unwrap();
+    assert!(search("waffles", &connection).is_empty());
+}
+
+#[test]
+/// Tests if the `synchronise_recipe_index` function indexes recipes missing from the index.
+fn test_synchronise_recipe_index() {
+    let connection = Configuration::in_memory_database_connection();
+    let recipe = Uuid::from_u128(1);
+    Recipe::insert_into_database_new_entry(recipe, "Pancakes", &connection).unwrap();
+    connection.execute("DELETE FROM recipe_search", []).unwrap();
+    assert!(search("pancakes", &connection).is_empty());
+
+    synchronise_recipe_index(&connection).unwrap();
+    assert_eq!(search("pancakes", &connection), vec![recipe]);
+}
@@ -0,0 +1,303 @@
+//! The `settlement_service` module aggregates the amounts of [`Payment`]s and
+//! computes the transfers that are needed to settle all open balances.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bigdecimal::{BigDecimal, Signed, Zero};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    entity::payment::Payment,
+};
+
+use super::{
+    currency_service::CurrencyConverter,
+    occurrence_service::{occurrence_count, occurrences},
+};
+
+/// The maximum number of occurrences of all payments that are settled at once.
+/// Recurring payments with an early start date would otherwise be expanded
+/// into an unbounded number of occurrences.
+const SETTLEMENT_MAXIMUM_OCCURRENCES: u64 = 100_000;
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The result of settling a set of [`Payment`]s.
+pub struct Settlement {
+    /// The currency of all balances and transfers.
+    currency: String,
+    /// The net balance of every person. Positive balances are owed to the person,
+    /// negative balances are owed by the person.
+    balances: BTreeMap<String, BigDecimal>,
+    /// The transfers that settle all balances.
+    transfers: Vec<Transfer>,
+}
+
+impl Settlement {
+    /// Computes the settlement of the specified [`Payment`]s in the base currency
+    /// of the converter.
+    ///
+    /// # Parameters
+    ///
+    /// * `payments` - the payments to settle
+    /// * `until` - the inclusive end of the time window, usually now
+    /// * `converter` - the converter used to convert all amounts into the base currency
+    pub fn from_payments<'a, T: IntoIterator<Item = &'a Payment>>(
+        payments: T,
+        until: DateTime<Utc>,
+        converter: &CurrencyConverter,
+    ) -> Result<Self, HomeworkError> {
+        let balances = net_balances(payments, until, converter)?;
+        let transfers = settle(&balances);
+        Ok(Settlement {
+            currency: converter.base_currency().to_string(),
+            balances,
+            transfers,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A single transfer of money from one person to another.
+pub struct Transfer {
+    from: String,
+    to: String,
+    amount: BigDecimal,
+}
+
+impl Transfer {
+    /// Creates a new `Transfer`.
+    ///
+    /// # Parameters
+    ///
+    /// * `from` - the person paying
+    /// * `to` - the person receiving the money
+    /// * `amount` - the transferred amount
+    pub fn new<T: ToString, U: ToString>(from: T, to: U, amount: BigDecimal) -> Self {
+        Transfer {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+        }
+    }
+
+    /// Returns the person paying.
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// Returns the person receiving the money.
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// Returns the transferred amount.
+    pub fn amount(&self) -> &BigDecimal {
+        &self.amount
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+/// The balances of all persons after a single occurrence of a [`Payment`].
+pub struct BalanceHistoryEntry {
+    /// The ID of the payment.
+    payment_id: Uuid,
+    /// The date of the occurrence.
+    date: DateTime<Utc>,
+    /// The target of the payment.
+    target: String,
+    /// If the payment is a transfer settling balances.
+    transfer: bool,
+    /// The net balance of every person after the payment.
+    balances: BTreeMap<String, BigDecimal>,
+    /// The persons whose open balance was cleared by the payment.
+    cleared: Vec<String>,
+}
+
+/// Computes the net balance of every person involved in the specified [`Payment`]s.
+/// The balance is the sum of all paid amounts minus the sum of all amounts the person
+/// is involved in. Recurring payments count once for every occurrence until the end
+/// of the time window, like in the spending report, and future payments are not due yet.
+/// All amounts are converted into the base currency at the date of the respective occurrence.
+/// Payments with more than [`SETTLEMENT_MAXIMUM_OCCURRENCES`] occurrences in total are rejected.
+///
+/// # Parameters
+///
+/// * `payments` - the payments to aggregate
+/// * `until` - the inclusive end of the time window, usually now
+/// * `converter` - the converter used to convert all amounts into the base currency
+pub fn net_balances<'a, T: IntoIterator<Item = &'a Payment>>(
+    payments: T,
+    until: DateTime<Utc>,
+    converter: &CurrencyConverter,
+) -> Result<BTreeMap<String, BigDecimal>, HomeworkError> {
+    let mut balances: BTreeMap<String, BigDecimal> = BTreeMap::new();
+    for (date, payment) in payment_occurrences(payments, until)? {
+        let paid = converter.convert_amounts(payment.paid(), payment.currency(), date)?;
+        let involved = converter.convert_amounts(payment.involved(), payment.currency(), date)?;
+        add_to_balances(&mut balances, &paid, false);
+        add_to_balances(&mut balances, &involved, true);
+    }
+    Ok(balances)
+}
+
+/// Computes the net balances of all persons after every single occurrence of a [`Payment`]
+/// ordered by date. The final balances equal the ones computed by [`net_balances`],
+/// so the history shows when a debt arose and when it was cleared.
+///
+/// # Parameters
+///
+/// * `payments` - the payments to aggregate
+/// * `until` - the inclusive end of the time window, usually now
+/// * `converter` - the converter used to convert all amounts into the base currency
+pub fn balance_history<'a, T: IntoIterator<Item = &'a Payment>>(
+    payments: T,
+    until: DateTime<Utc>,
+    converter: &CurrencyConverter,
+) -> Result<Vec<BalanceHistoryEntry>, HomeworkError> {
+    let mut occurrences = payment_occurrences(payments, until)?;
+    occurrences.sort_by_key(|(date, payment)| (*date, payment.id()));
+    let mut balances: BTreeMap<String, BigDecimal> = BTreeMap::new();
+    let mut history = Vec::new();
+    for (date, payment) in occurrences {
+        let paid = converter.convert_amounts(payment.paid(), payment.currency(), date)?;
+        let involved = converter.convert_amounts(payment.involved(), payment.currency(), date)?;
+        let open: Vec<String> = balances
+            .iter()
+            .filter(|(_, balance)| !balance.is_zero())
+            .map(|(person, _)| person.clone())
+            .collect();
+        add_to_balances(&mut balances, &paid, false);
+        add_to_balances(&mut balances, &involved, true);
+        history.push(BalanceHistoryEntry {
+            payment_id: payment.id(),
+            date,
+            target: payment.target().to_string(),
+            transfer: payment.is_transfer(),
+            cleared: open
+                .into_iter()
+                .filter(|person| balances.get(person).is_some_and(BigDecimal::is_zero))
+                .collect(),
+            balances: balances.clone(),
+        });
+    }
+    Ok(history)
+}
+
+/// Expands the [`Payment`]s into the dates of all their occurrences until the specified date.
+/// The occurrences are counted before, so payments with more than
+/// [`SETTLEMENT_MAXIMUM_OCCURRENCES`] occurrences are rejected without expanding them.
+///
+/// # Parameters
+///
+/// * `payments` - the payments to expand
+/// * `until` - the inclusive end of the time window
+fn payment_occurrences<'a, T: IntoIterator<Item = &'a Payment>>(
+    payments: T,
+    until: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, &'a Payment)>, HomeworkError> {
+    let payments: Vec<&Payment> = payments.into_iter().collect();
+    let count = payments.iter().fold(0u64, |count, payment| {
+        count.saturating_add(occurrence_count(
+            payment.payment_type(),
+            payment.payment_type().start(),
+            until,
+        ))
+    });
+    if count > SETTLEMENT_MAXIMUM_OCCURRENCES {
+        return Err(HomeworkError::BadRequestError(InternalError::new(
+            "Too many occurrences",
+            format!(
+                "The payments occur {} times until {}, which is more than the maximum of {}.",
+                count, until, SETTLEMENT_MAXIMUM_OCCURRENCES
+            ),
+            format!(
+                "Balances can be computed for at most {} occurrences of payments.",
+                SETTLEMENT_MAXIMUM_OCCURRENCES
+            ),
+        )));
+    }
+    Ok(payments
+        .into_iter()
+        .flat_map(|payment| {
+            occurrences(payment.payment_type(), payment.payment_type().start(), until)
+                .into_iter()
+                .map(move |date| (date, payment))
+        })
+        .collect())
+}
+
+/// Adds (or subtracts) the specified amounts to the balances.
+fn add_to_balances(
+    balances: &mut BTreeMap<String, BigDecimal>,
+    amounts: &HashMap<String, BigDecimal>,
+    subtract: bool,
+) {
+    for (person, amount) in amounts {
+        let balance = balances
+            .entry(person.clone())
+            .or_insert_with(BigDecimal::zero);
+        if subtract {
+            *balance -= amount;
+        } else {
+            *balance += amount;
+        }
+    }
+}
+
+/// Computes a list of transfers that settles the specified balances.
+/// The person owing the most always pays the person who is owed the most, which
+/// results in at most `n - 1` transfers for `n` people with a non-zero balance.
+/// Ties are resolved by name so the result is deterministic.
+///
+/// # Parameters
+///
+/// * `balances` - the net balances of all persons
+pub fn settle(balances: &BTreeMap<String, BigDecimal>) -> Vec<Transfer> {
+    let mut creditors: Vec<(String, BigDecimal)> = balances
+        .iter()
+        .filter(|(_, balance)| balance.is_positive())
+        .map(|(person, balance)| (person.clone(), balance.clone()))
+        .collect();
+    let mut debtors: Vec<(String, BigDecimal)> = balances
+        .iter()
+        .filter(|(_, balance)| balance.is_negative())
+        .map(|(person, balance)| (person.clone(), -balance))
+        .collect();
+    let mut transfers = Vec::new();
+    sort_by_amount(&mut creditors);
+    sort_by_amount(&mut debtors);
+    while let (Some((creditor, credit)), Some((debtor, debt))) = (creditors.pop(), debtors.pop()) {
+        let amount = if credit < debt {
+            credit.clone()
+        } else {
+            debt.clone()
+        };
+        let remaining_credit = credit - &amount;
+        let remaining_debt = debt - &amount;
+        transfers.push(Transfer::new(&debtor, &creditor, amount));
+        if remaining_credit.is_positive() {
+            creditors.push((creditor, remaining_credit));
+        }
+        if remaining_debt.is_positive() {
+            debtors.push((debtor, remaining_debt));
+        }
+        sort_by_amount(&mut creditors);
+        sort_by_amount(&mut debtors);
+    }
+    transfers
+}
+
+/// Sorts the amounts ascending so that the largest amount is at the end of the vector.
+/// Equal amounts are sorted by descending name.
+fn sort_by_amount(amounts: &mut [(String, BigDecimal)]) {
+    amounts.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)));
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,257 @@
+use std::str::FromStr;
+
+use crate::entity::{
+    exchange_rate::ExchangeRate,
+    payment::test_builder::{date, PaymentBuilder},
+};
+
+use super::*;
+
+/// Creates a balance map from the specified person and amount pairs.
+fn balances(entries: &[(&str, &str)]) -> BTreeMap<String, BigDecimal> {
+    entries
+        .iter()
+        .map(|(person, amount)| (person.to_string(), BigDecimal::from_str(amount).unwrap()))
+        .collect()
+}
+
+#[test]
+/// Tests if the `net_balances` function sums paid and involved amounts per person.
+fn test_net_balances() {
+    let payments = vec![
+        PaymentBuilder::new(
+            serde_json::json!({"Anna": "30.00"}),
+            serde_json::json!({"Anna": "10.00", "Ben": "10.00", "Carl": "10.00"}),
+        )
+        .build(),
+        PaymentBuilder::new(
+            serde_json::json!({"Ben": "12.40"}),
+            serde_json::json!({"Anna": "12.40"}),
+        )
+        .build(),
+    ];
+    assert_eq!(
+        net_balances(
+            &payments,
+            date("2023-01-01T00:00:00Z"),
+            &CurrencyConverter::new("EUR", Vec::new())
+        )
+        .unwrap(),
+        balances(&[("Anna", "7.60"), ("Ben", "2.40"), ("Carl", "-10.00")])
+    );
+}
+
+#[test]
+/// Tests if the `net_balances` function counts recurring payments once for every occurrence
+/// until the end of the time window and ignores payments that are not due yet.
+fn test_net_balances_recurring() {
+    let payments = vec![
+        PaymentBuilder::new(
+            serde_json::json!({"Anna": "500.00"}),
+            serde_json::json!({"Ben": "500.00"}),
+        )
+        .payment_type(serde_json::json!({"Monthly": {
+            "distance": 1,
+            "start": "2022-01-01T00:00:00Z",
+            "end": "2022-12-01T00:00:00Z"
+        }}))
+        .build(),
+        PaymentBuilder::new(
+            serde_json::json!({"Ben": "50.00"}),
+            serde_json::json!({"Anna": "50.00"}),
+        )
+        .one_off("2022-04-01T00:00:00Z")
+        .build(),
+    ];
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    assert_eq!(
+        net_balances(&payments, date("2022-03-15T00:00:00Z"), &converter).unwrap(),
+        balances(&[("Anna", "1500.00"), ("Ben", "-1500.00")])
+    );
+    assert_eq!(
+        net_balances(&payments, date("2023-01-01T00:00:00Z"), &converter).unwrap(),
+        balances(&[("Anna", "5950.00"), ("Ben", "-5950.00")])
+    );
+    let history = balance_history(&payments, date("2022-04-01T00:00:00Z"), &converter).unwrap();
+    assert_eq!(
+        history.iter().map(|entry| entry.date).collect::<Vec<_>>(),
+        vec![
+            date("2022-01-01T00:00:00Z"),
+            date("2022-02-01T00:00:00Z"),
+            date("2022-03-01T00:00:00Z"),
+            date("2022-04-01T00:00:00Z"),
+            date("2022-04-01T00:00:00Z"),
+        ]
+    );
+    assert_eq!(history[4].balances, balances(&[("Anna", "1950.00"), ("Ben", "-1950.00")]));
+}
+
+#[test]
+/// Tests if payments occurring more often than allowed are rejected without expanding them.
+fn test_net_balances_too_many_occurrences() {
+    let payments = vec![PaymentBuilder::new(
+        serde_json::json!({"Anna": "1.00"}),
+        serde_json::json!({"Ben": "1.00"}),
+    )
+    .payment_type(serde_json::json!({"Daily": {
+        "distance": 1,
+        "start": "1000-01-01T00:00:00Z",
+        "end": "9999-12-31T00:00:00Z"
+    }}))
+    .build()];
+    let converter = CurrencyConverter::new("EUR", Vec::new());
+    for result in [
+        net_balances(&payments, date("2022-01-01T00:00:00Z"), &converter).map(|_| ()),
+        balance_history(&payments, date("2022-01-01T00:00:00Z"), &converter).map(|_| ()),
+    ] {
+        assert!(matches!(result, Err(HomeworkError::BadRequestError(_))));
+    }
+    assert!(net_balances(&payments, date("1000-12-31T00:00:00Z"), &converter).is_ok());
+}
+
+#[test]
+/// Tests if the `net_balances` function converts all amounts into the base currency.
+fn test_net_balances_currency() {
+    let payments = vec![
+        PaymentBuilder::new(
+            serde_json::json!({"Anna": "20.00"}),
+            serde_json::json!({"Ben": "20.00"}),
+        )
+        .build(),
+        PaymentBuilder::new(
+            serde_json::json!({"Ben": "10.00"}),
+            serde_json::json!({"Anna": "10.00"}),
+        )
+        .currency("GBP")
+        .build(),
+    ];
+    let rate = ExchangeRate::new(
+        uuid::Uuid::nil(),
+        "GBP",
+        "EUR",
+        BigDecimal::from_str("1.15").unwrap(),
+        "2021-01-01T00:00:00Z".parse().unwrap(),
+    );
+    assert_eq!(
+        net_balances(
+            &payments,
+            date("2023-01-01T00:00:00Z"),
+            &CurrencyConverter::new("EUR", vec![rate])
+        )
+        .unwrap(),
+        balances(&[("Anna", "8.50"), ("Ben", "-8.50")])
+    );
+    assert!(net_balances(
+        &payments,
+        date("2023-01-01T00:00:00Z"),
+        &CurrencyConverter::new("EUR", Vec::new())
+    )
+    .is_err());
+}
+
+#[test]
+/// Tests if converted payments still balance, so settling them leaves no stray cent.
+fn test_net_balances_currency_rounding() {
+    let payments = vec![PaymentBuilder::new(
+        serde_json::json!({"Anna": "10.00"}),
+        serde_json::json!({"Anna": "3.34", "Ben": "3.33", "Carl": "3.33"}),
+    )
+    .currency("GBP")
+    .build()];
+    let rate = ExchangeRate::new(
+        uuid::Uuid::nil(),
+        "GBP",
+        "EUR",
+        BigDecimal::from_str("1.07").unwrap(),
+        "2021-01-01T00:00:00Z".parse().unwrap(),
+    );
+    let balances = net_balances(
+        &payments,
+        date("2023-01-01T00:00:00Z"),
+        &CurrencyConverter::new("EUR", vec![rate]),
+    )
+    .unwrap();
+    assert!(balances.values().sum::<BigDecimal>().is_zero());
+    assert_eq!(
+        settle(&balances),
+        vec![
+            Transfer::new("Ben", "Anna", BigDecimal::from_str("3.56").unwrap()),
+            Transfer::new("Carl", "Anna", BigDecimal::from_str("3.56").unwrap()),
+        ]
+    );
+}
+
+#[test]
+/// Tests if the `settle` function creates transfers that settle all balances.
+fn test_settle() {
+    let transfers = settle(&balances(&[("Anna", "7.60"), ("Ben", "2.40"), ("Carl", "-10.00")]));
+    assert_eq!(
+        transfers,
+        vec![
+            Transfer::new("Carl", "Anna", BigDecimal::from_str("7.60").unwrap()),
+            Transfer::new("Carl", "Ben", BigDecimal::from_str("2.40").unwrap()),
+        ]
+    );
+}
+
+#[test]
+/// Tests if the `settle` function creates no transfers for settled balances.
+fn test_settle_balanced() {
+    assert!(settle(&balances(&[("Anna", "0"), ("Ben", "0.00")])).is_empty());
+    assert!(settle(&BTreeMap::new()).is_empty());
+}
+
+#[test]
+/// Tests if the `settle` function needs at most one transfer less than there are people.
+fn test_settle_transfer_count() {
+    let balances = balances(&[
+        ("Anna", "-25.10"),
+        ("Ben", "40.00"),
+        ("Carl", "-4.90"),
+        ("Dora", "-10.00"),
+        ("Emil", "0.00"),
+    ]);
+    let transfers = settle(&balances);
+    assert!(transfers.len() <= 3);
+    let mut settled = balances.clone();
+    for transfer in &transfers {
+        *settled.get_mut(&transfer.from).unwrap() += &transfer.amount;
+        *settled.get_mut(&transfer.to).unwrap() -= &transfer.amount;
+    }
+    assert!(settled.values().all(|balance| balance.is_zero()));
+}
+
+#[test]
+/// Tests if the `balance_history` function orders payments by date and reports
+/// when a transfer clears the open balances.
+fn test_balance_history() {
+    let transfer = PaymentBuilder::new(
+        serde_json::json!({"Ben": "15.00"}),
+        serde_json::json!({"Anna": "15.00"}),
+    )
+    .target("Anna")
+    .one_off("2022-02-01T00:00:00Z")
+    .transfer()
+    .build();
+    let payments = vec![
+        transfer,
+        PaymentBuilder::new(
+            serde_json::json!({"Anna": "30.00"}),
+            serde_json::json!({"Anna": "15.00", "Ben": "15.00"}),
+        )
+        .build(),
+    ];
+    let history = balance_history(
+        &payments,
+        date("2023-01-01T00:00:00Z"),
+        &CurrencyConverter::new("EUR", Vec::new()),
+    )
+    .unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(!history[0].transfer);
+    assert_eq!(history[0].balances, balances(&[("Anna", "15.00"), ("Ben", "-15.00")]));
+    assert!(history[0].cleared.is_empty());
+    assert!(history[1].transfer);
+    assert_eq!(history[1].balances, balances(&[("Anna", "0.00"), ("Ben", "0.00")]));
+    assert_eq!(history[1].cleared, vec!["Anna", "Ben"]);
+}
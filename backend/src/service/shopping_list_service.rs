@@ -0,0 +1,175 @@
+//! The `shopping_list_service` module combines the [`Ingredient`]s of recipes
+//! into the items of a [`ShoppingList`](crate::entity::shopping_list::ShoppingList).
+
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    application::{config::Configuration, error::HomeworkError},
+    entity::{ingredient::Ingredient, recipe::Recipe, shopping_list::ShoppingListItem},
+};
+
+use super::{
+    quantity_service::{Fraction, Quantity},
+    sub_recipe_service::{expand_sub_recipes, flatten_sub_recipes},
+    unit_service::{Measure, Unit},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A recipe to shop the ingredients for.
+pub struct RecipeSelection {
+    /// The ID of the recipe.
+    recipe_id: Uuid,
+    /// The number of servings to shop for. Defaults to the servings of the recipe.
+    servings: Option<u32>,
+}
+
+impl RecipeSelection {
+    /// Creates a new `RecipeSelection`.
+    ///
+    /// # Parameters
+    ///
+    /// * `recipe_id` - the ID of the recipe
+    /// * `servings` - the number of servings to shop for
+    pub fn new(recipe_id: Uuid, servings: Option<u32>) -> Self {
+        RecipeSelection {
+            recipe_id,
+            servings,
+        }
+    }
+}
+
+/// Returns the ingredients of all selected recipes scaled to the selected servings.
+/// Ingredients referencing another recipe are replaced by the ingredients of the
+/// referenced recipe. If the referenced recipe specifies a number of servings, the amount
+/// of the referencing ingredient is interpreted as servings, otherwise as multiple of the recipe.
+///
+/// # Parameters
+///
+/// * `selections` - the selected recipes
+/// * `connection` - the database connection
+pub fn expand_ingredients(
+    selections: &[RecipeSelection],
+    connection: &Connection,
+) -> Result<Vec<Ingredient>, HomeworkError> {
+    let mut ingredients = Vec::new();
+    for selection in selections {
+        let factor = match selection.servings {
+            Some(servings) => Recipe::select_from_database_by_id(selection.recipe_id, connection)?
+                .scaling_factor(servings)?,
+            None => Fraction::from_integer(1),
+        };
+        ingredients.extend(flatten_sub_recipes(expand_sub_recipes(
+            selection.recipe_id,
+            &factor,
+            connection,
+        )?));
+    }
+    Ok(ingredients)
+}
+
+/// The summed amount of a single shopping list item.
+enum Amount {
+    /// A quantity of a known unit.
+    Measured(Measure),
+    /// A quantity of an unknown unit, e.g. `2 pieces`.
+    Counted(Quantity, String),
+    /// An amount that is not a quantity, e.g. `a pinch`.
+    Text(String, String),
+}
+
+/// Combines the ingredients into shopping list items. Ingredients are grouped by their
+/// filter text, falling back to their text, ignoring case. The quantities of a group are
+/// summed in the unit of the first quantity, converting between volume and mass if the
+/// density of an ingredient of the group is known. Quantities that cannot be converted
+/// and amounts that are not quantities are listed separately.
+///
+/// # Parameters
+///
+/// * `ingredients` - the ingredients to combine
+pub fn merge_ingredients(ingredients: &[Ingredient]) -> Vec<ShoppingListItem> {
+    let mut groups: Vec<(String, Vec<&Ingredient>)> = Vec::new();
+    let mut group_indices: HashMap<String, usize> = HashMap::new();
+    for ingredient in ingredients {
+        let name = ingredient
+            .filter_text()
+            .map(str::trim)
+            .filter(|filter_text| !filter_text.is_empty())
+            .unwrap_or_else(|| ingredient.text().trim());
+        let index = *group_indices.entry(name.to_lowercase()).or_insert_with(|| {
+            groups.push((name.to_string(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[index].1.push(ingredient);
+    }
+
+    let mut items = Vec::new();
+    for (name, group) in groups {
+        let density = group
+            .iter()
+            .find_map(|ingredient| ingredient.density_fraction());
+        let mut amounts: Vec<Amount> = Vec::new();
+        for ingredient in group {
+            let amount = match (ingredient.quantity(), Unit::parse(ingredient.unit())) {
+                (Some(quantity), Some(unit)) => Amount::Measured(Measure::new(quantity, unit)),
+                (Some(quantity), None) => {
+                    Amount::Counted(quantity, ingredient.unit().trim().to_string())
+                },
+                (None, _) => Amount::Text(
+                    ingredient.amount().trim().to_string(),
+                    ingredient.unit().trim().to_string(),
+                ),
+            };
+            add_amount(&mut amounts, amount, density.as_ref());
+        }
+        for amount in amounts {
+            let (amount, unit) = match amount {
+                Amount::Measured(measure) => {
+                    (measure.quantity().to_string(), measure.unit().to_string())
+                },
+                Amount::Counted(quantity, unit) => (quantity.to_string(), unit),
+                Amount::Text(amount, unit) => (amount, unit),
+            };
+            items.push(ShoppingListItem::new(Configuration::generate_uuid(), &name, amount, unit));
+        }
+    }
+    items
+}
+
+/// Adds the amount to the first compatible amount or appends it if there is none.
+fn add_amount(amounts: &mut Vec<Amount>, amount: Amount, density: Option<&Fraction>) {
+    for existing in amounts.iter_mut() {
+        let sum = match (&*existing, &amount) {
+            (Amount::Measured(existing), Amount::Measured(measure)) => {
+                existing.checked_add(measure, density).map(Amount::Measured)
+            },
+            (Amount::Counted(existing, existing_unit), Amount::Counted(quantity, unit))
+                if existing_unit.to_lowercase() == unit.to_lowercase() =>
+            {
+                existing
+                    .checked_add(quantity)
+                    .map(|sum| Amount::Counted(sum, existing_unit.clone()))
+            },
+            (Amount::Text(existing_amount, existing_unit), Amount::Text(text, unit))
+                if existing_amount.to_lowercase() == text.to_lowercase()
+                    && existing_unit.to_lowercase() == unit.to_lowercase() =>
+            {
+                // Identical amounts like "a pinch" are only listed once.
+                return;
+            },
+            _ => None,
+        };
+        if let Some(sum) = sum {
+            *existing = sum;
+            return;
+        }
+    }
+    amounts.push(amount);
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,144 @@
+use serde_json::json;
+
+use super::*;
+
+/// Creates an ingredient of the recipe.
+fn ingredient(
+    recipe_id: Uuid,
+    amount: &str,
+    unit: &str,
+    text: &str,
+    filter_text: Option<&str>,
+) -> Ingredient {
+    ingredient_json(recipe_id, amount, unit, text, filter_text, None)
+}
+
+/// Creates an ingredient of the recipe that references a sub-recipe.
+fn sub_recipe(recipe_id: Uuid, amount: &str, text: &str, recipe_reference: Uuid) -> Ingredient {
+    ingredient_json(recipe_id, amount, "", text, None, Some(recipe_reference))
+}
+
+/// Deserializes an ingredient from its JSON representation.
+fn ingredient_json(
+    recipe_id: Uuid,
+    amount: &str,
+    unit: &str,
+    text: &str,
+    filter_text: Option<&str>,
+    recipe_reference: Option<Uuid>,
+) -> Ingredient {
+    serde_json::from_value(json!({
+        "id": Configuration::generate_uuid(),
+        "amount": amount,
+        "unit": unit,
+        "text": text,
+        "creationTime": "2022-01-01T00:00:00Z",
+        "recipeReference": recipe_reference,
+        "recipeId": recipe_id,
+        "ordering": 0,
+        "filterText": filter_text,
+    }))
+    .unwrap()
+}
+
+/// Returns the name, amount and unit of all items.
+fn describe(items: &[ShoppingListItem]) -> Vec<(&str, &str, &str)> {
+    items
+        .iter()
+        .map(|item| (item.name(), item.amount(), item.unit()))
+        .collect()
+}
+
+#[test]
+/// Tests if the `merge_ingredients` function groups by filter text and sums compatible quantities.
+fn test_merge_ingredients() {
+    let recipe = Uuid::nil();
+    let ingredients = vec![
+        ingredient(recipe, "200", "g", "wheat flour, sifted", Some("Flour")),
+        ingredient(recipe, "2", "", "Eggs", None),
+        ingredient(recipe, "0,3", "kg", "flour", None),
+        ingredient(recipe, "a pinch", "", "Salt", None),
+        ingredient(recipe, "1-2", "", "eggs", None),
+        ingredient(recipe, "1", "cup", "Flour", None),
+        ingredient(recipe, "a pinch", "", "salt", None),
+    ];
+
+    assert_eq!(
+        describe(&merge_ingredients(&ingredients)),
+        vec![
+            ("Flour", "500", "g"),
+            ("Flour", "1", "cup"),
+            ("Eggs", "3-4", ""),
+            ("Salt", "a pinch", ""),
+        ]
+    );
+}
+
+#[test]
+/// Tests if the `expand_ingredients` function scales recipes and expands sub-recipes recursively.
+fn test_expand_ingredients() {
+    let connection = Configuration::in_memory_database_connection();
+    let (pizza, dough, sauce) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+    for (id, title, servings) in [
+        (pizza, "Pizza", 2),
+        (dough, "Dough", 4),
+        (sauce, "Sauce", 1),
+    ] {
+        Recipe::insert_into_database_new_entry(id, title, &connection).unwrap();
+        Recipe::update_in_database_servings(id, Some(servings), &connection).unwrap();
+    }
+    for ingredient in [
+        ingredient(pizza, "100", "g", "Cheese", None),
+        sub_recipe(pizza, "2", "Dough", dough),
+        ingredient(dough, "400", "g", "Flour", None),
+        sub_recipe(dough, "1", "Sauce", sauce),
+        ingredient(sauce, "1", "", "Tomato", None),
+    ] {
+        ingredient.insert_into_database(&connection).unwrap();
+    }
+
+    let selections = vec![RecipeSelection {
+        recipe_id: pizza,
+        servings: Some(4),
+    }];
+    let ingredients = expand_ingredients(&selections, &connection).unwrap();
+    assert_eq!(
+        describe(&merge_ingredients(&ingredients)),
+        vec![
+            ("Cheese", "200", "g"),
+            ("Flour", "400", "g"),
+            ("Tomato", "1", "")
+        ]
+    );
+}
+
+#[test]
+/// Tests if the `expand_ingredients` function rejects recipes referencing themselves,
+/// which may still be stored by earlier versions.
+fn test_expand_ingredients_cycle() {
+    let connection = Configuration::in_memory_database_connection();
+    let (first, second) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    Recipe::insert_into_database_new_entry(first, "First", &connection).unwrap();
+    Recipe::insert_into_database_new_entry(second, "Second", &connection).unwrap();
+    sub_recipe(second, "1", "First", first)
+        .insert_into_database(&connection)
+        .unwrap();
+    assert!(sub_recipe(first, "1", "Second", second)
+        .insert_into_database(&connection)
+        .is_err());
+    ingredient(first, "1", "", "Second", None)
+        .insert_into_database(&connection)
+        .unwrap();
+    connection
+        .execute(
+            "UPDATE ingredient SET recipe_reference = ?1 WHERE recipe_id = ?2",
+            [second, first],
+        )
+        .unwrap();
+
+    let selections = vec![RecipeSelection {
+        recipe_id: first,
+        servings: None,
+    }];
+    assert!(expand_ingredients(&selections, &connection).is_err());
+}
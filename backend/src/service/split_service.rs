@@ -0,0 +1,169 @@
+//! The `split_service` module splits the total amount of a [`Payment`](crate::entity::payment::Payment)
+//! between the involved persons according to a [`SplitMode`].
+
+use std::collections::HashMap;
+
+use bigdecimal::{BigDecimal, Signed};
+
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    entity::payment::SplitMode,
+};
+
+/// The minimum number of decimal places split amounts are rounded to.
+const SPLIT_MINIMUM_SCALE: i64 = 2;
+
+/// Splits the total amount according to the specified [`SplitMode`].
+/// The amounts are rounded down to cents and the remaining cents are distributed
+/// one by one to the persons with the largest rounding remainder, ties being resolved by name.
+/// This way the split amounts always add up to the total.
+///
+/// # Parameters
+///
+/// * `mode` - the mode to split the total with
+/// * `total` - the total amount to split
+pub fn split(
+    mode: &SplitMode,
+    total: &BigDecimal,
+) -> Result<HashMap<String, BigDecimal>, HomeworkError> {
+    if total.is_negative() {
+        return Err(HomeworkError::BadRequestError(InternalError::new(
+            "Negative amount",
+            format!("The total amount {} is negative.", total),
+            "Amounts must not be negative.",
+        )));
+    }
+    match mode {
+        SplitMode::Equal { persons } => {
+            let shares: Vec<(&str, BigDecimal)> = persons
+                .iter()
+                .map(|person| (person.as_str(), BigDecimal::from(1)))
+                .collect();
+            split_by_weights(&shares, total)
+        },
+        SplitMode::Shares { shares } => split_by_weights(&sorted_entries(shares), total),
+        SplitMode::Percentage { percentages } => {
+            let sum: BigDecimal = percentages.values().sum();
+            if sum != BigDecimal::from(100) {
+                return Err(HomeworkError::BadRequestError(InternalError::new(
+                    "Invalid percentages",
+                    format!("The percentages {:?} add up to {} instead of 100.", percentages, sum),
+                    "The percentages must add up to 100.",
+                )));
+            }
+            split_by_weights(&sorted_entries(percentages), total)
+        },
+        SplitMode::Exact { amounts } => {
+            let amounts = sorted_entries(amounts);
+            validate_persons(&amounts)?;
+            let sum: BigDecimal = amounts.iter().map(|(_, amount)| amount).sum();
+            if &sum != total {
+                return Err(HomeworkError::BadRequestError(InternalError::new(
+                    "Invalid amounts",
+                    format!("The amounts {:?} add up to {} instead of {}.", amounts, sum, total),
+                    "The exact amounts must add up to the total amount paid.",
+                )));
+            }
+            Ok(amounts
+                .into_iter()
+                .map(|(person, amount)| (person.trim().to_string(), amount))
+                .collect())
+        },
+    }
+}
+
+/// Splits the total proportionally to the specified weights.
+fn split_by_weights(
+    weights: &[(&str, BigDecimal)],
+    total: &BigDecimal,
+) -> Result<HashMap<String, BigDecimal>, HomeworkError> {
+    validate_persons(weights)?;
+    let weight_sum: BigDecimal = weights.iter().map(|(_, weight)| weight).sum();
+    if !weight_sum.is_positive() {
+        return Err(HomeworkError::BadRequestError(InternalError::new(
+            "Invalid weights",
+            format!("The weights {:?} do not add up to a positive value.", weights),
+            "At least one person must have a positive share.",
+        )));
+    }
+    let scale = std::cmp::max(SPLIT_MINIMUM_SCALE, total.as_bigint_and_exponent().1);
+    let unit = BigDecimal::new(1.into(), scale);
+    let mut parts: Vec<(&str, BigDecimal, BigDecimal)> = weights
+        .iter()
+        .map(|(person, weight)| {
+            let exact = total * weight / &weight_sum;
+            let rounded = exact.with_scale(scale);
+            let remainder = &exact - &rounded;
+            (*person, rounded, remainder)
+        })
+        .collect();
+    let distributed: BigDecimal = parts.iter().map(|(_, rounded, _)| rounded).sum();
+    let mut undistributed = total - distributed;
+    // Persons with the largest remainder receive the remaining units first.
+    parts.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+    for (_, rounded, _) in parts.iter_mut() {
+        if !undistributed.is_positive() {
+            break;
+        }
+        *rounded += &unit;
+        undistributed -= &unit;
+    }
+    Ok(parts
+        .into_iter()
+        .map(|(person, rounded, _)| (person.trim().to_string(), rounded))
+        .collect())
+}
+
+/// Returns the entries of the map sorted by name.
+fn sorted_entries(map: &HashMap<String, BigDecimal>) -> Vec<(&str, BigDecimal)> {
+    let mut entries: Vec<(&str, BigDecimal)> = map
+        .iter()
+        .map(|(person, value)| (person.as_str(), value.clone()))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Validates that at least one person is specified, that no person is specified twice
+/// or has an empty name and that no value is negative.
+fn validate_persons(entries: &[(&str, BigDecimal)]) -> Result<(), HomeworkError> {
+    if entries.is_empty() {
+        return Err(HomeworkError::BadRequestError(InternalError::new(
+            "No persons",
+            "A split without any persons was requested.",
+            "At least one person must be involved.",
+        )));
+    }
+    let mut persons = Vec::with_capacity(entries.len());
+    for (person, value) in entries {
+        let person = person.trim();
+        if person.is_empty() || persons.contains(&person) {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Invalid person",
+                format!("The person \"{}\" is empty or was supplied multiple times.", person),
+                "Every person must have a name and must only be supplied once.",
+            )));
+        }
+        if value.is_negative() {
+            return Err(HomeworkError::BadRequestError(InternalError::new(
+                "Negative value",
+                format!("The value {} of {} is negative.", value, person),
+                "Split values must not be negative.",
+            )));
+        }
+        persons.push(person);
+    }
+    Ok(())
+}
+
+/// Returns the total of the specified amounts.
+///
+/// # Parameters
+///
+/// * `amounts` - the amounts to add up
+pub fn total(amounts: &HashMap<String, BigDecimal>) -> BigDecimal {
+    amounts.values().sum()
+}
+
+#[cfg(test)]
+mod test;
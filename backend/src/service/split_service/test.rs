@@ -0,0 +1,96 @@
+use std::str::FromStr;
+
+use super::*;
+
+/// Creates a map from the specified person and value pairs.
+fn values(entries: &[(&str, &str)]) -> HashMap<String, BigDecimal> {
+    entries
+        .iter()
+        .map(|(person, value)| (person.to_string(), BigDecimal::from_str(value).unwrap()))
+        .collect()
+}
+
+#[test]
+/// Tests if an equal split distributes the remaining cents deterministically.
+fn test_split_equal() {
+    let mode = SplitMode::Equal {
+        persons: vec!["Carl".to_string(), "Anna".to_string(), "Ben".to_string()],
+    };
+    let total = BigDecimal::from_str("97.30").unwrap();
+    let split_amounts = split(&mode, &total).unwrap();
+    assert_eq!(split_amounts, values(&[("Anna", "32.44"), ("Ben", "32.43"), ("Carl", "32.43")]));
+    assert_eq!(split_amounts.values().sum::<BigDecimal>(), total);
+}
+
+#[test]
+/// Tests if a split by shares is proportional to the weights.
+fn test_split_shares() {
+    let mode = SplitMode::Shares {
+        shares: values(&[("Anna", "2"), ("Ben", "1"), ("Carl", "0")]),
+    };
+    let total = BigDecimal::from_str("10.00").unwrap();
+    let split_amounts = split(&mode, &total).unwrap();
+    assert_eq!(split_amounts, values(&[("Anna", "6.67"), ("Ben", "3.33"), ("Carl", "0.00")]));
+}
+
+#[test]
+/// Tests if a split by percentage requires the percentages to add up to 100.
+fn test_split_percentage() {
+    let total = BigDecimal::from_str("20").unwrap();
+    let mode = SplitMode::Percentage {
+        percentages: values(&[("Anna", "25"), ("Ben", "75")]),
+    };
+    assert_eq!(split(&mode, &total).unwrap(), values(&[("Anna", "5.00"), ("Ben", "15.00")]));
+    let invalid_mode = SplitMode::Percentage {
+        percentages: values(&[("Anna", "25"), ("Ben", "70")]),
+    };
+    assert!(split(&invalid_mode, &total).is_err());
+}
+
+#[test]
+/// Tests if exact amounts must add up to the total.
+fn test_split_exact() {
+    let total = BigDecimal::from_str("12.40").unwrap();
+    let mode = SplitMode::Exact {
+        amounts: values(&[("Anna", "10.00"), ("Ben", "2.40")]),
+    };
+    assert_eq!(split(&mode, &total).unwrap(), values(&[("Anna", "10.00"), ("Ben", "2.40")]));
+    let invalid_mode = SplitMode::Exact {
+        amounts: values(&[("Anna", "10.00"), ("Ben", "2.00")]),
+    };
+    assert!(split(&invalid_mode, &total).is_err());
+}
+
+#[test]
+/// Tests if invalid splits are rejected.
+fn test_split_invalid() {
+    let total = BigDecimal::from_str("10").unwrap();
+    assert!(split(
+        &SplitMode::Equal {
+            persons: Vec::new()
+        },
+        &total
+    )
+    .is_err());
+    assert!(split(
+        &SplitMode::Equal {
+            persons: vec!["Anna".to_string(), "Anna ".to_string()]
+        },
+        &total
+    )
+    .is_err());
+    assert!(split(
+        &SplitMode::Shares {
+            shares: values(&[("Anna", "-1"), ("Ben", "2")])
+        },
+        &total
+    )
+    .is_err());
+    assert!(split(
+        &SplitMode::Shares {
+            shares: values(&[("Anna", "0")])
+        },
+        &total
+    )
+    .is_err());
+}
@@ -0,0 +1,182 @@
+//! The `sub_recipe_service` module resolves [`Ingredient`]s referencing other recipes,
+//! which are used as sub-recipes.
+
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::{
+    application::error::{HomeworkError, InternalError},
+    entity::{ingredient::Ingredient, recipe::Recipe},
+};
+
+use super::quantity_service::Fraction;
+
+/// Checks that the recipe can use the sub-recipe. The sub-recipe must exist and must not
+/// use the recipe itself, neither directly nor via further sub-recipes.
+///
+/// # Parameters
+///
+/// * `recipe_id` - the ID of the recipe containing the ingredient
+/// * `sub_recipe_id` - the ID of the recipe referenced by the ingredient
+/// * `connection` - the database connection
+pub fn validate_sub_recipe(
+    recipe_id: Uuid,
+    sub_recipe_id: Uuid,
+    connection: &Connection,
+) -> Result<(), HomeworkError> {
+    if !Recipe::exists_in_database_by_id(sub_recipe_id, connection)? {
+        return Err(HomeworkError::NotFoundError(InternalError::new(
+            "Reference error",
+            format!("The sub-recipe {} of recipe {} does not exist.", sub_recipe_id, recipe_id),
+            "The recipe referenced by the ingredient does not exist.",
+        )));
+    }
+    let cyclic: bool = connection.query_row(
+        "WITH RECURSIVE used_recipe(id) AS (
+                SELECT ?1
+                UNION
+                SELECT ingredient.recipe_reference
+                FROM ingredient
+                INNER JOIN used_recipe
+                    ON ingredient.recipe_id = used_recipe.id
+                WHERE ingredient.recipe_reference IS NOT NULL
+            )
+            SELECT EXISTS (SELECT 1 FROM used_recipe WHERE id = ?2)",
+        [sub_recipe_id, recipe_id],
+        |row| row.get(0),
+    )?;
+    if cyclic {
+        return Err(HomeworkError::BadRequestError(InternalError::new(
+            "Recipe cycle",
+            format!(
+                "The recipe {} cannot use the sub-recipe {} as the sub-recipe uses the recipe.",
+                recipe_id, sub_recipe_id
+            ),
+            "The recipe cannot be used as ingredient, as it uses this recipe itself.",
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the ingredients of the recipe scaled by the factor. Ingredients referencing
+/// a sub-recipe contain the ingredients of the sub-recipe, which are expanded recursively.
+/// If the sub-recipe specifies a number of servings, the amount of the referencing
+/// ingredient is interpreted as servings, otherwise as multiple of the sub-recipe.
+///
+/// # Parameters
+///
+/// * `recipe_id` - the ID of the recipe
+/// * `factor` - the factor to scale the ingredients with
+/// * `connection` - the database connection
+pub fn expand_sub_recipes(
+    recipe_id: Uuid,
+    factor: &Fraction,
+    connection: &Connection,
+) -> Result<Vec<Ingredient>, HomeworkError> {
+    expand_ingredients(recipe_id, factor, &mut Vec::new(), connection)
+}
+
+/// Replaces all ingredients referencing a sub-recipe by the ingredients of the sub-recipe.
+///
+/// # Parameters
+///
+/// * `ingredients` - the ingredients expanded by [`expand_sub_recipes`]
+pub fn flatten_sub_recipes(ingredients: Vec<Ingredient>) -> Vec<Ingredient> {
+    let mut flattened = Vec::new();
+    for ingredient in ingredients {
+        if ingredient.recipe_reference().is_some() {
+            flattened.extend(flatten_sub_recipes(ingredient.into_sub_ingredients()));
+        } else {
+            flattened.push(ingredient);
+        }
+    }
+    flattened
+}
+
+/// Expands the scaled ingredients of a recipe and its sub-recipes.
+///
+/// # Parameters
+///
+/// * `recipe_id` - the ID of the recipe
+/// * `factor` - the factor to scale the ingredients with
+/// * `path` - the IDs of the recipes using the recipe
+/// * `connection` - the database connection
+fn expand_ingredients(
+    recipe_id: Uuid,
+    factor: &Fraction,
+    path: &mut Vec<Uuid>,
+    connection: &Connection,
+) -> Result<Vec<Ingredient>, HomeworkError> {
+    // Databases of earlier versions may still contain cycles.
+    if path.contains(&recipe_id) {
+        return Err(HomeworkError::BadRequestError(InternalError::new(
+            "Recipe cycle",
+            format!("The recipe {} references itself via {:?}.", recipe_id, path),
+            "The recipe references itself via its sub-recipes.",
+        )));
+    }
+    Recipe::exists_in_database_by_id_throw_not_found(recipe_id, connection)?;
+    path.push(recipe_id);
+    let mut ingredients = Ingredient::select_from_database_by_recipe_id(recipe_id, connection)?;
+    ingredients.sort_by_key(|ingredient| ingredient.ordering());
+    for ingredient in ingredients.iter_mut() {
+        let sub_recipe = ingredient
+            .recipe_reference()
+            .map(|sub_recipe_id| {
+                sub_recipe_factor(ingredient, sub_recipe_id, factor, connection)
+                    .map(|sub_factor| (sub_recipe_id, sub_factor))
+            })
+            .transpose()?;
+        // Scale before adding the sub-ingredients, which are already scaled.
+        ingredient.scale(factor)?;
+        if let Some((sub_recipe_id, sub_factor)) = sub_recipe {
+            let sub_ingredients = expand_ingredients(sub_recipe_id, &sub_factor, path, connection)?;
+            ingredient.set_sub_ingredients(sub_ingredients);
+        }
+    }
+    path.pop();
+    Ok(ingredients)
+}
+
+/// Returns the factor the ingredients of the sub-recipe need to be scaled with.
+///
+/// # Parameters
+///
+/// * `ingredient` - the unscaled ingredient referencing the sub-recipe
+/// * `sub_recipe_id` - the ID of the sub-recipe
+/// * `factor` - the factor the recipe containing the ingredient is scaled with
+/// * `connection` - the database connection
+fn sub_recipe_factor(
+    ingredient: &Ingredient,
+    sub_recipe_id: Uuid,
+    factor: &Fraction,
+    connection: &Connection,
+) -> Result<Fraction, HomeworkError> {
+    // Prefer the upper bound of ranges to have enough of the sub-recipe.
+    let amount = ingredient
+        .quantity()
+        .map(|quantity| quantity.maximum().unwrap_or_else(|| quantity.minimum()))
+        .unwrap_or_else(|| Fraction::from_integer(1));
+    let servings = Recipe::select_from_database_by_id(sub_recipe_id, connection)?
+        .servings()
+        .unwrap_or(1);
+    Fraction::new(1, servings.into())
+        .and_then(|per_serving| amount.checked_mul(&per_serving))
+        .and_then(|sub_factor| factor.checked_mul(&sub_factor))
+        .ok_or_else(|| {
+            HomeworkError::BadRequestError(InternalError::new(
+                "Invalid scaling",
+                format!(
+                    "The sub-recipe {} of recipe {} cannot be scaled by {} times {}.",
+                    sub_recipe_id,
+                    ingredient.recipe_id(),
+                    factor,
+                    amount
+                ),
+                "The ingredient quantity is too large to be scaled.",
+            ))
+        })
+}
+
+#[cfg(test)]
+mod test;
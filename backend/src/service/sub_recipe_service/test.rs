@@ -0,0 +1,116 @@
+use serde_json::json;
+
+use crate::application::config::Configuration;
+
+use super::*;
+
+/// Creates an ingredient of the recipe, which optionally references a sub-recipe.
+fn ingredient(
+    recipe_id: Uuid,
+    ordering: u32,
+    amount: &str,
+    unit: &str,
+    text: &str,
+    recipe_reference: Option<Uuid>,
+) -> Ingredient {
+    serde_json::from_value(json!({
+        "id": Configuration::generate_uuid(),
+        "amount": amount,
+        "unit": unit,
+        "text": text,
+        "creationTime": "2022-01-01T00:00:00Z",
+        "recipeReference": recipe_reference,
+        "recipeId": recipe_id,
+        "ordering": ordering,
+    }))
+    .unwrap()
+}
+
+/// Creates a recipe with the number of servings.
+fn recipe(id: Uuid, title: &str, servings: Option<u32>, connection: &Connection) {
+    Recipe::insert_into_database_new_entry(id, title, connection).unwrap();
+    Recipe::update_in_database_servings(id, servings, connection).unwrap();
+}
+
+/// Returns the nesting depth, text and amount of all ingredients in depth-first order.
+fn describe(ingredients: Vec<Ingredient>, depth: usize) -> Vec<(usize, String, String)> {
+    let mut described = Vec::new();
+    for ingredient in ingredients {
+        described.push((depth, ingredient.text().to_string(), ingredient.amount().to_string()));
+        described.extend(describe(ingredient.into_sub_ingredients(), depth + 1));
+    }
+    described
+}
+
+#[test]
+/// Tests if the `validate_sub_recipe` function rejects direct and indirect cycles.
+fn test_validate_sub_recipe() {
+    let connection = Configuration::in_memory_database_connection();
+    let (pizza, dough, sauce) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+    for (id, title) in [(pizza, "Pizza"), (dough, "Dough"), (sauce, "Sauce")] {
+        recipe(id, title, None, &connection);
+    }
+    ingredient(pizza, 0, "1", "", "Dough", Some(dough))
+        .insert_into_database(&connection)
+        .unwrap();
+    ingredient(dough, 0, "1", "", "Sauce", Some(sauce))
+        .insert_into_database(&connection)
+        .unwrap();
+
+    assert!(validate_sub_recipe(pizza, sauce, &connection).is_ok());
+    assert!(matches!(
+        validate_sub_recipe(sauce, sauce, &connection),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    assert!(matches!(
+        validate_sub_recipe(dough, pizza, &connection),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    assert!(matches!(
+        validate_sub_recipe(sauce, pizza, &connection),
+        Err(HomeworkError::BadRequestError(_))
+    ));
+    assert!(matches!(
+        validate_sub_recipe(pizza, Uuid::from_u128(4), &connection),
+        Err(HomeworkError::NotFoundError(_))
+    ));
+}
+
+#[test]
+/// Tests if the `expand_sub_recipes` function nests the scaled ingredients of sub-recipes
+/// and the `flatten_sub_recipes` function replaces the referencing ingredients by them.
+fn test_expand_sub_recipes() {
+    let connection = Configuration::in_memory_database_connection();
+    let (pizza, dough, sauce) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+    recipe(pizza, "Pizza", Some(2), &connection);
+    recipe(dough, "Dough", Some(4), &connection);
+    recipe(sauce, "Sauce", None, &connection);
+    for ingredient in [
+        ingredient(pizza, 1, "100", "g", "Cheese", None),
+        ingredient(pizza, 0, "2", "", "Dough", Some(dough)),
+        ingredient(dough, 0, "400", "g", "Flour", None),
+        ingredient(dough, 1, "1/2", "", "Sauce", Some(sauce)),
+        ingredient(sauce, 0, "2", "", "Tomatoes", None),
+    ] {
+        ingredient.insert_into_database(&connection).unwrap();
+    }
+
+    let ingredients = expand_sub_recipes(pizza, &Fraction::from_integer(2), &connection).unwrap();
+    assert_eq!(
+        describe(ingredients, 0),
+        vec![
+            (0, "Dough".to_string(), "4".to_string()),
+            (1, "Flour".to_string(), "400".to_string()),
+            (1, "Sauce".to_string(), "1/2".to_string()),
+            (2, "Tomatoes".to_string(), "1".to_string()),
+            (0, "Cheese".to_string(), "200".to_string()),
+        ]
+    );
+
+    let ingredients = expand_sub_recipes(pizza, &Fraction::from_integer(1), &connection).unwrap();
+    let flattened: Vec<String> = flatten_sub_recipes(ingredients)
+        .into_iter()
+        .map(|ingredient| ingredient.text().to_string())
+        .collect();
+    assert_eq!(flattened, vec!["Flour", "Tomatoes", "Cheese"]);
+}
@@ -0,0 +1,226 @@
+//! The `unit_service` module converts the quantities of
+//! [`Ingredient`](crate::entity::ingredient::Ingredient)s between units of mass and volume.
+
+use serde::Deserialize;
+
+use super::quantity_service::{Fraction, Quantity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The physical dimension a [`Unit`] measures.
+pub enum Dimension {
+    Mass,
+    Volume,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A unit of mass or volume. Imperial volumes are US customary units.
+pub enum Unit {
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+    Millilitre,
+    Litre,
+    Teaspoon,
+    Tablespoon,
+    Cup,
+}
+
+impl Unit {
+    /// Parses the free-text unit of an ingredient ignoring case, surrounding whitespace
+    /// and a trailing dot. Returns `None` if the unit is unknown, e.g. `pinch`.
+    ///
+    /// # Parameters
+    ///
+    /// * `text` - the text to parse
+    pub fn parse(text: &str) -> Option<Unit> {
+        let text = text.trim().trim_end_matches('.').to_lowercase();
+        match text.as_str() {
+            "g" | "gr" | "gram" | "grams" | "gramm" => Some(Unit::Gram),
+            "kg" | "kilogram" | "kilograms" | "kilogramm" => Some(Unit::Kilogram),
+            "oz" | "ounce" | "ounces" => Some(Unit::Ounce),
+            "lb" | "lbs" | "pound" | "pounds" => Some(Unit::Pound),
+            "ml" | "millilitre" | "millilitres" | "milliliter" | "milliliters" => {
+                Some(Unit::Millilitre)
+            },
+            "l" | "litre" | "litres" | "liter" | "liters" => Some(Unit::Litre),
+            "tsp" | "teaspoon" | "teaspoons" | "tl" | "teelöffel" => Some(Unit::Teaspoon),
+            "tbsp" | "tablespoon" | "tablespoons" | "el" | "esslöffel" => Some(Unit::Tablespoon),
+            "cup" | "cups" | "tasse" | "tassen" => Some(Unit::Cup),
+            _ => None,
+        }
+    }
+
+    /// Returns the physical dimension this `Unit` measures.
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Gram | Unit::Kilogram | Unit::Ounce | Unit::Pound => Dimension::Mass,
+            Unit::Millilitre | Unit::Litre | Unit::Teaspoon | Unit::Tablespoon | Unit::Cup => {
+                Dimension::Volume
+            },
+        }
+    }
+
+    /// Returns the size of this `Unit` in grams or millilitres respectively.
+    fn base_factor(&self) -> Fraction {
+        let (numerator, denominator) = match self {
+            Unit::Gram | Unit::Millilitre => (1, 1),
+            Unit::Kilogram | Unit::Litre => (1000, 1),
+            Unit::Ounce => (28_349_523_125, 1_000_000_000),
+            Unit::Pound => (45_359_237, 100_000),
+            Unit::Teaspoon => (492_892_159_375, 100_000_000_000),
+            Unit::Tablespoon => (1_478_676_478_125, 100_000_000_000),
+            Unit::Cup => (2_365_882_365, 10_000_000),
+        };
+        Fraction::new(numerator, denominator).expect("The unit factors are valid fractions.")
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Unit::Gram => "g",
+                Unit::Kilogram => "kg",
+                Unit::Ounce => "oz",
+                Unit::Pound => "lb",
+                Unit::Millilitre => "ml",
+                Unit::Litre => "l",
+                Unit::Teaspoon => "tsp",
+                Unit::Tablespoon => "tbsp",
+                Unit::Cup => "cup",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The system of units quantities are displayed in.
+pub enum UnitSystem {
+    /// Grams, kilograms, millilitres and litres.
+    /// Volumes are converted into masses if the density is known.
+    Metric,
+    /// Ounces, pounds, teaspoons, tablespoons and cups.
+    /// Masses are converted into volumes if the density is known.
+    Imperial,
+    /// The units as entered.
+    #[default]
+    Original,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A [`Quantity`] of a [`Unit`].
+pub struct Measure {
+    quantity: Quantity,
+    unit: Unit,
+}
+
+impl Measure {
+    /// Creates a new `Measure`.
+    ///
+    /// # Parameters
+    ///
+    /// * `quantity` - the quantity
+    /// * `unit` - the unit of the quantity
+    pub fn new(quantity: Quantity, unit: Unit) -> Self {
+        Measure { quantity, unit }
+    }
+
+    /// Returns the quantity of this `Measure`.
+    pub fn quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    /// Returns the unit of this `Measure`.
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// Converts this `Measure` into the specified unit.
+    /// Volumes and masses are converted into each other using the density.
+    /// Returns `None` if the dimensions differ and no density is known
+    /// or if the result is too large to be represented.
+    ///
+    /// # Parameters
+    ///
+    /// * `unit` - the unit to convert to
+    /// * `density` - the density of the ingredient in grams per millilitre if known
+    pub fn convert(&self, unit: Unit, density: Option<&Fraction>) -> Option<Measure> {
+        let mut factor = self.unit.base_factor().checked_div(&unit.base_factor())?;
+        factor = match (self.unit.dimension(), unit.dimension()) {
+            (Dimension::Volume, Dimension::Mass) => factor.checked_mul(density?)?,
+            (Dimension::Mass, Dimension::Volume) => factor.checked_div(density?)?,
+            _ => factor,
+        };
+        Some(Measure {
+            quantity: self.quantity.checked_scale(&factor)?,
+            unit,
+        })
+    }
+
+    /// Adds the other `Measure` in the unit of this `Measure`, e.g. `200 g` and `0.3 kg` to `500 g`.
+    /// Returns `None` if the other measure cannot be converted into the unit of this measure
+    /// or if the result is too large to be represented.
+    ///
+    /// # Parameters
+    ///
+    /// * `other` - the measure to add
+    /// * `density` - the density of the ingredient in grams per millilitre if known
+    pub fn checked_add(&self, other: &Measure, density: Option<&Fraction>) -> Option<Measure> {
+        let other = other.convert(self.unit, density)?;
+        Some(Measure {
+            quantity: self.quantity.checked_add(&other.quantity)?,
+            unit: self.unit,
+        })
+    }
+
+    /// Converts this `Measure` into the most readable unit of the specified system,
+    /// e.g. `1500 g` into `1 1/2 kg`. Returns `None` if the result is too large to be represented.
+    ///
+    /// # Parameters
+    ///
+    /// * `system` - the system of units to convert to
+    /// * `density` - the density of the ingredient in grams per millilitre if known
+    pub fn convert_to_system(
+        &self,
+        system: UnitSystem,
+        density: Option<&Fraction>,
+    ) -> Option<Measure> {
+        let dimension = match (system, density) {
+            (UnitSystem::Metric, Some(_)) => Dimension::Mass,
+            (UnitSystem::Imperial, Some(_)) => Dimension::Volume,
+            _ => self.unit.dimension(),
+        };
+        // The units of a system and dimension ordered from the smallest to the largest.
+        let units: &[Unit] = match (system, dimension) {
+            (UnitSystem::Original, _) => return Some(*self),
+            (UnitSystem::Metric, Dimension::Mass) => &[Unit::Gram, Unit::Kilogram],
+            (UnitSystem::Metric, Dimension::Volume) => &[Unit::Millilitre, Unit::Litre],
+            (UnitSystem::Imperial, Dimension::Mass) => &[Unit::Ounce, Unit::Pound],
+            (UnitSystem::Imperial, Dimension::Volume) => {
+                &[Unit::Teaspoon, Unit::Tablespoon, Unit::Cup]
+            },
+        };
+        let mut measure = self.convert(units[0], density)?;
+        for unit in &units[1..] {
+            let converted = self.convert(*unit, density)?;
+            // Switch to the larger unit once at least a quarter cup or a whole unit is reached.
+            let threshold = if *unit == Unit::Cup {
+                Fraction::new(1, 4)
+            } else {
+                Fraction::new(1, 1)
+            }
+            .expect("The thresholds are valid fractions.");
+            if converted.quantity().minimum() >= threshold {
+                measure = converted;
+            }
+        }
+        Some(measure)
+    }
+}
+
+#[cfg(test)]
+mod test;
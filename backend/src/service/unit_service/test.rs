@@ -0,0 +1,85 @@
+use super::*;
+
+/// Creates a measure from the textual quantity and unit.
+fn measure(quantity: &str, unit: &str) -> Measure {
+    Measure::new(Quantity::parse(quantity).unwrap(), Unit::parse(unit).unwrap())
+}
+
+/// Returns the textual representation of the measure.
+fn display(measure: Option<Measure>) -> String {
+    let measure = measure.unwrap();
+    format!("{} {}", measure.quantity(), measure.unit())
+}
+
+#[test]
+/// Tests if the `parse` function recognises units ignoring case and abbreviation dots.
+fn test_parse_unit() {
+    assert_eq!(Unit::parse(" KG "), Some(Unit::Kilogram));
+    assert_eq!(Unit::parse("Tbsp."), Some(Unit::Tablespoon));
+    assert_eq!(Unit::parse("EL"), Some(Unit::Tablespoon));
+    assert_eq!(Unit::parse("cups"), Some(Unit::Cup));
+    assert_eq!(Unit::parse("pinch"), None);
+    assert_eq!(Unit::parse(""), None);
+}
+
+#[test]
+/// Tests if the `convert` function converts exactly within and across dimensions.
+fn test_convert() {
+    assert_eq!(display(measure("0,3", "kg").convert(Unit::Gram, None)), "300 g");
+    assert_eq!(display(measure("1", "lb").convert(Unit::Ounce, None)), "16 oz");
+    assert_eq!(display(measure("1", "cup").convert(Unit::Tablespoon, None)), "16 tbsp");
+    assert_eq!(display(measure("2", "tbsp").convert(Unit::Teaspoon, None)), "6 tsp");
+    assert_eq!(measure("1", "cup").convert(Unit::Gram, None), None);
+    let flour = Fraction::new(53, 100).unwrap();
+    assert_eq!(display(measure("100", "ml").convert(Unit::Gram, Some(&flour))), "53 g");
+    assert_eq!(display(measure("53", "g").convert(Unit::Litre, Some(&flour))), "1/10 l");
+}
+
+#[test]
+/// Tests if the `convert_to_system` function picks the most readable unit of the system.
+fn test_convert_to_system() {
+    assert_eq!(
+        display(measure("1500", "g").convert_to_system(UnitSystem::Metric, None)),
+        "1 1/2 kg"
+    );
+    assert_eq!(
+        display(measure("2", "cups").convert_to_system(UnitSystem::Metric, None)),
+        "473.18 ml"
+    );
+    assert_eq!(
+        display(measure("200", "g").convert_to_system(UnitSystem::Imperial, None)),
+        "7.05 oz"
+    );
+    assert_eq!(display(measure("1", "kg").convert_to_system(UnitSystem::Imperial, None)), "2.2 lb");
+    assert_eq!(
+        display(measure("10", "ml").convert_to_system(UnitSystem::Imperial, None)),
+        "2.03 tsp"
+    );
+    assert_eq!(
+        display(measure("3", "tbsp").convert_to_system(UnitSystem::Imperial, None)),
+        "3 tbsp"
+    );
+    assert_eq!(
+        display(measure("12", "tsp").convert_to_system(UnitSystem::Imperial, None)),
+        "1/4 cup"
+    );
+    assert_eq!(
+        display(measure("1-2", "cups").convert_to_system(UnitSystem::Original, None)),
+        "1-2 cup"
+    );
+}
+
+#[test]
+/// Tests if the `convert_to_system` function prefers mass in the metric and volume
+/// in the imperial system if the density is known.
+fn test_convert_to_system_with_density() {
+    let flour = Fraction::new(1, 2).unwrap();
+    assert_eq!(
+        display(measure("1", "cup").convert_to_system(UnitSystem::Metric, Some(&flour))),
+        "118.29 g"
+    );
+    assert_eq!(
+        display(measure("0,3", "kg").convert_to_system(UnitSystem::Imperial, Some(&flour))),
+        "2.54 cup"
+    );
+}